@@ -87,6 +87,14 @@ impl fmt::Debug for ObjectId {
     }
 }
 
+/// Serializes as `interface@id`, like `wl_surface@3`
+#[cfg(feature = "serde")]
+impl serde::Serialize for ObjectId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{}@{}", self.interface().name, self.protocol_id()))
+    }
+}
+
 impl ObjectId {
     /// Check if this is a null ID
     ///
@@ -14,6 +14,7 @@ pub static WL_DISPLAY_INTERFACE: Interface = Interface {
         MessageDesc {
             name: "sync",
             since: 1,
+            deprecated_since: None,
             is_destructor: false,
             signature: &[ArgumentType::NewId],
             child_interface: Some(&WL_CALLBACK_INTERFACE),
@@ -22,6 +23,7 @@ pub static WL_DISPLAY_INTERFACE: Interface = Interface {
         MessageDesc {
             name: "get_registry",
             since: 1,
+            deprecated_since: None,
             is_destructor: false,
             signature: &[ArgumentType::NewId],
             child_interface: Some(&WL_REGISTRY_INTERFACE),
@@ -32,6 +34,7 @@ pub static WL_DISPLAY_INTERFACE: Interface = Interface {
         MessageDesc {
             name: "error",
             since: 1,
+            deprecated_since: None,
             is_destructor: false,
             signature: &[
                 ArgumentType::Object(AllowNull::No),
@@ -44,6 +47,7 @@ pub static WL_DISPLAY_INTERFACE: Interface = Interface {
         MessageDesc {
             name: "delete_id",
             since: 1,
+            deprecated_since: None,
             is_destructor: false,
             signature: &[ArgumentType::Uint],
             child_interface: None,
@@ -60,6 +64,7 @@ pub static WL_REGISTRY_INTERFACE: Interface = Interface {
     requests: &[MessageDesc {
         name: "bind",
         since: 1,
+        deprecated_since: None,
         is_destructor: false,
         signature: &[
             ArgumentType::Uint,
@@ -74,6 +79,7 @@ pub static WL_REGISTRY_INTERFACE: Interface = Interface {
         MessageDesc {
             name: "global",
             since: 1,
+            deprecated_since: None,
             is_destructor: false,
             signature: &[ArgumentType::Uint, ArgumentType::Str(AllowNull::No), ArgumentType::Uint],
             child_interface: None,
@@ -82,6 +88,7 @@ pub static WL_REGISTRY_INTERFACE: Interface = Interface {
         MessageDesc {
             name: "global_remove",
             since: 1,
+            deprecated_since: None,
             is_destructor: false,
             signature: &[ArgumentType::Uint],
             child_interface: None,
@@ -99,6 +106,7 @@ pub static WL_CALLBACK_INTERFACE: Interface = Interface {
     events: &[MessageDesc {
         name: "done",
         since: 1,
+        deprecated_since: None,
         is_destructor: true,
         signature: &[ArgumentType::Uint],
         child_interface: None,
@@ -6,7 +6,7 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::protocol::Argument;
+use crate::protocol::{Argument, MessageDesc};
 
 /// The `WAYLAND_DEBUG` env variable is set to debug client.
 pub fn has_debug_client_env() -> bool {
@@ -56,6 +56,18 @@ pub fn print_send_message<Id: Display, Fd: AsRawFd>(
     eprintln!();
 }
 
+/// Print a warning to stderr if the message is deprecated at the version of the object, in a
+/// following format:
+///
+/// [timestamp] !! interface.msg_name is deprecated since version N
+#[cfg_attr(coverage, coverage(off))]
+pub fn print_deprecated_message(interface: &str, msg: &MessageDesc, version: u32) {
+    if let Some(since) = msg.deprecated_since.filter(|&since| version >= since) {
+        print_timestamp();
+        eprintln!(" !! {}.{} is deprecated since version {}", interface, msg.name, since);
+    }
+}
+
 pub(crate) struct DisplaySlice<'a, D>(pub &'a [D]);
 
 impl<'a, D: Display> Display for DisplaySlice<'a, D> {
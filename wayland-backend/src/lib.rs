@@ -50,6 +50,11 @@
 /// Reexport of the `smallvec` crate, which is part of `wayland-backend`'s public API.
 pub extern crate smallvec;
 
+// used by the code generated by `wayland-scanner` with the `serde` option
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde;
+
 /// Helper macro for quickly making a [`Message`](crate::protocol::Message)
 #[macro_export]
 macro_rules! message {
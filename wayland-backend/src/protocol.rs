@@ -161,8 +161,21 @@ impl std::fmt::Display for Interface {
     }
 }
 
+/// Serializes as the name of the interface
+#[cfg(feature = "serde")]
+impl serde::Serialize for Interface {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
 /// Wire metadata of a given message
+///
+/// This type is non-exhaustive so that new metadata can be added without breaking the code generated
+/// by `wayland-scanner`, it is created with [`MessageDesc::new()`], which is usable in `static`
+/// initializers.
 #[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
 pub struct MessageDesc {
     /// Name of this message
     pub name: &'static str,
@@ -170,6 +183,8 @@ pub struct MessageDesc {
     pub signature: &'static [ArgumentType],
     /// Minimum required version of the interface
     pub since: u32,
+    /// Version of the interface from which this message is deprecated, if any
+    pub deprecated_since: Option<u32>,
     /// Whether this message is a destructor
     pub is_destructor: bool,
     /// The child interface created from this message.
@@ -180,6 +195,33 @@ pub struct MessageDesc {
     pub arg_interfaces: &'static [&'static Interface],
 }
 
+impl MessageDesc {
+    /// Create the description of a message that is not deprecated
+    pub const fn new(
+        name: &'static str,
+        signature: &'static [ArgumentType],
+        since: u32,
+        is_destructor: bool,
+        child_interface: Option<&'static Interface>,
+        arg_interfaces: &'static [&'static Interface],
+    ) -> MessageDesc {
+        MessageDesc {
+            name,
+            signature,
+            since,
+            deprecated_since: None,
+            is_destructor,
+            child_interface,
+            arg_interfaces,
+        }
+    }
+
+    /// Set the version of the interface from which this message is deprecated
+    pub const fn deprecated_since(self, version: u32) -> MessageDesc {
+        MessageDesc { deprecated_since: Some(version), ..self }
+    }
+}
+
 /// Special interface representing an anonymous object
 pub static ANONYMOUS_INTERFACE: Interface =
     Interface { name: "<anonymous>", version: 0, requests: &[], events: &[], c_ptr: None };
@@ -270,6 +312,28 @@ pub fn same_interface(a: &'static Interface, b: &'static Interface) -> bool {
     std::ptr::eq(a, b) || a.name == b.name
 }
 
+/// The number of bytes per pixel of a single-plane RGB format of `wl_shm`
+///
+/// `format` is a value of the `wl_shm.format` enum. This is `None` for the other formats, such as the YUV
+/// and multi-planar ones, and for unknown formats.
+pub fn wl_shm_bytes_per_pixel(format: u32) -> Option<u32> {
+    // argb8888 and xrgb8888 are 0 and 1, the other formats are DRM fourcc codes
+    if format <= 1 {
+        return Some(4);
+    }
+    match &format.to_le_bytes() {
+        b"C8  " | b"RGB8" | b"BGR8" | b"R8  " => Some(1),
+        b"XR12" | b"XB12" | b"RX12" | b"BX12" | b"AR12" | b"AB12" | b"RA12" | b"BA12" | b"XR15"
+        | b"XB15" | b"RX15" | b"BX15" | b"AR15" | b"AB15" | b"RA15" | b"BA15" | b"RG16"
+        | b"BG16" | b"R16 " | b"RG88" | b"GR88" => Some(2),
+        b"RG24" | b"BG24" => Some(3),
+        b"XB24" | b"RX24" | b"BX24" | b"AB24" | b"RA24" | b"BA24" | b"XR30" | b"XB30" | b"RX30"
+        | b"BX30" | b"AR30" | b"AB30" | b"RA30" | b"BA30" | b"RG32" | b"GR32" => Some(4),
+        b"XR4H" | b"XB4H" | b"AR4H" | b"AB4H" => Some(8),
+        _ => None,
+    }
+}
+
 pub(crate) fn check_for_signature<Id, Fd>(
     signature: &[ArgumentType],
     args: &[Argument<Id, Fd>],
@@ -331,6 +395,17 @@ impl<T> WEnum<T> {
     }
 }
 
+/// Serializes as the interpreted value, or as the raw `u32` value if it is unknown
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for WEnum<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Value(v) => v.serialize(serializer),
+            Self::Unknown(value) => serializer.serialize_u32(*value),
+        }
+    }
+}
+
 impl<T> From<WEnum<T>> for Result<T, WEnumError> {
     fn from(me: WEnum<T>) -> Self {
         me.into_result()
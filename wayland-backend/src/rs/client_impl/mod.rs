@@ -426,6 +426,7 @@ impl InnerBackend {
                 &args,
                 false,
             );
+            debug::print_deprecated_message(object.interface.name, message_desc, object.version);
         }
         #[cfg(feature = "log")]
         crate::log_debug!("Sending {}.{} ({})", id, message_desc.name, debug::DisplaySlice(&args));
@@ -760,6 +761,11 @@ fn dispatch_events(state: Arc<ConnectionState>) -> Result<usize, WaylandError> {
                 message_desc.name,
                 &args,
             );
+            debug::print_deprecated_message(
+                receiver.interface.name,
+                message_desc,
+                receiver.version,
+            );
         }
 
         // If this event is send to an already destroyed object (by the client), swallow it
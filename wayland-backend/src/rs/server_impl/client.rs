@@ -14,7 +14,7 @@ use crate::{
         INLINE_ARGS,
     },
     rs::map::SERVER_ID_LIMIT,
-    types::server::{DisconnectReason, InvalidId},
+    types::server::{ClientStats, DisconnectReason, InvalidId},
 };
 
 use smallvec::SmallVec;
@@ -50,7 +50,11 @@ pub(crate) struct Client<D: 'static> {
     last_serial: u32,
     pub(crate) id: InnerClientId,
     pub(crate) killed: bool,
+    pub(crate) suspended: bool,
+    pub(crate) poll_rearmed: bool,
     pub(crate) data: Arc<dyn ClientData>,
+    // only the message counters and dispatch time are tracked here, see `Client::stats()`
+    pub(crate) stats: ClientStats,
 }
 
 impl<D> Client<D> {
@@ -81,7 +85,18 @@ impl<D> Client<D> {
 
         data.initialized(ClientId { id: id.clone() });
 
-        Self { socket, map, debug, id, killed: false, last_serial: 0, data }
+        Self {
+            socket,
+            map,
+            debug,
+            id,
+            killed: false,
+            suspended: false,
+            poll_rearmed: false,
+            last_serial: 0,
+            data,
+            stats: ClientStats::default(),
+        }
     }
 
     pub(crate) fn create_object(
@@ -143,6 +158,7 @@ impl<D> Client<D> {
                 &args,
                 false,
             );
+            debug::print_deprecated_message(object.interface.name, message_desc, object.version);
         }
 
         let mut msg_args = SmallVec::with_capacity(args.len());
@@ -196,6 +212,7 @@ impl<D> Client<D> {
         if self.socket.write_message(&msg).is_err() {
             self.kill(DisconnectReason::ConnectionClosed);
         }
+        self.stats.events += 1;
 
         // Handle destruction if relevant
         if message_desc.is_destructor {
@@ -216,6 +233,7 @@ impl<D> Client<D> {
             if self.socket.write_message(&msg).is_err() {
                 self.kill(DisconnectReason::ConnectionClosed);
             }
+            self.stats.events += 1;
         }
         self.map.remove(object_id.id);
     }
@@ -318,6 +336,27 @@ impl<D> Client<D> {
         self.socket.flush()
     }
 
+    pub(crate) fn stats(&self) -> ClientStats {
+        let traffic = self.socket.traffic();
+        let mut objects = std::collections::HashMap::new();
+        for (_, obj) in self.map.all_objects() {
+            *objects.entry(obj.interface.name).or_insert(0) += 1;
+        }
+        ClientStats {
+            bytes_in: traffic.bytes_in,
+            bytes_out: traffic.bytes_out,
+            fds_in: traffic.fds_in,
+            fds_out: traffic.fds_out,
+            objects,
+            ..self.stats.clone()
+        }
+    }
+
+    /// Whether some requests of this client were already read from its socket but not dispatched yet
+    pub(crate) fn has_pending_requests(&self) -> bool {
+        self.socket.has_incoming_data()
+    }
+
     pub(crate) fn all_objects(&self) -> impl Iterator<Item = ObjectId> + '_ {
         let client_id = self.id.clone();
         self.map.all_objects().map(move |(id, obj)| ObjectId {
@@ -362,14 +401,17 @@ impl<D> Client<D> {
             };
 
             let obj = self.map.find(msg.sender_id).unwrap();
+            self.stats.requests += 1;
 
             if self.debug {
+                let message_desc = obj.interface.requests.get(msg.opcode as usize).unwrap();
                 debug::print_dispatched_message(
                     obj.interface.name,
                     msg.sender_id,
-                    obj.interface.requests.get(msg.opcode as usize).unwrap().name,
+                    message_desc.name,
                     &msg.args,
                 );
+                debug::print_deprecated_message(obj.interface.name, message_desc, obj.version);
             }
 
             return Ok((msg, obj));
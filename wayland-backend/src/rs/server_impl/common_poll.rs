@@ -1,7 +1,9 @@
 use std::{
+    collections::HashMap,
     os::unix::io::AsRawFd,
     os::unix::io::{BorrowedFd, OwnedFd},
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
 use super::{
@@ -12,7 +14,7 @@ use crate::{
     core_interfaces::{WL_DISPLAY_INTERFACE, WL_REGISTRY_INTERFACE},
     protocol::{same_interface, Argument, Message},
     rs::map::Object,
-    types::server::InitError,
+    types::server::{DispatchBudget, InitError},
 };
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
@@ -31,6 +33,7 @@ use smallvec::SmallVec;
 #[derive(Debug)]
 pub struct InnerBackend<D: 'static> {
     state: Arc<Mutex<State<D>>>,
+    budget: DispatchBudget,
 }
 
 impl<D> InnerBackend<D> {
@@ -49,7 +52,10 @@ impl<D> InnerBackend<D> {
         ))]
         let poll_fd = kqueue().map_err(Into::into).map_err(InitError::Io)?;
 
-        Ok(Self { state: Arc::new(Mutex::new(State::new(poll_fd))) })
+        Ok(Self {
+            state: Arc::new(Mutex::new(State::new(poll_fd))),
+            budget: DispatchBudget::default(),
+        })
     }
 
     pub fn flush(&self, client: Option<ClientId>) -> std::io::Result<()> {
@@ -67,43 +73,80 @@ impl<D> InnerBackend<D> {
         unsafe { BorrowedFd::borrow_raw(raw_fd) }
     }
 
+    pub fn set_dispatch_budget(&mut self, budget: DispatchBudget) {
+        self.budget = budget;
+    }
+
     pub fn dispatch_client(
         &self,
         data: &mut D,
         client_id: InnerClientId,
     ) -> std::io::Result<usize> {
-        let ret = self.dispatch_events_for(data, client_id);
+        let ret = self.dispatch_events_for(data, client_id.clone(), DispatchLimit::NONE);
+        self.state.lock().unwrap().reset_client_poll(client_id);
         let cleanup = self.state.lock().unwrap().cleanup();
         cleanup(&self.handle(), data);
         ret
     }
 
-    #[cfg(any(target_os = "linux", target_os = "android"))]
     pub fn dispatch_all_clients(&self, data: &mut D) -> std::io::Result<usize> {
-        use std::os::unix::io::AsFd;
-
-        let poll_fd = self.poll_fd();
-        let mut dispatched = 0;
+        let mut scheduler = Scheduler::new(self.budget);
         loop {
-            let mut events = epoll::EventVec::with_capacity(32);
-            epoll::wait(poll_fd.as_fd(), &mut events, 0)?;
+            let ready = self.poll_ready_clients()?;
 
-            if events.is_empty() {
+            if ready.is_empty() {
                 break;
             }
 
-            for event in events.iter() {
-                let id = InnerClientId::from_u64(event.data.u64());
-                // remove the cb while we call it, to gracefully handle reentrancy
-                if let Ok(count) = self.dispatch_events_for(data, id) {
-                    dispatched += count;
-                }
-            }
+            let progress = self.dispatch_ready_clients(data, ready, &mut scheduler);
             let cleanup = self.state.lock().unwrap().cleanup();
             cleanup(&self.handle(), data);
+
+            if !progress || scheduler.out_of_time() {
+                break;
+            }
         }
 
-        Ok(dispatched)
+        // suspended clients that hung up were killed without being reported as ready
+        let cleanup = self.state.lock().unwrap().cleanup();
+        cleanup(&self.handle(), data);
+
+        // make sure the poll fd signals again the clients we did not fully process
+        let mut state = self.state.lock().unwrap();
+        for id in scheduler.leftover {
+            state.reset_client_poll(id.clone());
+            if state
+                .clients
+                .get_client(id.clone())
+                .map(|c| c.has_pending_requests())
+                .unwrap_or(false)
+            {
+                state.rearm_client_poll(id);
+            }
+        }
+
+        Ok(scheduler.dispatched)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn poll_ready_clients(&self) -> std::io::Result<Vec<InnerClientId>> {
+        use std::os::unix::io::AsFd;
+
+        let poll_fd = self.poll_fd();
+        let mut events = epoll::EventVec::with_capacity(32);
+        epoll::wait(poll_fd.as_fd(), &mut events, 0)?;
+
+        let mut state = self.state.lock().unwrap();
+        let mut ready = Vec::with_capacity(events.len());
+        for event in events.iter() {
+            let id = InnerClientId::from_u64(event.data.u64());
+            let flags = event.flags;
+            let hangup = flags.intersects(epoll::EventFlags::HUP | epoll::EventFlags::ERR);
+            if state.check_suspended_client(id.clone(), hangup) {
+                ready.push(id);
+            }
+        }
+        Ok(ready)
     }
 
     #[cfg(any(
@@ -113,45 +156,96 @@ impl<D> InnerBackend<D> {
         target_os = "openbsd",
         target_os = "macos"
     ))]
-    pub fn dispatch_all_clients(&self, data: &mut D) -> std::io::Result<usize> {
+    fn poll_ready_clients(&self) -> std::io::Result<Vec<InnerClientId>> {
         use std::time::Duration;
 
         let poll_fd = self.poll_fd();
-        let mut dispatched = 0;
-        loop {
-            let mut events = Vec::with_capacity(32);
-            let nevents = unsafe { kevent(&poll_fd, &[], &mut events, Some(Duration::ZERO))? };
+        let mut events = Vec::with_capacity(32);
+        let nevents = unsafe { kevent(&poll_fd, &[], &mut events, Some(Duration::ZERO))? };
 
-            if nevents == 0 {
-                break;
+        let mut state = self.state.lock().unwrap();
+        let mut ready: Vec<InnerClientId> = Vec::with_capacity(nevents);
+        for event in events.iter().take(nevents) {
+            let id = InnerClientId::from_u64(event.udata() as u64);
+            let hangup = event.flags().intersects(EventFlags::EOF | EventFlags::ERROR);
+            // a client may be reported twice if both its read and write filters triggered
+            if !ready.contains(&id) && state.check_suspended_client(id.clone(), hangup) {
+                ready.push(id);
             }
+        }
+        Ok(ready)
+    }
 
-            for event in events.iter().take(nevents) {
-                let id = InnerClientId::from_u64(event.udata() as u64);
-                // remove the cb while we call it, to gracefully handle reentrancy
-                if let Ok(count) = self.dispatch_events_for(data, id) {
-                    dispatched += count;
+    /// Dispatch the requests of the given ready clients, within the limits of the scheduler
+    ///
+    /// Returns whether any request was dispatched.
+    fn dispatch_ready_clients(
+        &self,
+        data: &mut D,
+        ready: Vec<InnerClientId>,
+        scheduler: &mut Scheduler,
+    ) -> bool {
+        let mut progress = false;
+        let mut active = Vec::with_capacity(ready.len());
+        for id in ready {
+            if scheduler.remaining_for(&id) == 0 {
+                scheduler.leftover.push(id);
+            } else {
+                active.push(id);
+            }
+        }
+
+        while !active.is_empty() {
+            let mut still_active = Vec::with_capacity(active.len());
+            for id in active {
+                if scheduler.out_of_time() {
+                    scheduler.leftover.push(id);
+                    continue;
+                }
+                let limit = scheduler.limit_for(&id);
+                match self.dispatch_events_for(data, id.clone(), limit) {
+                    Ok(count) => {
+                        scheduler.record(&id, count);
+                        progress |= count > 0;
+                        if !limit.reached(count) {
+                            // all pending requests of this client were processed
+                            self.state.lock().unwrap().reset_client_poll(id);
+                        } else if scheduler.remaining_for(&id) == 0 || scheduler.out_of_time() {
+                            scheduler.leftover.push(id);
+                        } else {
+                            still_active.push(id);
+                        }
+                    }
+                    Err(_) => self.state.lock().unwrap().reset_client_poll(id),
                 }
             }
-            let cleanup = self.state.lock().unwrap().cleanup();
-            cleanup(&self.handle(), data);
+            active = still_active;
         }
 
-        Ok(dispatched)
+        progress
     }
 
     pub(crate) fn dispatch_events_for(
         &self,
         data: &mut D,
         client_id: InnerClientId,
+        limit: DispatchLimit,
     ) -> std::io::Result<usize> {
         let mut dispatched = 0;
         let handle = self.handle();
         let mut state = self.state.lock().unwrap();
         loop {
+            if limit.reached(dispatched) {
+                break;
+            }
             let action = {
                 let state = &mut *state;
                 if let Ok(client) = state.clients.get_client_mut(client_id.clone()) {
+                    if client.suspended {
+                        // the client was suspended, possibly by one of its own requests, leave the
+                        // remaining requests buffered until it is resumed
+                        break;
+                    }
                     let (message, object) = match client.next_request() {
                         Ok(v) => v,
                         Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -216,6 +310,7 @@ impl<D> InnerBackend<D> {
                 } => {
                     // temporarily unlock the state Mutex while this request is dispatched
                     std::mem::drop(state);
+                    let start = Instant::now();
                     let ret = object.data.user_data.clone().request(
                         &handle.clone(),
                         data,
@@ -226,6 +321,7 @@ impl<D> InnerBackend<D> {
                             args: arguments,
                         },
                     );
+                    let elapsed = start.elapsed();
                     if is_destructor {
                         object.data.user_data.clone().destroyed(
                             &handle.clone(),
@@ -236,6 +332,9 @@ impl<D> InnerBackend<D> {
                     }
                     // acquire the lock again and continue
                     state = self.state.lock().unwrap();
+                    if let Ok(client) = state.clients.get_client_mut(client_id.clone()) {
+                        client.stats.dispatch_time += elapsed;
+                    }
                     if is_destructor {
                         if let Ok(client) = state.clients.get_client_mut(client_id.clone()) {
                             client.send_delete_id(object_id);
@@ -306,3 +405,71 @@ enum DispatchAction<D: 'static> {
         handler: Arc<dyn GlobalHandler<D>>,
     },
 }
+
+/// Bounds on the number of requests dispatched by a single call to `dispatch_events_for()`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DispatchLimit {
+    max_requests: usize,
+    deadline: Option<Instant>,
+}
+
+impl DispatchLimit {
+    pub(crate) const NONE: DispatchLimit =
+        DispatchLimit { max_requests: usize::MAX, deadline: None };
+
+    // the deadline is only checked after the first request, so that some progress is always made
+    fn reached(&self, dispatched: usize) -> bool {
+        dispatched >= self.max_requests
+            || (dispatched > 0 && self.deadline.map(|d| Instant::now() >= d).unwrap_or(false))
+    }
+}
+
+/// Book-keeping of the [`DispatchBudget`] during a call to `dispatch_all_clients()`
+struct Scheduler {
+    budget: DispatchBudget,
+    deadline: Option<Instant>,
+    dispatched: usize,
+    per_client: HashMap<InnerClientId, usize>,
+    // clients that still had pending requests when their budget was exhausted
+    leftover: Vec<InnerClientId>,
+}
+
+impl Scheduler {
+    fn new(budget: DispatchBudget) -> Self {
+        Self {
+            budget,
+            deadline: budget.time.map(|time| Instant::now() + time),
+            dispatched: 0,
+            per_client: HashMap::new(),
+            leftover: Vec::new(),
+        }
+    }
+
+    // like `DispatchLimit::reached()`, at least one request is dispatched before running out of time
+    fn out_of_time(&self) -> bool {
+        self.dispatched > 0 && self.deadline.map(|d| Instant::now() >= d).unwrap_or(false)
+    }
+
+    fn remaining_for(&self, id: &InnerClientId) -> usize {
+        match self.budget.requests_per_client {
+            Some(max) => max.get().saturating_sub(self.per_client.get(id).copied().unwrap_or(0)),
+            None => usize::MAX,
+        }
+    }
+
+    fn limit_for(&self, id: &InnerClientId) -> DispatchLimit {
+        let max_requests = if self.budget.round_robin {
+            self.remaining_for(id).min(1)
+        } else {
+            self.remaining_for(id)
+        };
+        DispatchLimit { max_requests, deadline: self.deadline }
+    }
+
+    fn record(&mut self, id: &InnerClientId, count: usize) {
+        self.dispatched += count;
+        if self.budget.requests_per_client.is_some() {
+            *self.per_client.entry(id.clone()).or_insert(0) += count;
+        }
+    }
+}
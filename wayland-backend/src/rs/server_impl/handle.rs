@@ -7,7 +7,7 @@ use std::{
 
 use crate::{
     protocol::{same_interface, Interface, Message, ObjectInfo, ANONYMOUS_INTERFACE},
-    types::server::{DisconnectReason, GlobalInfo, InvalidId},
+    types::server::{ClientStats, DisconnectReason, GlobalInfo, InvalidId},
 };
 
 use super::{
@@ -67,6 +67,99 @@ impl<D> State<D> {
             Ok(())
         }
     }
+
+    /// Request the poll fd to report this client as ready even if its socket has no new data
+    ///
+    /// This is used when some requests are already buffered in the client socket but were not dispatched,
+    /// as otherwise the level-triggered poll fd would not signal them again.
+    pub(crate) fn rearm_client_poll(&mut self, client_id: InnerClientId) {
+        let client = match self.clients.get_client_mut(client_id.clone()) {
+            Ok(client) => client,
+            Err(InvalidId) => return,
+        };
+        if client.suspended || client.poll_rearmed {
+            return;
+        }
+        client.poll_rearmed = true;
+
+        // The socket is almost always writable, so watching for writability forces an early wakeup.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            use rustix::event::epoll;
+            let _ = epoll::modify(
+                &self.poll_fd,
+                &*client,
+                epoll::EventData::new_u64(client_id.as_u64()),
+                epoll::EventFlags::IN | epoll::EventFlags::OUT | epoll::EventFlags::ONESHOT,
+            );
+        }
+
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "macos"
+        ))]
+        {
+            use rustix::event::kqueue::*;
+            use std::os::unix::io::{AsFd, AsRawFd};
+
+            let evt = Event::new(
+                EventFilter::Write(client.as_fd().as_raw_fd()),
+                EventFlags::ADD | EventFlags::ONESHOT | EventFlags::RECEIPT,
+                client_id.as_u64() as isize,
+            );
+
+            let mut events = Vec::new();
+            let _ = unsafe { kevent(&self.poll_fd, &[evt], &mut events, None) };
+        }
+    }
+
+    /// Handle a client reported by the poll fd, returns whether its requests should be dispatched
+    ///
+    /// Only hangups and errors are monitored for suspended clients, in which case they are killed.
+    pub(crate) fn check_suspended_client(
+        &mut self,
+        client_id: InnerClientId,
+        hangup: bool,
+    ) -> bool {
+        let client = match self.clients.get_client_mut(client_id) {
+            Ok(client) => client,
+            Err(InvalidId) => return true,
+        };
+        if !client.suspended {
+            return true;
+        }
+        if hangup && !client.killed {
+            client.kill(DisconnectReason::ConnectionClosed);
+        }
+        false
+    }
+
+    /// Restore the normal poll registration of a client after [`State::rearm_client_poll`]
+    pub(crate) fn reset_client_poll(&mut self, client_id: InnerClientId) {
+        let client = match self.clients.get_client_mut(client_id.clone()) {
+            Ok(client) => client,
+            Err(InvalidId) => return,
+        };
+        if !client.poll_rearmed {
+            return;
+        }
+        client.poll_rearmed = false;
+
+        // kqueue oneshot filters are deleted once triggered, only epoll needs to be restored
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if !client.suspended {
+            use rustix::event::epoll;
+            let _ = epoll::modify(
+                &self.poll_fd,
+                &*client,
+                epoll::EventData::new_u64(client_id.as_u64()),
+                epoll::EventFlags::IN,
+            );
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -130,6 +223,14 @@ impl InnerHandle {
         self.state.lock().unwrap().with_all_clients(&mut f)
     }
 
+    pub fn client_stats(&self, id: InnerClientId) -> Result<ClientStats, InvalidId> {
+        self.state.lock().unwrap().client_stats(id)
+    }
+
+    pub fn all_client_stats(&self) -> Vec<(ClientId, ClientStats)> {
+        self.state.lock().unwrap().all_client_stats()
+    }
+
     pub fn with_all_objects_for(
         &self,
         client_id: InnerClientId,
@@ -215,6 +316,18 @@ impl InnerHandle {
         self.state.lock().unwrap().kill_client(client_id, reason)
     }
 
+    pub fn suspend_client(&self, client_id: InnerClientId) -> Result<(), InvalidId> {
+        self.state.lock().unwrap().suspend_client(client_id)
+    }
+
+    pub fn resume_client(&self, client_id: InnerClientId) -> Result<(), InvalidId> {
+        self.state.lock().unwrap().resume_client(client_id)
+    }
+
+    pub fn is_client_suspended(&self, client_id: InnerClientId) -> Result<bool, InvalidId> {
+        self.state.lock().unwrap().is_client_suspended(client_id)
+    }
+
     pub fn create_global<D: 'static>(
         &self,
         interface: &'static Interface,
@@ -277,6 +390,8 @@ pub(crate) trait ErasedState: downcast_rs::Downcast {
     fn get_client_data(&self, id: InnerClientId) -> Result<Arc<dyn ClientData>, InvalidId>;
     fn get_client_credentials(&self, id: InnerClientId) -> Result<Credentials, InvalidId>;
     fn with_all_clients(&self, f: &mut dyn FnMut(ClientId));
+    fn client_stats(&self, id: InnerClientId) -> Result<ClientStats, InvalidId>;
+    fn all_client_stats(&self) -> Vec<(ClientId, ClientStats)>;
     fn with_all_objects_for(
         &self,
         client_id: InnerClientId,
@@ -295,6 +410,9 @@ pub(crate) trait ErasedState: downcast_rs::Downcast {
     fn send_event(&mut self, msg: Message<ObjectId, RawFd>) -> Result<(), InvalidId>;
     fn post_error(&mut self, object_id: InnerObjectId, error_code: u32, message: CString);
     fn kill_client(&mut self, client_id: InnerClientId, reason: DisconnectReason);
+    fn suspend_client(&mut self, client_id: InnerClientId) -> Result<(), InvalidId>;
+    fn resume_client(&mut self, client_id: InnerClientId) -> Result<(), InvalidId>;
+    fn is_client_suspended(&self, client_id: InnerClientId) -> Result<bool, InvalidId>;
     fn global_info(&self, id: InnerGlobalId) -> Result<GlobalInfo, InvalidId>;
     fn flush(&mut self, client: Option<ClientId>) -> std::io::Result<()>;
 }
@@ -380,6 +498,17 @@ impl<D> ErasedState for State<D> {
         }
     }
 
+    fn client_stats(&self, id: InnerClientId) -> Result<ClientStats, InvalidId> {
+        Ok(self.clients.get_client(id)?.stats())
+    }
+
+    fn all_client_stats(&self) -> Vec<(ClientId, ClientStats)> {
+        self.clients
+            .all_clients_id()
+            .filter_map(|id| Some((id.clone(), self.clients.get_client(id.id).ok()?.stats())))
+            .collect()
+    }
+
     fn with_all_objects_for(
         &self,
         client_id: InnerClientId,
@@ -434,6 +563,128 @@ impl<D> ErasedState for State<D> {
             client.kill(reason)
         }
     }
+
+    fn suspend_client(&mut self, client_id: InnerClientId) -> Result<(), InvalidId> {
+        let client = self.clients.get_client_mut(client_id.clone())?;
+        if client.suspended {
+            return Ok(());
+        }
+        client.suspended = true;
+
+        // stop monitoring the client socket for incoming data, which stays buffered in the kernel until
+        // resumed, but keep watching for hangups so that a disconnection is noticed
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            use rustix::event::epoll;
+            client.poll_rearmed = false;
+            // EPOLLHUP and EPOLLERR are always reported, even if not requested
+            let _ = epoll::modify(
+                &self.poll_fd,
+                &*client,
+                epoll::EventData::new_u64(client_id.as_u64()),
+                epoll::EventFlags::empty(),
+            );
+        }
+
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "macos"
+        ))]
+        {
+            use rustix::event::kqueue::*;
+            use std::os::unix::io::{AsFd, AsRawFd};
+
+            // the read filter reports the end of the connection, make it edge-triggered so that the
+            // buffered data does not keep waking up the poll fd
+            let fd = client.as_fd().as_raw_fd();
+            let evts = [
+                Event::new(
+                    EventFilter::Read(fd),
+                    EventFlags::DELETE | EventFlags::RECEIPT,
+                    client_id.as_u64() as isize,
+                ),
+                Event::new(
+                    EventFilter::Read(fd),
+                    EventFlags::ADD | EventFlags::CLEAR | EventFlags::RECEIPT,
+                    client_id.as_u64() as isize,
+                ),
+            ];
+
+            let mut events = Vec::new();
+            let _ = unsafe { kevent(&self.poll_fd, &evts, &mut events, None) };
+        }
+
+        Ok(())
+    }
+
+    fn resume_client(&mut self, client_id: InnerClientId) -> Result<(), InvalidId> {
+        let client = self.clients.get_client_mut(client_id.clone())?;
+        if !client.suspended {
+            return Ok(());
+        }
+        client.suspended = false;
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let ret = {
+            use rustix::event::epoll;
+            epoll::modify(
+                &self.poll_fd,
+                &*client,
+                epoll::EventData::new_u64(client_id.as_u64()),
+                epoll::EventFlags::IN,
+            )
+        };
+
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "macos"
+        ))]
+        let ret = {
+            use rustix::event::kqueue::*;
+            use std::os::unix::io::{AsFd, AsRawFd};
+
+            // the edge-triggered flag of a filter cannot be changed, it needs to be re-created
+            let fd = client.as_fd().as_raw_fd();
+            let evts = [
+                Event::new(
+                    EventFilter::Read(fd),
+                    EventFlags::DELETE | EventFlags::RECEIPT,
+                    client_id.as_u64() as isize,
+                ),
+                Event::new(
+                    EventFilter::Read(fd),
+                    EventFlags::ADD | EventFlags::RECEIPT,
+                    client_id.as_u64() as isize,
+                ),
+            ];
+
+            let mut events = Vec::new();
+            unsafe { kevent(&self.poll_fd, &evts, &mut events, None).map(|_| ()) }
+        };
+
+        if ret.is_err() {
+            // the client can no longer be monitored, it would never be dispatched again
+            client.kill(DisconnectReason::ConnectionClosed);
+            return Ok(());
+        }
+
+        // requests that were read before the suspension will not be signaled by the socket again
+        if client.has_pending_requests() {
+            self.rearm_client_poll(client_id);
+        }
+
+        Ok(())
+    }
+
+    fn is_client_suspended(&self, client_id: InnerClientId) -> Result<bool, InvalidId> {
+        Ok(self.clients.get_client(client_id)?.suspended)
+    }
     fn global_info(&self, id: InnerGlobalId) -> Result<GlobalInfo, InvalidId> {
         self.registry.get_info(id)
     }
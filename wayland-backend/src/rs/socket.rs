@@ -123,6 +123,19 @@ impl AsRawFd for Socket {
  * BufferedSocket
  */
 
+/// Amount of data transferred through a socket
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Traffic {
+    /// Number of bytes received
+    pub bytes_in: u64,
+    /// Number of bytes sent
+    pub bytes_out: u64,
+    /// Number of file descriptors received
+    pub fds_in: u64,
+    /// Number of file descriptors sent
+    pub fds_out: u64,
+}
+
 /// An adapter around a raw Socket that directly handles buffering and
 /// conversion from/to wayland messages
 #[derive(Debug)]
@@ -132,6 +145,7 @@ pub struct BufferedSocket {
     in_fds: VecDeque<OwnedFd>,
     out_data: Buffer<u8>,
     out_fds: Vec<OwnedFd>,
+    traffic: Traffic,
 }
 
 impl BufferedSocket {
@@ -143,9 +157,15 @@ impl BufferedSocket {
             in_fds: VecDeque::new(),                 // able to store leftover data if needed
             out_data: Buffer::new(MAX_BYTES_OUT),
             out_fds: Vec::new(),
+            traffic: Traffic::default(),
         }
     }
 
+    /// Amount of data transferred through this socket so far
+    pub fn traffic(&self) -> Traffic {
+        self.traffic
+    }
+
     /// Flush the contents of the outgoing buffer into the socket
     pub fn flush(&mut self) -> IoResult<()> {
         let written = {
@@ -155,6 +175,8 @@ impl BufferedSocket {
             }
             self.socket.send_msg(bytes, &self.out_fds)?
         };
+        self.traffic.bytes_out += written as u64;
+        self.traffic.fds_out += self.out_fds.len() as u64;
         self.out_data.offset(written);
         self.out_data.move_to_front();
         self.out_fds.clear();
@@ -209,10 +231,13 @@ impl BufferedSocket {
         // reorganize the buffers
         self.in_data.move_to_front();
         // receive a message
+        let fds_before = self.in_fds.len();
         let in_bytes = {
             let bytes = self.in_data.get_writable_storage();
             self.socket.rcv_msg(bytes, &mut self.in_fds)?
         };
+        self.traffic.fds_in += (self.in_fds.len() - fds_before) as u64;
+        self.traffic.bytes_in += in_bytes as u64;
         if in_bytes == 0 {
             // the other end of the socket was closed
             return Err(rustix::io::Errno::PIPE.into());
@@ -257,6 +282,11 @@ impl BufferedSocket {
 
         Ok(msg)
     }
+
+    /// Check whether some data was already read from the socket but not parsed yet
+    pub fn has_incoming_data(&self) -> bool {
+        !self.in_data.get_contents().is_empty()
+    }
 }
 
 impl AsRawFd for BufferedSocket {
@@ -7,7 +7,9 @@ use std::{
 };
 
 use crate::protocol::{Interface, Message, ObjectInfo};
-pub use crate::types::server::{Credentials, DisconnectReason, GlobalInfo, InitError, InvalidId};
+pub use crate::types::server::{
+    ClientStats, Credentials, DisconnectReason, DispatchBudget, GlobalInfo, InitError, InvalidId,
+};
 
 use super::server_impl;
 
@@ -196,6 +198,14 @@ impl fmt::Debug for ObjectId {
     }
 }
 
+/// Serializes as `interface@id`, like `wl_surface@3`
+#[cfg(feature = "serde")]
+impl serde::Serialize for ObjectId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{}@{}", self.interface().name, self.protocol_id()))
+    }
+}
+
 /// An ID representing a Wayland client
 ///
 /// The backend internally tracks which IDs are still valid, invalidates them when the client they represent
@@ -226,6 +236,11 @@ impl fmt::Debug for GlobalId {
     }
 }
 
+/// A snapshot of the statistics of all the clients of a server
+///
+/// See [`Handle::stats()`].
+pub type ServerStats = crate::types::server::ServerStats<ClientId>;
+
 /// Main handle of a backend to the Wayland protocol
 ///
 /// This type hosts most of the protocol-related functionality of the backend, and is the
@@ -419,6 +434,73 @@ impl Handle {
         self.handle.kill_client(client_id.id, reason)
     }
 
+    /// Suspends the dispatching of the requests of a client, without disconnecting it.
+    ///
+    /// While a client is suspended, its socket is only monitored by the [`Backend::poll_fd`] for hangups, so
+    /// that its disconnection is still noticed, and the requests it sends are left buffered until it is
+    /// resumed with [`resume_client()`](Handle::resume_client). If this method is invoked from the handler of
+    /// one of the requests of this client, the dispatching stops right after this request, and resumes
+    /// exactly where it left off.
+    ///
+    /// Events can still be sent to a suspended client. They are not queued by the server but written to its
+    /// socket as usual, so the amount of pending events is bounded by the buffers of the socket: if the
+    /// client does not read its socket and these buffers are full, it is disconnected, in the same way as a
+    /// client that is not suspended.
+    ///
+    /// Suspending an already suspended client does nothing.
+    ///
+    /// **Note:** Suspending clients is not supported by the system backend, as libwayland provides no way to
+    /// stop monitoring the socket of a client. On this backend, this method does nothing: the requests of
+    /// the client keep being dispatched, and [`is_client_suspended()`](Handle::is_client_suspended) always
+    /// returns `false`.
+    #[inline]
+    pub fn suspend_client(&self, client_id: ClientId) -> Result<(), InvalidId> {
+        self.handle.suspend_client(client_id.id)
+    }
+
+    /// Resumes the dispatching of the requests of a client previously suspended with
+    /// [`suspend_client()`](Handle::suspend_client).
+    ///
+    /// If some requests of this client were left buffered, the [`Backend::poll_fd`] will become readable
+    /// again, so that they are processed by the next invocation of [`Backend::dispatch_all_clients`].
+    ///
+    /// Resuming a client that is not suspended does nothing.
+    #[inline]
+    pub fn resume_client(&self, client_id: ClientId) -> Result<(), InvalidId> {
+        self.handle.resume_client(client_id.id)
+    }
+
+    /// Returns whether the given client is currently suspended
+    #[inline]
+    pub fn is_client_suspended(&self, client_id: ClientId) -> Result<bool, InvalidId> {
+        self.handle.is_client_suspended(client_id.id)
+    }
+
+    /// Returns the statistics of a client
+    ///
+    /// This includes the number of messages, bytes and file descriptors it exchanged with the server, the
+    /// number of its live objects for each interface, and the time spent dispatching its requests.
+    ///
+    /// **Note:** The counters are currently only tracked by the rust backend, the system backend only
+    /// reports the live objects of the client.
+    #[inline]
+    pub fn client_stats(&self, client_id: ClientId) -> Result<ClientStats, InvalidId> {
+        self.handle.client_stats(client_id.id)
+    }
+
+    /// Returns a snapshot of the statistics of all the clients currently connected
+    ///
+    /// The statistics of clients that have disconnected are not accounted for. See
+    /// [`client_stats()`](Handle::client_stats) for details.
+    pub fn stats(&self) -> ServerStats {
+        let clients = self.handle.all_client_stats();
+        let mut total = ClientStats::default();
+        for (_, stats) in &clients {
+            total.merge(stats);
+        }
+        ServerStats { clients, total }
+    }
+
     /// Creates a global of the specified interface and version and then advertises it to clients.
     ///
     /// The clients which the global is advertised to is determined by the implementation of the [`GlobalHandler`].
@@ -561,6 +643,18 @@ impl<D> Backend<D> {
         self.backend.dispatch_client(data, client_id.id)
     }
 
+    /// Sets the limits on the work done by each invocation of
+    /// [`dispatch_all_clients()`](Backend::dispatch_all_clients).
+    ///
+    /// See [`DispatchBudget`] for details. By default, no limit is applied.
+    ///
+    /// **Note:** This functionality is currently only available on the rust backend, on the system backend
+    /// the budget is ignored.
+    #[inline]
+    pub fn set_dispatch_budget(&mut self, budget: DispatchBudget) {
+        self.backend.set_dispatch_budget(budget)
+    }
+
     /// Dispatches all pending messages from all clients.
     ///
     /// This method will not block if there are no pending messages.
@@ -570,6 +664,10 @@ impl<D> Backend<D> {
     /// For performance reasons, use of this function should be integrated with an event loop, monitoring the
     /// file descriptor retrieved by [`Backend::poll_fd`] and only calling this method when messages are
     /// available.
+    ///
+    /// The amount of requests processed by a single invocation can be limited with
+    /// [`set_dispatch_budget()`](Backend::set_dispatch_budget). The remaining requests are left pending
+    /// and the poll fd stays readable.
     #[inline]
     pub fn dispatch_all_clients(&mut self, data: &mut D) -> std::io::Result<usize> {
         self.backend.dispatch_all_clients(data)
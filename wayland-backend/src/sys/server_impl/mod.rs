@@ -26,7 +26,9 @@ use wayland_sys::{common::*, ffi_dispatch, server::*};
 use super::{free_arrays, server::*, RUST_MANAGED};
 
 #[allow(unused_imports)]
-pub use crate::types::server::{Credentials, DisconnectReason, GlobalInfo, InitError, InvalidId};
+pub use crate::types::server::{
+    ClientStats, Credentials, DisconnectReason, DispatchBudget, GlobalInfo, InitError, InvalidId,
+};
 
 scoped_thread_local! {
     // scoped_tls does not allow unsafe_op_in_unsafe_fn internally
@@ -394,6 +396,9 @@ impl<D> InnerBackend<D> {
         }
     }
 
+    // libwayland always processes all pending requests of ready clients
+    pub fn set_dispatch_budget(&mut self, _budget: DispatchBudget) {}
+
     pub fn dispatch_client(
         &mut self,
         data: &mut D,
@@ -522,6 +527,24 @@ impl InnerHandle {
         self.state.lock().unwrap().with_all_objects_for(client_id, &mut f)
     }
 
+    pub fn client_stats(&self, client_id: InnerClientId) -> Result<ClientStats, InvalidId> {
+        // libwayland does not expose its traffic, only the live objects can be counted
+        let mut stats = ClientStats::default();
+        self.with_all_objects_for(client_id, |id| {
+            *stats.objects.entry(id.interface().name).or_insert(0) += 1;
+        })?;
+        Ok(stats)
+    }
+
+    pub fn all_client_stats(&self) -> Vec<(ClientId, ClientStats)> {
+        let mut clients = Vec::new();
+        self.with_all_clients(|id| clients.push(id));
+        clients
+            .into_iter()
+            .filter_map(|id| Some((id.clone(), self.client_stats(id.id).ok()?)))
+            .collect()
+    }
+
     pub fn object_for_protocol_id(
         &self,
         client_id: InnerClientId,
@@ -672,6 +695,29 @@ impl InnerHandle {
         self.state.lock().unwrap().kill_client(client_id, reason)
     }
 
+    // libwayland does not provide any way to stop monitoring the socket of a client, so suspending
+    // clients is not supported by this backend.
+    pub fn suspend_client(&self, client_id: InnerClientId) -> Result<(), InvalidId> {
+        if !client_id.alive.load(Ordering::Acquire) {
+            return Err(InvalidId);
+        }
+        Ok(())
+    }
+
+    pub fn resume_client(&self, client_id: InnerClientId) -> Result<(), InvalidId> {
+        if !client_id.alive.load(Ordering::Acquire) {
+            return Err(InvalidId);
+        }
+        Ok(())
+    }
+
+    pub fn is_client_suspended(&self, client_id: InnerClientId) -> Result<bool, InvalidId> {
+        if !client_id.alive.load(Ordering::Acquire) {
+            return Err(InvalidId);
+        }
+        Ok(false)
+    }
+
     pub fn create_global<D: 'static>(
         &self,
         interface: &'static Interface,
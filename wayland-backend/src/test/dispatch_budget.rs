@@ -0,0 +1,147 @@
+use std::{num::NonZeroUsize, time::Duration};
+
+use super::*;
+
+fn poll_fd_is_readable(server: &server_rs::Backend<()>) -> bool {
+    use rustix::event::{poll, PollFd, PollFlags};
+    let poll_fd = server.poll_fd();
+    let mut fds = [PollFd::new(&poll_fd, PollFlags::IN)];
+    poll(&mut fds, 0).unwrap() > 0
+}
+
+// Dispatch budgets are only supported by the rust server backend
+#[test]
+fn dispatch_budget_per_client() {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_rs::Backend::connect(tx).unwrap();
+
+    server.set_dispatch_budget(
+        server_rs::DispatchBudget::default().requests_per_client(NonZeroUsize::new(1).unwrap()),
+    );
+
+    // send three wl_display.sync requests
+    for _ in 0..3 {
+        client
+            .send_request(
+                message!(client.display_id(), 0, [Argument::NewId(client_rs::ObjectId::null())]),
+                Some(Arc::new(DoNothingData)),
+                Some((&interfaces::WL_CALLBACK_INTERFACE, 1)),
+            )
+            .unwrap();
+    }
+    client.flush().unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    // the requests are processed one by one, the poll fd remaining readable in between
+    for _ in 0..3 {
+        assert!(poll_fd_is_readable(&server));
+        assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 1);
+    }
+    assert!(!poll_fd_is_readable(&server));
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 0);
+}
+
+#[test]
+fn dispatch_budget_round_robin() {
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let mut clients = Vec::new();
+    for _ in 0..2 {
+        let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+        server.handle().insert_client(rx, Arc::new(())).unwrap();
+        clients.push(client_rs::Backend::connect(tx).unwrap());
+    }
+
+    server.set_dispatch_budget(
+        server_rs::DispatchBudget::default()
+            .requests_per_client(NonZeroUsize::new(2).unwrap())
+            .round_robin(true),
+    );
+
+    for client in &clients {
+        for _ in 0..3 {
+            client
+                .send_request(
+                    message!(
+                        client.display_id(),
+                        0,
+                        [Argument::NewId(client_rs::ObjectId::null())]
+                    ),
+                    Some(Arc::new(DoNothingData)),
+                    Some((&interfaces::WL_CALLBACK_INTERFACE, 1)),
+                )
+                .unwrap();
+        }
+        client.flush().unwrap();
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    // two requests of each client, then the last one of each
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 4);
+    assert!(poll_fd_is_readable(&server));
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 2);
+    assert!(!poll_fd_is_readable(&server));
+}
+
+fn send_syncs(client: &client_rs::Backend, count: usize) {
+    for _ in 0..count {
+        client
+            .send_request(
+                message!(client.display_id(), 0, [Argument::NewId(client_rs::ObjectId::null())]),
+                Some(Arc::new(DoNothingData)),
+                Some((&interfaces::WL_CALLBACK_INTERFACE, 1)),
+            )
+            .unwrap();
+    }
+    client.flush().unwrap();
+    std::thread::sleep(Duration::from_millis(10));
+}
+
+#[test]
+fn dispatch_budget_time() {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_rs::Backend::connect(tx).unwrap();
+
+    // a generous time budget does not prevent dispatching all requests
+    server.set_dispatch_budget(server_rs::DispatchBudget::default().time(Duration::from_secs(60)));
+    send_syncs(&client, 3);
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 3);
+    assert!(!poll_fd_is_readable(&server));
+
+    // an exhausted time budget still dispatches one request per invocation
+    server.set_dispatch_budget(server_rs::DispatchBudget::default().time(Duration::ZERO));
+    send_syncs(&client, 3);
+    for _ in 0..3 {
+        assert!(poll_fd_is_readable(&server));
+        assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 1);
+    }
+    assert!(!poll_fd_is_readable(&server));
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 0);
+}
+
+#[test]
+fn dispatch_budget_time_multiple_clients() {
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let mut clients = Vec::new();
+    for _ in 0..2 {
+        let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+        server.handle().insert_client(rx, Arc::new(())).unwrap();
+        clients.push(client_rs::Backend::connect(tx).unwrap());
+    }
+
+    server.set_dispatch_budget(server_rs::DispatchBudget::default().time(Duration::ZERO));
+    for client in &clients {
+        send_syncs(client, 1);
+    }
+
+    // the client left over when running out of time is dispatched by the next invocation
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 1);
+    assert!(poll_fd_is_readable(&server));
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 1);
+    assert!(!poll_fd_is_readable(&server));
+}
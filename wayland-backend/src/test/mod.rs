@@ -51,15 +51,18 @@ macro_rules! expand_test {
 mod interfaces {
     use crate as wayland_backend;
     wayland_scanner::generate_interfaces!(
-        "../wayland-scanner/tests/scanner_assets/test-protocol.xml"
+        "../wayland-codegen/tests/scanner_assets/test-protocol.xml"
     );
 }
 
 mod destructors;
+mod dispatch_budget;
 mod many_args;
 mod object_args;
 mod protocol_error;
 mod server_created_objects;
+mod stats;
+mod suspend;
 mod sync;
 
 /*
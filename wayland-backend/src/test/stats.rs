@@ -0,0 +1,43 @@
+use super::*;
+
+// Traffic statistics are only tracked by the rust server backend
+#[test]
+fn client_stats() {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_rs::Backend::connect(tx).unwrap();
+
+    // send a wl_display.sync request
+    client
+        .send_request(
+            message!(client.display_id(), 0, [Argument::NewId(client_rs::ObjectId::null())]),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::WL_CALLBACK_INTERFACE, 1)),
+        )
+        .unwrap();
+    client.flush().unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    server.dispatch_all_clients(&mut ()).unwrap();
+    server.flush(None).unwrap();
+
+    let stats = server.handle().client_stats(client_id.clone()).unwrap();
+    assert_eq!(stats.requests, 1);
+    // wl_callback.done and wl_display.delete_id
+    assert_eq!(stats.events, 2);
+    assert_eq!(stats.bytes_in, 12);
+    assert_eq!(stats.bytes_out, 24);
+    assert_eq!(stats.fds_in, 0);
+    assert_eq!(stats.fds_out, 0);
+    // the callback was destroyed, only the display remains
+    assert_eq!(stats.objects.get("wl_display"), Some(&1));
+    assert_eq!(stats.objects.get("wl_callback"), None);
+
+    let server_stats = server.handle().stats();
+    assert_eq!(server_stats.clients.len(), 1);
+    assert_eq!(server_stats.clients[0].0, client_id);
+    assert_eq!(server_stats.total.requests, 1);
+    assert_eq!(server_stats.total.events, 2);
+}
@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::*;
+
+struct SyncData(AtomicBool);
+
+impl client_rs::ObjectData for SyncData {
+    fn event(
+        self: Arc<Self>,
+        _: &client_rs::Backend,
+        _: Message<client_rs::ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn client_rs::ObjectData>> {
+        self.0.store(true, Ordering::SeqCst);
+        None
+    }
+
+    fn destroyed(&self, _: client_rs::ObjectId) {}
+}
+
+// Suspension of clients is only supported by the rust server backend
+#[test]
+fn suspend_and_resume_client() {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_rs::Backend::connect(tx).unwrap();
+
+    server.handle().suspend_client(client_id.clone()).unwrap();
+    assert!(server.handle().is_client_suspended(client_id.clone()).unwrap());
+
+    // send a wl_display.sync request
+    let sync_data = Arc::new(SyncData(AtomicBool::new(false)));
+    client
+        .send_request(
+            message!(client.display_id(), 0, [Argument::NewId(client_rs::ObjectId::null())]),
+            Some(sync_data.clone()),
+            Some((&interfaces::WL_CALLBACK_INTERFACE, 1)),
+        )
+        .unwrap();
+    client.flush().unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    // the request is not processed while the client is suspended
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 0);
+    assert_eq!(server.dispatch_single_client(&mut (), client_id.clone()).unwrap(), 0);
+
+    server.handle().resume_client(client_id.clone()).unwrap();
+    assert!(!server.handle().is_client_suspended(client_id).unwrap());
+
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 1);
+    server.flush(None).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(10));
+
+    client.prepare_read().unwrap().read().unwrap();
+    assert!(sync_data.0.load(Ordering::SeqCst));
+}
+
+struct DisconnectData(AtomicBool);
+
+impl server_rs::ClientData for DisconnectData {
+    fn disconnected(&self, _: server_rs::ClientId, _: server_rs::DisconnectReason) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn suspended_client_hangup() {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let client_data = Arc::new(DisconnectData(AtomicBool::new(false)));
+    let client_id = server.handle().insert_client(rx, client_data.clone()).unwrap();
+    let client = client_rs::Backend::connect(tx).unwrap();
+
+    server.handle().suspend_client(client_id.clone()).unwrap();
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 0);
+    assert!(!client_data.0.load(Ordering::SeqCst));
+
+    std::mem::drop(client);
+
+    // the disconnection is noticed without resuming the client
+    assert_eq!(server.dispatch_all_clients(&mut ()).unwrap(), 0);
+    assert!(client_data.0.load(Ordering::SeqCst));
+    assert!(server.handle().is_client_suspended(client_id).is_err());
+
+    // and the dead client is no longer reported by the poll fd
+    let poll_fd = server.poll_fd();
+    let mut fds = [rustix::event::PollFd::new(&poll_fd, rustix::event::PollFlags::IN)];
+    assert_eq!(rustix::event::poll(&mut fds, 0).unwrap(), 0);
+}
+
+#[test]
+fn suspended_client_events_bounded() {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let client_data = Arc::new(DisconnectData(AtomicBool::new(false)));
+    let client_id = server.handle().insert_client(rx, client_data.clone()).unwrap();
+    // the client never reads its socket
+    let _client = client_rs::Backend::connect(tx).unwrap();
+
+    server.handle().suspend_client(client_id.clone()).unwrap();
+    let display = server
+        .handle()
+        .object_for_protocol_id(client_id, &crate::core_interfaces::WL_DISPLAY_INTERFACE, 1)
+        .unwrap();
+
+    // send wl_display.delete_id events until the buffers of the socket are full
+    for id in 0..1_000_000 {
+        if client_data.0.load(Ordering::SeqCst) {
+            break;
+        }
+        let _ = server.handle().send_event(message!(display.clone(), 1, [Argument::Uint(id)]));
+        let _ = server.flush(None);
+    }
+
+    assert!(client_data.0.load(Ordering::SeqCst));
+}
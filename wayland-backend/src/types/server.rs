@@ -72,3 +72,115 @@ pub struct Credentials {
     /// gid of the client
     pub gid: rustix::process::RawGid,
 }
+
+/// Limits on the amount of work done by a single invocation of `Backend::dispatch_all_clients()`
+///
+/// These limits allow to prevent a single client flooding the server with requests from starving the
+/// other clients. When a dispatching pass stops because of these limits while some requests are still
+/// pending, the poll fd of the backend remains readable, so that they are processed by the next
+/// invocation.
+///
+/// The default value does not put any limit, in which case all pending requests of each client are
+/// dispatched in turn. Whatever the limits, each invocation dispatches at least one of the pending
+/// requests, so that the server always makes progress. The limits are set with the builder methods:
+///
+/// ```
+/// # use std::num::NonZeroUsize;
+/// # use wayland_backend::rs::server::DispatchBudget;
+/// let budget = DispatchBudget::default()
+///     .requests_per_client(NonZeroUsize::new(16).unwrap())
+///     .round_robin(true);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DispatchBudget {
+    /// Maximum number of requests dispatched for each client
+    pub requests_per_client: Option<std::num::NonZeroUsize>,
+    /// Maximum wall-clock time spent dispatching requests
+    ///
+    /// This limit is checked between requests, so a single slow request handler can still exceed it,
+    /// and the first request is always dispatched, even with a zero duration.
+    pub time: Option<std::time::Duration>,
+    /// Dispatch the pending requests of the ready clients in a round-robin fashion, one request of each
+    /// client at a time, rather than draining each client before moving on to the next one
+    pub round_robin: bool,
+}
+
+impl DispatchBudget {
+    /// Set the maximum number of requests dispatched for each client
+    pub fn requests_per_client(mut self, requests: std::num::NonZeroUsize) -> Self {
+        self.requests_per_client = Some(requests);
+        self
+    }
+
+    /// Set the maximum wall-clock time spent dispatching requests
+    pub fn time(mut self, time: std::time::Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Set whether the requests of the ready clients are dispatched in a round-robin fashion
+    pub fn round_robin(mut self, round_robin: bool) -> Self {
+        self.round_robin = round_robin;
+        self
+    }
+}
+
+/// Statistics about the activity of a client
+///
+/// The message and traffic counters are cumulative since the client connected, while the object
+/// counts reflect its current state.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ClientStats {
+    /// Number of requests received from the client
+    pub requests: u64,
+    /// Number of events sent to the client
+    pub events: u64,
+    /// Number of bytes received from the client
+    pub bytes_in: u64,
+    /// Number of bytes sent to the client
+    pub bytes_out: u64,
+    /// Number of file descriptors received from the client
+    pub fds_in: u64,
+    /// Number of file descriptors sent to the client
+    pub fds_out: u64,
+    /// Number of live objects of the client, for each interface name
+    pub objects: std::collections::HashMap<&'static str, usize>,
+    /// Total time spent in the request handlers of the objects of the client
+    pub dispatch_time: std::time::Duration,
+}
+
+impl ClientStats {
+    /// Add the statistics of an other client to these ones
+    pub fn merge(&mut self, other: &ClientStats) {
+        self.requests += other.requests;
+        self.events += other.events;
+        self.bytes_in += other.bytes_in;
+        self.bytes_out += other.bytes_out;
+        self.fds_in += other.fds_in;
+        self.fds_out += other.fds_out;
+        for (interface, count) in &other.objects {
+            *self.objects.entry(interface).or_insert(0) += count;
+        }
+        self.dispatch_time += other.dispatch_time;
+    }
+}
+
+/// A snapshot of the statistics of all the clients of a server
+///
+/// The type parameter is the `ClientId` type of the backend.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ServerStats<C> {
+    /// Statistics of each connected client
+    pub clients: Vec<(C, ClientStats)>,
+    /// Statistics of all connected clients added together
+    pub total: ClientStats,
+}
+
+impl<C> Default for ServerStats<C> {
+    fn default() -> Self {
+        Self { clients: Vec::new(), total: ClientStats::default() }
+    }
+}
@@ -128,6 +128,14 @@
 //! }
 //! ```
 //!
+//! ## Serialization
+//!
+//! If you activate the `serde` cargo feature, the objects, enums, requests and events of the
+//! [`protocol`] module implement `serde::Serialize`, which can be used to record or inspect the messages
+//! of a connection. Objects are serialized as `interface@id` (like `wl_surface@3`) and file
+//! descriptors as a `"<fd>"` placeholder. Code generated by `wayland-scanner` with its `serde = true`
+//! option also requires this feature.
+//!
 //! ## Advanced use
 //!
 //! ### Bypassing [`Dispatch`]
@@ -180,6 +188,7 @@ use wayland_backend::{
 mod conn;
 mod event_queue;
 pub mod globals;
+pub mod shm;
 
 /// Backend reexports
 pub mod backend {
@@ -188,6 +197,9 @@ pub mod backend {
         WeakBackend,
     };
     pub use wayland_backend::protocol;
+    #[cfg(feature = "serde")]
+    #[doc(hidden)]
+    pub use wayland_backend::serde;
     pub use wayland_backend::smallvec;
 }
 
@@ -217,7 +229,10 @@ pub mod protocol {
     pub mod __interfaces {
         wayland_scanner::generate_interfaces!("wayland.xml");
     }
-    wayland_scanner::generate_client_code!("wayland.xml");
+    #[cfg(not(feature = "serde"))]
+    wayland_scanner::generate_client_code!("wayland.xml", handlers = true);
+    #[cfg(feature = "serde")]
+    wayland_scanner::generate_client_code!("wayland.xml", handlers = true, serde = true);
 }
 
 /// Trait representing a Wayland interface
@@ -364,6 +379,32 @@ impl From<WaylandError> for DispatchError {
     }
 }
 
+/// Error returned by the version-checked request methods of the generated proxies
+///
+/// These methods are named `try_<request>()`, and are generated for the requests introduced after the first
+/// version of an interface. They fail without sending anything if the object was created with a version
+/// lower than the one introducing the request. The minimal version of each request is also available as a
+/// `REQ_<REQUEST>_SINCE` constant in the module of its interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionTooLow {
+    /// The minimal version of the interface supporting the request
+    pub required: u32,
+    /// The version of the object
+    pub bound: u32,
+}
+
+impl std::error::Error for VersionTooLow {}
+
+impl fmt::Display for VersionTooLow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "This request requires version {} of the interface, but the object has version {}",
+            self.required, self.bound
+        )
+    }
+}
+
 /// A weak handle to a Wayland object
 ///
 /// This handle does not keep the underlying user data alive, and can be converted back to a full proxy
@@ -0,0 +1,673 @@
+//! Helpers for shared memory buffers
+//!
+//! Drawing into shared memory buffers is the simplest way for a client to display content. This module
+//! takes care of the allocation of the shared memory and of the life cycle of the buffers:
+//!
+//! - [`Shm`] binds the `wl_shm` global and keeps track of the pixel formats supported by the server.
+//! - [`ShmPool`] maps a memory pool shared with the server, grows it as needed and allocates the
+//!   [`Buffer`]s in it, reusing the space of the dropped buffers.
+//! - [`Buffer`] tracks whether the server is still reading it, from the `wl_buffer.release` events. A buffer
+//!   can only be drawn into while the server does not use it, and its space is only reused once it has
+//!   been released.
+//! - [`MultiBuffer`] manages a set of buffers of the same size, for double or triple buffering.
+//!
+//! The objects created by these helpers handle their events themselves, so no [`Dispatch`](crate::Dispatch)
+//! implementation is needed for them.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use wayland_client::{
+//!     globals::registry_queue_init,
+//!     protocol::{wl_registry, wl_shm::Format, wl_surface::WlSurface},
+//!     shm::{MultiBuffer, Shm, ShmPool},
+//!     Connection,
+//! };
+//! # struct State;
+//! # impl wayland_client::Dispatch<wl_registry::WlRegistry, wayland_client::globals::GlobalListContents> for State {
+//! #     fn event(
+//! #         _: &mut State,
+//! #         _: &wl_registry::WlRegistry,
+//! #         _: wl_registry::Event,
+//! #         _: &wayland_client::globals::GlobalListContents,
+//! #         _: &Connection,
+//! #         _: &wayland_client::QueueHandle<State>,
+//! #     ) {}
+//! # }
+//! # fn example(surface: WlSurface) -> Result<(), Box<dyn std::error::Error>> {
+//! let conn = Connection::connect_to_env()?;
+//! let (globals, _queue) = registry_queue_init::<State>(&conn)?;
+//! let shm = Shm::bind(&globals)?;
+//! // receive the supported formats
+//! conn.roundtrip()?;
+//!
+//! let pool = ShmPool::new(&shm, 256 * 256 * 4)?;
+//! let mut buffers = MultiBuffer::new(pool, 2, 256, 256, 256 * 4, Format::Argb8888);
+//!
+//! // when drawing a frame
+//! if let Some((buffer, canvas)) = buffers.next_buffer()? {
+//!     canvas.fill(0xff);
+//!     buffer.attach_to(&surface);
+//!     surface.damage_buffer(0, 0, 256, 256);
+//!     surface.commit();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    ffi::c_void,
+    fmt, io,
+    os::unix::io::{AsFd, BorrowedFd, OwnedFd},
+    ptr,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rustix::{
+    fs::Mode,
+    io::Errno,
+    mm::{mmap, munmap, MapFlags, ProtFlags},
+    shm::{shm_open, shm_unlink, ShmOFlags},
+};
+use wayland_backend::{
+    client::{Backend, InvalidId, ObjectData, ObjectId},
+    protocol::{wl_shm_bytes_per_pixel, Message},
+};
+
+use crate::{
+    globals::{BindError, GlobalList},
+    protocol::{
+        wl_buffer::{self, WlBuffer},
+        wl_registry::{self, WlRegistry},
+        wl_shm::{self, Format, WlShm},
+        wl_shm_pool::{self, WlShmPool},
+        wl_surface::WlSurface,
+    },
+    Connection, Proxy, WEnum,
+};
+
+/// An error that occurred while allocating shared memory buffers
+#[derive(Debug)]
+pub enum ShmError {
+    /// The `wl_shm` global could not be bound
+    Bind(BindError),
+    /// The shared memory could not be allocated, resized or mapped
+    Io(io::Error),
+    /// The connection to the server is dead
+    InvalidId(InvalidId),
+    /// The server does not support the pixel format
+    UnsupportedFormat(Format),
+    /// The requested size is invalid or exceeds the maximal size of a pool
+    InvalidSize,
+}
+
+impl std::error::Error for ShmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShmError::Bind(source) => Some(source),
+            ShmError::Io(source) => Some(source),
+            ShmError::InvalidId(source) => Some(source),
+            ShmError::UnsupportedFormat(_) | ShmError::InvalidSize => None,
+        }
+    }
+}
+
+impl fmt::Display for ShmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShmError::Bind(source) => write!(f, "Failed to bind wl_shm: {source}"),
+            ShmError::Io(source) => write!(f, "Failed to allocate shared memory: {source}"),
+            ShmError::InvalidId(source) => write!(f, "{source}"),
+            ShmError::UnsupportedFormat(format) => {
+                write!(f, "The pixel format {format:?} is not supported by the server")
+            }
+            ShmError::InvalidSize => f.write_str("Invalid shared memory size"),
+        }
+    }
+}
+
+impl From<BindError> for ShmError {
+    fn from(source: BindError) -> Self {
+        ShmError::Bind(source)
+    }
+}
+
+impl From<io::Error> for ShmError {
+    fn from(source: io::Error) -> Self {
+        ShmError::Io(source)
+    }
+}
+
+impl From<Errno> for ShmError {
+    fn from(source: Errno) -> Self {
+        ShmError::Io(source.into())
+    }
+}
+
+impl From<InvalidId> for ShmError {
+    fn from(source: InvalidId) -> Self {
+        ShmError::InvalidId(source)
+    }
+}
+
+/// The `wl_shm` global, with the pixel formats supported by the server
+#[derive(Debug, Clone)]
+pub struct Shm {
+    shm: WlShm,
+    formats: Arc<Mutex<Vec<Format>>>,
+}
+
+impl Shm {
+    /// Bind the `wl_shm` global from the list of globals
+    ///
+    /// The supported formats are advertised by the server after the global is bound, they are known once
+    /// a roundtrip has been done.
+    pub fn bind(globals: &GlobalList) -> Result<Self, ShmError> {
+        let global = globals.contents().with_list(|list| {
+            list.iter()
+                .find(|global| global.interface == WlShm::interface().name)
+                .map(|global| global.name)
+        });
+        let name = global.ok_or(BindError::NotPresent)?;
+        Self::bind_from_registry(globals.registry(), name)
+    }
+
+    /// Bind the `wl_shm` global with the given name from a registry
+    pub fn bind_from_registry(registry: &WlRegistry, name: u32) -> Result<Self, ShmError> {
+        let formats = Arc::new(Mutex::new(Vec::new()));
+        let shm = registry.send_constructor(
+            wl_registry::Request::Bind { name, id: (WlShm::interface(), 1) },
+            Arc::new(ShmData { formats: formats.clone() }),
+        )?;
+        Ok(Self { shm, formats })
+    }
+
+    /// Use a `wl_shm` object bound elsewhere
+    ///
+    /// Its events are not received by the returned `Shm`, which only knows of the `Argb8888` and
+    /// `Xrgb8888` formats supported by all servers: [`formats()`](Shm::formats) is empty.
+    pub fn from_wl_shm(shm: WlShm) -> Self {
+        Self { shm, formats: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    /// The `wl_shm` object
+    pub fn wl_shm(&self) -> &WlShm {
+        &self.shm
+    }
+
+    /// The pixel formats advertised by the server
+    pub fn formats(&self) -> Vec<Format> {
+        self.formats.lock().unwrap().clone()
+    }
+
+    /// Whether the server supports a pixel format
+    ///
+    /// The `Argb8888` and `Xrgb8888` formats are always supported.
+    pub fn supports(&self, format: Format) -> bool {
+        supports(&self.formats, format)
+    }
+}
+
+fn supports(formats: &Mutex<Vec<Format>>, format: Format) -> bool {
+    matches!(format, Format::Argb8888 | Format::Xrgb8888)
+        || formats.lock().unwrap().contains(&format)
+}
+
+struct ShmData {
+    formats: Arc<Mutex<Vec<Format>>>,
+}
+
+impl ObjectData for ShmData {
+    fn event(
+        self: Arc<Self>,
+        backend: &Backend,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData>> {
+        let conn = Connection::from_backend(backend.clone());
+        // formats unknown to this version of the protocol cannot be used anyway
+        if let Ok((_, wl_shm::Event::Format { format: WEnum::Value(format) })) =
+            WlShm::parse_event(&conn, msg)
+        {
+            let mut formats = self.formats.lock().unwrap();
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        None
+    }
+
+    fn destroyed(&self, _: ObjectId) {}
+}
+
+/// The free ranges of a pool, as sorted and merged `(offset, len)` pairs
+#[derive(Debug)]
+struct Slots {
+    free: Vec<(usize, usize)>,
+}
+
+impl Slots {
+    fn allocate(&mut self, len: usize) -> Option<usize> {
+        let i = self.free.iter().position(|&(_, free_len)| free_len >= len)?;
+        let (offset, free_len) = self.free[i];
+        if free_len == len {
+            self.free.remove(i);
+        } else {
+            self.free[i] = (offset + len, free_len - len);
+        }
+        Some(offset)
+    }
+
+    fn release(&mut self, offset: usize, len: usize) {
+        let i = self.free.partition_point(|&(free_offset, _)| free_offset < offset);
+        self.free.insert(i, (offset, len));
+        if i + 1 < self.free.len() && offset + len == self.free[i + 1].0 {
+            self.free[i].1 += self.free.remove(i + 1).1;
+        }
+        if i > 0 && self.free[i - 1].0 + self.free[i - 1].1 == offset {
+            self.free[i - 1].1 += self.free.remove(i).1;
+        }
+    }
+}
+
+/// A shared memory mapping
+#[derive(Debug)]
+struct Mapping {
+    ptr: *mut c_void,
+    len: usize,
+}
+
+// The mapping is only accessed through `&mut self`
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    fn new(fd: BorrowedFd<'_>, len: usize) -> io::Result<Self> {
+        let ptr = unsafe {
+            mmap(ptr::null_mut(), len, ProtFlags::READ | ProtFlags::WRITE, MapFlags::SHARED, fd, 0)
+        }?;
+        Ok(Self { ptr, len })
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        // The file is never shrunk, so the mapping cannot be truncated under us
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.cast(), self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        let _ = unsafe { munmap(self.ptr, self.len) };
+    }
+}
+
+/// A memory pool shared with the server, in which buffers are allocated
+///
+/// The pool grows when there is not enough free space for a new buffer. The space of a dropped buffer is
+/// reused once the server has released it.
+#[derive(Debug)]
+pub struct ShmPool {
+    pool: WlShmPool,
+    fd: OwnedFd,
+    map: Mapping,
+    formats: Arc<Mutex<Vec<Format>>>,
+    slots: Arc<Mutex<Slots>>,
+}
+
+impl ShmPool {
+    /// Create a new pool with an initial size in bytes
+    pub fn new(shm: &Shm, size: usize) -> Result<Self, ShmError> {
+        if size == 0 || size > i32::MAX as usize {
+            return Err(ShmError::InvalidSize);
+        }
+        let fd = create_shm_fd()?;
+        rustix::fs::ftruncate(&fd, size as u64)?;
+        let map = Mapping::new(fd.as_fd(), size)?;
+        let pool = shm.shm.send_constructor(
+            wl_shm::Request::CreatePool { fd: fd.as_fd(), size: size as i32 },
+            Arc::new(NoEventData),
+        )?;
+        Ok(Self {
+            pool,
+            fd,
+            map,
+            formats: shm.formats.clone(),
+            slots: Arc::new(Mutex::new(Slots { free: vec![(0, size)] })),
+        })
+    }
+
+    /// The `wl_shm_pool` object
+    pub fn wl_shm_pool(&self) -> &WlShmPool {
+        &self.pool
+    }
+
+    /// The size of the pool in bytes
+    pub fn len(&self) -> usize {
+        self.map.len
+    }
+
+    /// Whether the pool is empty, which is never the case
+    pub fn is_empty(&self) -> bool {
+        self.map.len == 0
+    }
+
+    /// Grow the pool to a new size in bytes
+    ///
+    /// The pool cannot shrink, so this does nothing if the size is not larger than its current size.
+    pub fn resize(&mut self, size: usize) -> Result<(), ShmError> {
+        let old_size = self.map.len;
+        if size <= old_size {
+            return Ok(());
+        }
+        if size > i32::MAX as usize {
+            return Err(ShmError::InvalidSize);
+        }
+        rustix::fs::ftruncate(&self.fd, size as u64)?;
+        self.map = Mapping::new(self.fd.as_fd(), size)?;
+        self.pool.resize(size as i32);
+        self.slots.lock().unwrap().release(old_size, size - old_size);
+        Ok(())
+    }
+
+    /// Allocate a buffer in the pool, growing it if needed
+    ///
+    /// The buffer uses `stride * height` bytes, the stride being the length of a row in bytes. This fails
+    /// with [`ShmError::UnsupportedFormat`] if the server did not advertise the format, and with
+    /// [`ShmError::InvalidSize`] if a row of `width` pixels does not fit in `stride` bytes.
+    pub fn create_buffer(
+        &mut self,
+        width: i32,
+        height: i32,
+        stride: i32,
+        format: Format,
+    ) -> Result<Buffer, ShmError> {
+        if width <= 0 || height <= 0 {
+            return Err(ShmError::InvalidSize);
+        }
+        let bytes_per_pixel = wl_shm_bytes_per_pixel(format.into()).unwrap_or(1);
+        let min_stride = width.checked_mul(bytes_per_pixel as i32);
+        if min_stride.map_or(true, |min_stride| stride < min_stride) {
+            return Err(ShmError::InvalidSize);
+        }
+        if !supports(&self.formats, format) {
+            return Err(ShmError::UnsupportedFormat(format));
+        }
+        let len = stride.checked_mul(height).ok_or(ShmError::InvalidSize)? as usize;
+
+        let allocated = self.slots.lock().unwrap().allocate(len);
+        let offset = match allocated {
+            Some(offset) => offset,
+            None => {
+                let size = (self.map.len * 2).max(self.map.len + len).min(i32::MAX as usize);
+                self.resize(size)?;
+                self.slots.lock().unwrap().allocate(len).ok_or(ShmError::InvalidSize)?
+            }
+        };
+
+        let data = Arc::new(BufferData {
+            offset,
+            len,
+            slots: self.slots.clone(),
+            state: Mutex::new(BufferState { busy: false, dropped: false }),
+        });
+        let buffer = self.pool.send_constructor(
+            wl_shm_pool::Request::CreateBuffer {
+                offset: offset as i32,
+                width,
+                height,
+                stride,
+                format: WEnum::Value(format),
+            },
+            data.clone(),
+        );
+        let buffer = match buffer {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                self.slots.lock().unwrap().release(offset, len);
+                return Err(err.into());
+            }
+        };
+        Ok(Buffer { buffer, data, width, height, stride, format })
+    }
+
+    /// Access the memory of a buffer, to draw into it
+    ///
+    /// This returns `None` if the buffer is in use by the server, or if it was not allocated from this pool.
+    pub fn canvas(&mut self, buffer: &Buffer) -> Option<&mut [u8]> {
+        if !Arc::ptr_eq(&buffer.data.slots, &self.slots) || buffer.is_busy() {
+            return None;
+        }
+        Some(&mut self.map.as_mut_slice()[buffer.data.offset..][..buffer.data.len])
+    }
+}
+
+impl Drop for ShmPool {
+    fn drop(&mut self) {
+        // the buffers remain valid after the destruction of their pool
+        self.pool.destroy();
+    }
+}
+
+/// A buffer allocated from a [`ShmPool`]
+///
+/// Dropping the buffer destroys it, once the server has released it if it is in use.
+#[derive(Debug)]
+pub struct Buffer {
+    buffer: WlBuffer,
+    data: Arc<BufferData>,
+    width: i32,
+    height: i32,
+    stride: i32,
+    format: Format,
+}
+
+impl Buffer {
+    /// The `wl_buffer` object
+    pub fn wl_buffer(&self) -> &WlBuffer {
+        &self.buffer
+    }
+
+    /// Attach the buffer to a surface
+    ///
+    /// The buffer is then in use by the server, until it sends a `wl_buffer.release` event. Attaching the
+    /// buffer with `wl_surface.attach` directly does not mark it as in use, so it could be drawn into
+    /// while the server reads it.
+    pub fn attach_to(&self, surface: &WlSurface) {
+        self.data.state.lock().unwrap().busy = true;
+        surface.attach(Some(&self.buffer), 0, 0);
+    }
+
+    /// Whether the buffer is in use by the server
+    pub fn is_busy(&self) -> bool {
+        self.data.state.lock().unwrap().busy
+    }
+
+    /// The width of the buffer in pixels
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// The height of the buffer in pixels
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// The stride of the buffer in bytes
+    pub fn stride(&self) -> i32 {
+        self.stride
+    }
+
+    /// The pixel format of the buffer
+    pub fn format(&self) -> Format {
+        self.format
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        let mut state = self.data.state.lock().unwrap();
+        if state.busy {
+            // destroyed on release
+            state.dropped = true;
+        } else {
+            self.buffer.destroy();
+            self.data.release();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BufferState {
+    busy: bool,
+    dropped: bool,
+}
+
+#[derive(Debug)]
+struct BufferData {
+    offset: usize,
+    len: usize,
+    slots: Arc<Mutex<Slots>>,
+    state: Mutex<BufferState>,
+}
+
+impl BufferData {
+    fn release(&self) {
+        self.slots.lock().unwrap().release(self.offset, self.len);
+    }
+}
+
+impl ObjectData for BufferData {
+    fn event(
+        self: Arc<Self>,
+        backend: &Backend,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData>> {
+        let conn = Connection::from_backend(backend.clone());
+        if let Ok((buffer, wl_buffer::Event::Release)) = WlBuffer::parse_event(&conn, msg) {
+            let mut state = self.state.lock().unwrap();
+            state.busy = false;
+            if state.dropped {
+                buffer.destroy();
+                self.release();
+            }
+        }
+        None
+    }
+
+    fn destroyed(&self, _: ObjectId) {}
+}
+
+/// Object data of the objects which have no events
+struct NoEventData;
+
+impl ObjectData for NoEventData {
+    fn event(
+        self: Arc<Self>,
+        _: &Backend,
+        _: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData>> {
+        None
+    }
+
+    fn destroyed(&self, _: ObjectId) {}
+}
+
+/// A set of buffers of the same size, for double or triple buffering
+///
+/// The buffers are created as needed, up to the given count: when drawing a frame,
+/// [`next_buffer()`](MultiBuffer::next_buffer) returns a buffer which is not in use by the server,
+/// creating a new one only if all the existing ones are in use.
+#[derive(Debug)]
+pub struct MultiBuffer {
+    pool: ShmPool,
+    buffers: Vec<Buffer>,
+    count: usize,
+    width: i32,
+    height: i32,
+    stride: i32,
+    format: Format,
+}
+
+impl MultiBuffer {
+    /// Create a set of at most `count` buffers with the given size and format, allocated from `pool`
+    pub fn new(
+        pool: ShmPool,
+        count: usize,
+        width: i32,
+        height: i32,
+        stride: i32,
+        format: Format,
+    ) -> Self {
+        Self { pool, buffers: Vec::new(), count: count.max(1), width, height, stride, format }
+    }
+
+    /// Change the size of the buffers
+    ///
+    /// The current buffers are dropped, and new ones are created by the next calls to
+    /// [`next_buffer()`](MultiBuffer::next_buffer).
+    pub fn resize(&mut self, width: i32, height: i32, stride: i32) {
+        if (width, height, stride) != (self.width, self.height, self.stride) {
+            self.buffers.clear();
+            (self.width, self.height, self.stride) = (width, height, stride);
+        }
+    }
+
+    /// Get a buffer which is not in use by the server, with its memory to draw into
+    ///
+    /// This returns `None` if all the buffers are in use. The content of the buffer is the one of the
+    /// last frame drawn into it, or zeroes for a new buffer.
+    pub fn next_buffer(&mut self) -> Result<Option<(&Buffer, &mut [u8])>, ShmError> {
+        let index = match self.buffers.iter().position(|buffer| !buffer.is_busy()) {
+            Some(index) => index,
+            None if self.buffers.len() < self.count => {
+                let buffer =
+                    self.pool.create_buffer(self.width, self.height, self.stride, self.format)?;
+                self.buffers.push(buffer);
+                self.buffers.len() - 1
+            }
+            None => return Ok(None),
+        };
+        let buffer = &self.buffers[index];
+        Ok(self.pool.canvas(buffer).map(|canvas| (buffer, canvas)))
+    }
+
+    /// The pool of the buffers
+    pub fn pool(&self) -> &ShmPool {
+        &self.pool
+    }
+}
+
+/// Create a shared file descriptor in memory
+fn create_shm_fd() -> io::Result<OwnedFd> {
+    // Only try memfd on systems that provide it, (like Linux, Android)
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    loop {
+        match rustix::fs::memfd_create("wayland-client-shm", rustix::fs::MemfdFlags::CLOEXEC) {
+            Ok(fd) => return Ok(fd),
+            Err(Errno::INTR) => continue,
+            Err(Errno::NOSYS) => break,
+            Err(errno) => return Err(errno.into()),
+        }
+    }
+
+    // Fallback to using shm_open
+    loop {
+        let name = format!(
+            "/wayland-client-shm-{}",
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos()
+        );
+        match shm_open(
+            name.as_str(),
+            ShmOFlags::CREATE | ShmOFlags::EXCL | ShmOFlags::RDWR,
+            Mode::RUSR | Mode::WUSR,
+        ) {
+            Ok(fd) => {
+                shm_unlink(name.as_str())?;
+                return Ok(fd);
+            }
+            // if a file with that name exists, retry with another name
+            Err(Errno::EXIST) | Err(Errno::INTR) => continue,
+            Err(errno) => return Err(errno.into()),
+        }
+    }
+}
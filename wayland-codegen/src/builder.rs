@@ -0,0 +1,352 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    generate_protocol_code, parse, parse::parse_inner, protocol::Protocol, try_format_rust_code,
+    CodeKind, GenerateError, Options, ParseError,
+};
+
+/// An error that occured while generating code using a [`Builder`]
+#[derive(Debug)]
+pub enum BuildError {
+    /// An IO error occured while reading a protocol file or writing the generated code
+    Io(io::Error),
+    /// A protocol file is invalid
+    Protocol {
+        /// Path of the protocol file
+        path: PathBuf,
+        /// The error found in this file
+        error: ParseError,
+    },
+    /// The interface filtering options are invalid for a protocol file
+    Options {
+        /// Path of the protocol file
+        path: PathBuf,
+        /// Description of the error
+        message: String,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Io(e) => write!(f, "{}", e),
+            BuildError::Protocol { path, error } => write!(f, "{}:{}", path.display(), error),
+            BuildError::Options { path, message } => write!(f, "{}: {}", path.display(), message),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::Io(e) => Some(e),
+            BuildError::Protocol { error, .. } => Some(error),
+            BuildError::Options { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for BuildError {
+    fn from(e: io::Error) -> BuildError {
+        BuildError::Io(e)
+    }
+}
+
+/// Builder for generating the code of protocol files from a build script
+///
+/// By default, only the low-level interfaces are generated, see [`client()`](Builder::client) and
+/// [`server()`](Builder::server) to also generate the client-side or server-side API. For each protocol
+/// file `path/to/name.xml`, the following files are written in the output directory:
+///
+/// - `name_interfaces.rs`, equivalent to `wayland_scanner::generate_interfaces!`
+/// - `name_client.rs`, equivalent to `wayland_scanner::generate_client_code!`
+/// - `name_server.rs`, equivalent to `wayland_scanner::generate_server_code!`
+///
+/// The `interface` and `enum` references of the protocol files are checked against the interfaces of
+/// the core Wayland protocol, of the other protocol files of the builder, and of the protocol files
+/// added with [`dependency()`](Builder::dependency). When running in a build script, the
+/// [`warnings()`](crate::warnings) of the protocol files are printed as `cargo:warning` directives.
+#[derive(Clone, Debug)]
+pub struct Builder {
+    protocols: Vec<PathBuf>,
+    dependencies: Vec<PathBuf>,
+    out_dir: Option<PathBuf>,
+    interfaces: bool,
+    client: bool,
+    server: bool,
+    options: Options,
+    rustfmt: bool,
+    rerun_if_changed: bool,
+}
+
+impl Builder {
+    /// Create a new builder with no protocol files
+    pub fn new() -> Builder {
+        Builder {
+            protocols: Vec::new(),
+            dependencies: Vec::new(),
+            out_dir: None,
+            interfaces: true,
+            client: false,
+            server: false,
+            options: Options::default(),
+            rustfmt: true,
+            rerun_if_changed: true,
+        }
+    }
+
+    /// Add a protocol file to generate code for
+    pub fn protocol(mut self, path: impl AsRef<Path>) -> Builder {
+        self.protocols.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Add several protocol files to generate code for
+    pub fn protocols(mut self, paths: impl IntoIterator<Item = impl AsRef<Path>>) -> Builder {
+        self.protocols.extend(paths.into_iter().map(|path| path.as_ref().to_owned()));
+        self
+    }
+
+    /// Add a protocol file the generated protocols refer to, without generating its code
+    ///
+    /// Its interfaces are only used to check the references of the generated protocols, which is needed
+    /// for protocols extending an other protocol than the core one, like the ones referring to the
+    /// `xdg_surface` of `xdg-shell`.
+    pub fn dependency(mut self, path: impl AsRef<Path>) -> Builder {
+        self.dependencies.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Set the directory in which the files are written
+    ///
+    /// Defaults to the `OUT_DIR` environment variable set by cargo when running build scripts.
+    pub fn out_dir(mut self, path: impl AsRef<Path>) -> Builder {
+        self.out_dir = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Whether to generate the low-level interfaces (enabled by default)
+    pub fn interfaces(mut self, enabled: bool) -> Builder {
+        self.interfaces = enabled;
+        self
+    }
+
+    /// Whether to generate the client-side API (disabled by default)
+    pub fn client(mut self, enabled: bool) -> Builder {
+        self.client = enabled;
+        self
+    }
+
+    /// Whether to generate the server-side API (disabled by default)
+    pub fn server(mut self, enabled: bool) -> Builder {
+        self.server = enabled;
+        self
+    }
+
+    /// Whether to generate a `Handler` trait for each interface (disabled by default)
+    ///
+    /// The trait has one method per message received on this side of the connection, and the interface
+    /// type implements `Dispatch` for any state implementing it. This is equivalent to the
+    /// `handlers = true` argument of the `wayland-scanner` macros.
+    pub fn handlers(mut self, enabled: bool) -> Builder {
+        self.options.handlers = enabled;
+        self
+    }
+
+    /// Whether to implement `serde::Serialize` for the generated types (disabled by default)
+    ///
+    /// Objects serialize as `interface@id`, and file descriptors as a `"<fd>"` placeholder. The generated
+    /// code requires the `serde` feature of `wayland-client` or `wayland-server`. This is equivalent to the
+    /// `serde = true` argument of the `wayland-scanner` macros.
+    pub fn serde(mut self, enabled: bool) -> Builder {
+        self.options.serde = enabled;
+        self
+    }
+
+    /// Whether to generate a `mock` module in the server-side code (disabled by default)
+    ///
+    /// This module contains a `MockState`, which implements `GlobalDispatch` and `Dispatch` for all
+    /// the interfaces of the protocol by recording the requests it receives, to be used in tests. This is
+    /// equivalent to the `mock = true` argument of `wayland_scanner::generate_server_code!`.
+    pub fn mock(mut self, enabled: bool) -> Builder {
+        self.options.mock = enabled;
+        self
+    }
+
+    /// Only generate these interfaces
+    ///
+    /// The interfaces they refer to must also be generated, or be mapped to another module using
+    /// [`extern_interface()`](Builder::extern_interface). This applies to all protocol files, so you
+    /// should use a separate builder for each filtered protocol.
+    pub fn only(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Builder {
+        self.options.only = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Do not generate these interfaces
+    ///
+    /// The other interfaces must not refer to them, unless they are mapped to another module using
+    /// [`extern_interface()`](Builder::extern_interface). This applies to all protocol files, so you
+    /// should use a separate builder for each filtered protocol.
+    pub fn exclude(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Builder {
+        self.options.exclude.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Use the code of an interface generated in another module
+    ///
+    /// The interface is not generated, and the generated code refers to the module at `path` instead,
+    /// which must contain the code generated for this interface: the parent of its module for the
+    /// client-side and server-side code, and the module containing its `*_INTERFACE` static for the
+    /// interfaces. This is equivalent to the `extern` argument of the `wayland-scanner` macros.
+    pub fn extern_interface(mut self, name: impl Into<String>, path: impl Into<String>) -> Builder {
+        self.options.externs.push((name.into(), path.into()));
+        self
+    }
+
+    /// Whether to format the generated code using `rustfmt` (enabled by default)
+    ///
+    /// If `rustfmt` is not available, the code is written unformatted.
+    pub fn rustfmt(mut self, enabled: bool) -> Builder {
+        self.rustfmt = enabled;
+        self
+    }
+
+    /// Whether to print `cargo:rerun-if-changed` directives for the protocol files (enabled by default)
+    ///
+    /// This makes cargo only re-run your build script when the protocol files change, you should disable
+    /// it if you use this builder outside of a build script.
+    pub fn rerun_if_changed(mut self, enabled: bool) -> Builder {
+        self.rerun_if_changed = enabled;
+        self
+    }
+
+    /// Generate the code and write it to the output directory
+    ///
+    /// Returns the paths of the written files.
+    pub fn generate(&self) -> Result<Vec<PathBuf>, BuildError> {
+        let out_dir = match self.out_dir {
+            Some(ref dir) => dir.clone(),
+            None => match std::env::var_os("OUT_DIR") {
+                Some(dir) => PathBuf::from(dir),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "OUT_DIR is not set, use Builder::out_dir() outside of build scripts",
+                    )
+                    .into())
+                }
+            },
+        };
+        std::fs::create_dir_all(&out_dir)?;
+
+        let kinds = [
+            (self.interfaces, CodeKind::Interfaces, "interfaces"),
+            (self.client, CodeKind::Client, "client"),
+            (self.server, CodeKind::Server, "server"),
+        ];
+
+        // parse all the files first, so that their references can be resolved against each other
+        let mut files = Vec::new();
+        for path in self.protocols.iter().chain(&self.dependencies) {
+            if self.rerun_if_changed {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+            let source = std::fs::read(path)?;
+            let protocol = parse(&source)
+                .map_err(|error| BuildError::Protocol { path: path.clone(), error })?;
+            files.push((source, protocol));
+        }
+
+        let mut written = Vec::new();
+        for (i, protocol) in self.protocols.iter().enumerate() {
+            let stem = protocol.file_stem().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid protocol file path: {}", protocol.display()),
+                )
+            })?;
+            let dependencies = files
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, (_, parsed))| parsed.clone())
+                .collect::<Vec<Protocol>>();
+            let (parsed, warnings) = parse_inner(&files[i].0, Some(&dependencies))
+                .map_err(|error| BuildError::Protocol { path: protocol.clone(), error })?;
+            if std::env::var_os("OUT_DIR").is_some() {
+                for warning in warnings {
+                    println!("cargo:warning={}:{}", protocol.display(), warning);
+                }
+            }
+            for &(_, kind, suffix) in kinds.iter().filter(|&&(enabled, _, _)| enabled) {
+                let code = match generate_protocol_code(parsed.clone(), kind, &self.options) {
+                    Ok(code) => code.to_string(),
+                    Err(GenerateError::Protocol(error)) => {
+                        return Err(BuildError::Protocol { path: protocol.clone(), error })
+                    }
+                    Err(GenerateError::Options(message)) => {
+                        return Err(BuildError::Options { path: protocol.clone(), message })
+                    }
+                };
+                let code =
+                    if self.rustfmt { try_format_rust_code(&code).unwrap_or(code) } else { code };
+                let mut file_name = stem.to_owned();
+                file_name.push(format!("_{}.rs", suffix));
+                let path = out_dir.join(file_name);
+                // avoid touching files that did not change, to not trigger needless rebuilds
+                if std::fs::read_to_string(&path).ok().as_deref() != Some(code.as_str()) {
+                    std::fs::write(&path, code)?;
+                }
+                written.push(path);
+            }
+        }
+        Ok(written)
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn builder() {
+        let out_dir = std::env::temp_dir().join(format!("wayland-codegen-{}", std::process::id()));
+        let written = super::Builder::new()
+            .protocol("./tests/scanner_assets/test-protocol.xml")
+            .out_dir(&out_dir)
+            .client(true)
+            .handlers(true)
+            .serde(true)
+            .mock(true)
+            .rerun_if_changed(false)
+            .generate()
+            .unwrap();
+        assert_eq!(
+            written,
+            vec![
+                out_dir.join("test-protocol_interfaces.rs"),
+                out_dir.join("test-protocol_client.rs")
+            ]
+        );
+
+        let generated = std::fs::read_to_string(&written[1]).unwrap();
+        let reference =
+            std::fs::read_to_string("./tests/scanner_assets/test-client-code.rs").unwrap();
+        let reference = crate::format_rust_code(&reference);
+        std::fs::remove_dir_all(&out_dir).unwrap();
+
+        if reference != generated {
+            let diff = similar::TextDiff::from_lines(&reference, &generated);
+            print!("{}", diff.unified_diff().context_radius(10).header("reference", "generated"));
+            panic!("Generated does not match reference!")
+        }
+    }
+}
@@ -4,15 +4,18 @@ use quote::{format_ident, quote};
 
 use crate::{
     protocol::{Interface, Protocol, Type},
-    util::{description_to_doc_attr, dotted_to_relname, is_keyword, snake_to_camel, to_doc_attr},
-    Side,
+    util::{
+        deprecated_attr, description_to_doc, description_to_doc_attr, dotted_to_relname,
+        is_keyword, snake_to_camel, to_doc_attr,
+    },
+    Options, Side,
 };
 
-pub fn generate_client_objects(protocol: &Protocol) -> TokenStream {
-    protocol.interfaces.iter().map(generate_objects_for).collect()
+pub fn generate_client_objects(protocol: &Protocol, options: &Options) -> TokenStream {
+    protocol.interfaces.iter().map(|iface| generate_objects_for(iface, options)).collect()
 }
 
-fn generate_objects_for(interface: &Interface) -> TokenStream {
+fn generate_objects_for(interface: &Interface, options: &Options) -> TokenStream {
     let mod_name = Ident::new(&interface.name, Span::call_site());
     let mod_doc = interface.description.as_ref().map(description_to_doc_attr);
     let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
@@ -37,20 +40,30 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
     let parse_body = crate::common::gen_parse_body(interface, Side::Client);
     let write_body = crate::common::gen_write_body(interface, Side::Client);
     let methods = gen_methods(interface);
+    let handler = options.handlers.then(|| crate::handlers::generate_client_handler(interface));
+    let serialize =
+        options.serde.then(|| crate::serialize::generate_serialize(interface, Side::Client));
 
     let event_ref = if interface.events.is_empty() {
         "This interface has no events."
     } else {
         "See also the [Event] enum for this interface."
     };
-    let docs = match &interface.description {
-        Some((short, long)) => format!("{}\n\n{}\n\n{}", short, long, event_ref),
-        None => format!("{}\n\n{}", interface.name, event_ref),
-    };
+    let mut docs = description_to_doc(interface.description.as_ref(), Some(&interface.name));
+    docs.push_str("\n\n");
+    docs.push_str(event_ref);
+    if interface.frozen {
+        docs.push_str("\n\nThis interface is frozen: no new versions of it will be released.");
+    }
+    docs.push_str("\n\n");
+    docs.push_str(&crate::common::versions_table(interface));
     let doc_attr = to_doc_attr(&docs);
+    // the generated code itself uses the deprecated items
+    let allow_deprecated = interface.has_deprecated().then(|| quote! { #[allow(deprecated)] });
 
     quote! {
         #mod_doc
+        #allow_deprecated
         pub mod #mod_name {
             use std::sync::Arc;
             use std::os::unix::io::OwnedFd;
@@ -176,6 +189,9 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
             impl #iface_name {
                 #methods
             }
+
+            #handler
+            #serialize
         }
     }
 }
@@ -228,7 +244,13 @@ fn gen_methods(interface: &Interface) -> TokenStream {
             Some(quote! {
                 #arg_name: #arg_type
             })
-        });
+        }).collect::<Vec<_>>();
+        // the names of the arguments of the method, to forward them from its version-checked variant
+        let call_args = request.args.iter().flat_map(|arg| match arg.typ {
+            Type::NewId if arg.interface.is_none() => Some(format_ident!("version")),
+            Type::NewId => None,
+            _ => Some(format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name)),
+        }).collect::<Vec<_>>();
 
         let enum_args = request.args.iter().flat_map(|arg| {
             let arg_name = format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
@@ -255,46 +277,67 @@ fn gen_methods(interface: &Interface) -> TokenStream {
             .description
             .as_ref()
             .map(description_to_doc_attr);
+        let destructor_doc = crate::common::destructor_note(request, true).map(|note| {
+            let separator = doc_attr.is_some().then(|| quote! { #[doc = ""] });
+            quote! { #separator #[doc = #note] }
+        });
+        let doc_attr = quote! { #doc_attr #destructor_doc };
+        let deprecated = deprecated_attr(request.deprecated_since);
+        let since_const = format_ident!("REQ_{}_SINCE", request.name.to_ascii_uppercase());
+        let try_name = format_ident!("try_{}", request.name);
+        let since_doc = (request.since > 1).then(|| {
+            let doc = format!(
+                "Only available since version {} of the interface ([`{}`]), see [`{}()`](Self::{}) \
+                 for a version-checked variant.",
+                request.since, since_const, try_name, try_name
+            );
+            let separator = (!doc_attr.is_empty()).then(|| quote! { #[doc = ""] });
+            quote! { #separator #[doc = #doc] }
+        });
+        let doc_attr = quote! { #doc_attr #since_doc #deprecated };
 
-        match created_interface {
+        let (generics, params, ret, body) = match created_interface {
             Some(Some(ref created_interface)) => {
                 // a regular creating request
                 let created_iface_mod = Ident::new(created_interface, Span::call_site());
                 let created_iface_type = Ident::new(&snake_to_camel(created_interface), Span::call_site());
-                quote! {
-                    #doc_attr
-                    #[allow(clippy::too_many_arguments)]
-                    pub fn #method_name<U: Send + Sync + 'static, D: Dispatch<super::#created_iface_mod::#created_iface_type, U> + 'static>(&self, #(#fn_args,)* qh: &QueueHandle<D>, udata: U) -> super::#created_iface_mod::#created_iface_type {
+                (
+                    quote! { <U: Send + Sync + 'static, D: Dispatch<super::#created_iface_mod::#created_iface_type, U> + 'static> },
+                    quote! { #(#fn_args,)* qh: &QueueHandle<D>, udata: U },
+                    Some(quote! { super::#created_iface_mod::#created_iface_type }),
+                    quote! {
                         self.send_constructor(
                             Request::#enum_variant {
                                 #(#enum_args),*
                             },
                             qh.make_data::<super::#created_iface_mod::#created_iface_type, U>(udata),
                         ).unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
-                    }
-                }
+                    },
+                )
             },
             Some(None) => {
                 // a bind-like request
-                quote! {
-                    #doc_attr
-                    #[allow(clippy::too_many_arguments)]
-                    pub fn #method_name<I: Proxy + 'static, U: Send + Sync + 'static, D: Dispatch<I, U> + 'static>(&self, #(#fn_args,)* qh: &QueueHandle<D>, udata: U) -> I {
+                (
+                    quote! { <I: Proxy + 'static, U: Send + Sync + 'static, D: Dispatch<I, U> + 'static> },
+                    quote! { #(#fn_args,)* qh: &QueueHandle<D>, udata: U },
+                    Some(quote! { I }),
+                    quote! {
                         self.send_constructor(
                             Request::#enum_variant {
                                 #(#enum_args),*
                             },
                             qh.make_data::<I, U>(udata),
                         ).unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
-                    }
-                }
+                    },
+                )
             },
             None => {
                 // a non-creating request
-                quote! {
-                    #doc_attr
-                    #[allow(clippy::too_many_arguments)]
-                    pub fn #method_name(&self, #(#fn_args),*) {
+                (
+                    quote! {},
+                    quote! { #(#fn_args),* },
+                    None,
+                    quote! {
                         let backend = match self.backend.upgrade() {
                             Some(b) => b,
                             None => return,
@@ -307,8 +350,45 @@ fn gen_methods(interface: &Interface) -> TokenStream {
                             },
                             None
                         );
-                    }
+                    },
+                )
+            }
+        };
+
+        let ret_arrow = ret.as_ref().map(|ret| quote! { -> #ret });
+        let method = quote! {
+            #doc_attr
+            #[allow(clippy::too_many_arguments)]
+            pub fn #method_name #generics(&self, #params) #ret_arrow {
+                #body
+            }
+        };
+        if request.since == 1 {
+            return method;
+        }
+
+        let call_args = if created_interface.is_some() {
+            quote! { #(#call_args,)* qh, udata }
+        } else {
+            quote! { #(#call_args),* }
+        };
+        let ret = ret.unwrap_or_else(|| quote! { () });
+        let try_doc = format!(
+            "Version-checked variant of [`{}()`](Self::{})\n\nReturns an error without sending the request if \
+             the version of this object is lower than {}.",
+            method_name, method_name, request.since
+        );
+        quote! {
+            #method
+
+            #[doc = #try_doc]
+            #deprecated
+            #[allow(clippy::too_many_arguments)]
+            pub fn #try_name #generics(&self, #params) -> Result<#ret, super::wayland_client::VersionTooLow> {
+                if self.version < #since_const {
+                    return Err(super::wayland_client::VersionTooLow { required: #since_const, bound: self.version });
                 }
+                Ok(self.#method_name(#call_args))
             }
         }
     }).collect()
@@ -318,10 +398,13 @@ fn gen_methods(interface: &Interface) -> TokenStream {
 mod tests {
     #[test]
     fn client_gen() {
-        let protocol_file =
-            std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
-        let protocol_parsed = crate::parse::parse(protocol_file);
-        let generated: String = super::generate_client_objects(&protocol_parsed).to_string();
+        let protocol_file = std::fs::read("./tests/scanner_assets/test-protocol.xml").unwrap();
+        let protocol_parsed = crate::parse::parse(&protocol_file).unwrap();
+        let generated: String = super::generate_client_objects(
+            &protocol_parsed,
+            &crate::Options { handlers: true, serde: true, ..Default::default() },
+        )
+        .to_string();
         let generated = crate::format_rust_code(&generated);
 
         let reference =
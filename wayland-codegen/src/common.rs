@@ -15,16 +15,19 @@ impl ToTokens for Enum {
         let enum_decl;
         let enum_impl;
 
-        let doc_attr = self.description.as_ref().map(description_to_doc_attr);
+        let mut docs = description_to_doc(self.description.as_ref(), None);
+        if self.since > 1 {
+            write!(docs, "\n\nOnly available since version {} of the interface", self.since)
+                .unwrap();
+        }
+        let doc_attr = (!docs.is_empty()).then(|| to_doc_attr(&docs));
         let ident = Ident::new(&snake_to_camel(&self.name), Span::call_site());
 
         if self.bitfield {
             let entries = self.entries.iter().map(|entry| {
-                let doc_attr = entry
-                    .description
-                    .as_ref()
-                    .map(description_to_doc_attr)
-                    .or_else(|| entry.summary.as_ref().map(|s| to_doc_attr(s)));
+                let doc_attr = entry_doc_attr(entry);
+
+                let deprecated = deprecated_attr(entry.deprecated_since.map(Into::into));
 
                 let prefix = if entry.name.chars().next().unwrap().is_numeric() { "_" } else { "" };
                 let ident = format_ident!("{}{}", prefix, snake_to_camel(&entry.name));
@@ -33,6 +36,7 @@ impl ToTokens for Enum {
 
                 quote! {
                     #doc_attr
+                    #deprecated
                     const #ident = #value;
                 }
             });
@@ -61,11 +65,9 @@ impl ToTokens for Enum {
             };
         } else {
             let variants = self.entries.iter().map(|entry| {
-                let doc_attr = entry
-                    .description
-                    .as_ref()
-                    .map(description_to_doc_attr)
-                    .or_else(|| entry.summary.as_ref().map(|s| to_doc_attr(s)));
+                let doc_attr = entry_doc_attr(entry);
+
+                let deprecated = deprecated_attr(entry.deprecated_since.map(Into::into));
 
                 let prefix = if entry.name.chars().next().unwrap().is_numeric() { "_" } else { "" };
                 let variant = format_ident!("{}{}", prefix, snake_to_camel(&entry.name));
@@ -74,6 +76,7 @@ impl ToTokens for Enum {
 
                 quote! {
                     #doc_attr
+                    #deprecated
                     #variant = #value
                 }
             });
@@ -122,6 +125,14 @@ impl ToTokens for Enum {
     }
 }
 
+fn entry_doc_attr(entry: &Entry) -> Option<TokenStream> {
+    let mut docs = description_to_doc(entry.description.as_ref(), entry.summary.as_ref());
+    if entry.since > 1 {
+        write!(docs, "\n\nOnly available since version {} of the interface", entry.since).unwrap();
+    }
+    (!docs.is_empty()).then(|| to_doc_attr(&docs))
+}
+
 pub(crate) fn gen_msg_constants(requests: &[Message], events: &[Message]) -> TokenStream {
     let req_constants = requests.iter().enumerate().map(|(opcode, msg)| {
         let since_cstname = format_ident!("REQ_{}_SINCE", msg.name.to_ascii_uppercase());
@@ -154,6 +165,69 @@ pub(crate) fn gen_msg_constants(requests: &[Message], events: &[Message]) -> Tok
     }
 }
 
+/// Documentation note of a destructor message, `sent` being whether the documented code sends it
+pub(crate) fn destructor_note(msg: &Message, sent: bool) -> Option<String> {
+    (msg.typ == Some(Type::Destructor)).then(|| {
+        format!(
+            "This is a destructor, once {} this object cannot be used any longer.",
+            if sent { "sent" } else { "received" }
+        )
+    })
+}
+
+/// Documentation linking to the interface and enum referenced by an argument
+pub(crate) fn arg_links(arg: &Arg) -> Option<String> {
+    let interface = arg.interface.as_ref().map(|iface| {
+        let link =
+            format!("[`{}`](super::{}::{})", snake_to_camel(iface), iface, snake_to_camel(iface));
+        if arg.typ == Type::NewId {
+            format!(
+                "Creates a new {} object, see the [`{}`](super::{}) module.",
+                link, iface, iface
+            )
+        } else {
+            format!("Object of interface {}.", link)
+        }
+    });
+    let enu = arg.enum_.as_ref().map(|enu| {
+        let path = dotted_to_relname(enu).to_string().replace(' ', "");
+        let name = enu.rsplit('.').next().unwrap();
+        format!("Values from the [`{}`]({}) enum.", snake_to_camel(name), path)
+    });
+    match (interface, enu) {
+        (Some(interface), Some(enu)) => Some(format!("{}\n{}", interface, enu)),
+        (interface, enu) => interface.or(enu),
+    }
+}
+
+/// Documentation section listing the messages introduced by each version of an interface
+pub(crate) fn versions_table(interface: &Interface) -> String {
+    let list = |messages: &[Message], kind: &str, version: u32| {
+        let links = messages
+            .iter()
+            .filter(|msg| msg.since == version)
+            .map(|msg| format!("[`{}`]({}::{})", msg.name, kind, snake_to_camel(&msg.name)))
+            .collect::<Vec<_>>();
+        if links.is_empty() {
+            "-".into()
+        } else {
+            links.join(", ")
+        }
+    };
+
+    let mut table = String::from("# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n");
+    for version in 1..=interface.version {
+        let requests = list(&interface.requests, "Request", version);
+        let events = list(&interface.events, "Event", version);
+        // skip the versions which did not change this interface
+        if version > 1 && requests == "-" && events == "-" {
+            continue;
+        }
+        writeln!(table, "| {} | {} | {} |", version, requests, events).unwrap();
+    }
+    table
+}
+
 pub(crate) fn gen_message_enum(
     name: &Ident,
     side: Side,
@@ -167,13 +241,8 @@ pub(crate) fn gen_message_enum(
             if let Some((ref short, ref long)) = msg.description {
                 write!(docs, "{}\n\n{}\n", short, long.trim()).unwrap();
             }
-            if let Some(Type::Destructor) = msg.typ {
-                write!(
-                    docs,
-                    "\nThis is a destructor, once {} this object cannot be used any longer.",
-                    if receiver { "received" } else { "sent" },
-                )
-                .unwrap()
+            if let Some(note) = destructor_note(msg, !receiver) {
+                write!(docs, "\n{}", note).unwrap();
             }
             if msg.since > 1 {
                 write!(docs, "\nOnly available since version {} of the interface", msg.since)
@@ -181,102 +250,42 @@ pub(crate) fn gen_message_enum(
             }
 
             let doc_attr = to_doc_attr(&docs);
+            let deprecated = deprecated_attr(msg.deprecated_since);
             let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
-            let msg_variant_decl =
-                if msg.args.is_empty() {
-                    msg_name.into_token_stream()
-                } else {
-                    let fields = msg.args.iter().flat_map(|arg| {
-                let field_name =
-                    format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
-                let field_type_inner = if let Some(ref enu) = arg.enum_ {
-                    let enum_type = dotted_to_relname(enu);
-                    quote! { WEnum<#enum_type> }
-                } else {
-                    match arg.typ {
-                        Type::Uint => quote! { u32 },
-                        Type::Int => quote! { i32 },
-                        Type::Fixed => quote! { f64 },
-                        Type::String => quote! { String },
-                        Type::Array => quote! { Vec<u8> },
-                        Type::Fd => {
-                            if receiver {
-                                quote! { OwnedFd }
-                            } else {
-                                quote! { std::os::unix::io::BorrowedFd<'a> }
-                            }
-                        }
-                        Type::Object => {
-                            if let Some(ref iface) = arg.interface {
-                                let iface_mod = Ident::new(iface, Span::call_site());
-                                let iface_type =
-                                    Ident::new(&snake_to_camel(iface), Span::call_site());
-                                quote! { super::#iface_mod::#iface_type }
-                            } else if side == Side::Client {
-                                quote! { super::wayland_client::ObjectId }
-                            } else {
-                                quote! { super::wayland_server::ObjectId }
-                            }
-                        }
-                        Type::NewId if !receiver && side == Side::Client => {
-                            // Client-side sending does not have a pre-existing object
-                            // so skip serializing it
-                            if arg.interface.is_some() {
-                                return None;
-                            } else {
-                                quote! { (&'static Interface, u32) }
-                            }
-                        }
-                        Type::NewId => {
-                            if let Some(ref iface) = arg.interface {
-                                let iface_mod = Ident::new(iface, Span::call_site());
-                                let iface_type =
-                                    Ident::new(&snake_to_camel(iface), Span::call_site());
-                                if receiver && side == Side::Server {
-                                    quote! { New<super::#iface_mod::#iface_type> }
-                                } else {
-                                    quote! { super::#iface_mod::#iface_type }
-                                }
-                            } else {
-                                // bind-like function
-                                if side == Side::Client {
-                                    quote! { (String, u32, super::wayland_client::ObjectId) }
-                                } else {
-                                    quote! { (String, u32, super::wayland_server::ObjectId) }
-                                }
-                            }
-                        }
-                        Type::Destructor => panic!("An argument cannot have type \"destructor\"."),
+            let msg_variant_decl = if msg.args.is_empty() {
+                msg_name.into_token_stream()
+            } else {
+                let fields = msg.args.iter().flat_map(|arg| {
+                    let field_name = format_ident!(
+                        "{}{}",
+                        if is_keyword(&arg.name) { "_" } else { "" },
+                        arg.name
+                    );
+                    let field_type = message_field_type(arg, side, receiver)?;
+
+                    let mut docs =
+                        description_to_doc(arg.description.as_ref(), arg.summary.as_ref());
+                    if let Some(links) = arg_links(arg) {
+                        write!(docs, "\n\n{}", links).unwrap();
                     }
-                };
-
-                let field_type = if arg.allow_null {
-                    quote! { Option<#field_type_inner> }
-                } else {
-                    field_type_inner.into_token_stream()
-                };
+                    let doc_attr = (!docs.trim().is_empty()).then(|| to_doc_attr(&docs));
 
-                let doc_attr = arg
-                    .description
-                    .as_ref()
-                    .map(description_to_doc_attr)
-                    .or_else(|| arg.summary.as_ref().map(|s| to_doc_attr(s)));
-
-                Some(quote! {
-                    #doc_attr
-                    #field_name: #field_type
-                })
-            });
+                    Some(quote! {
+                        #doc_attr
+                        #field_name: #field_type
+                    })
+                });
 
-                    quote! {
-                        #msg_name {
-                            #(#fields,)*
-                        }
+                quote! {
+                    #msg_name {
+                        #(#fields,)*
                     }
-                };
+                }
+            };
 
             quote! {
                 #doc_attr
+                #deprecated
                 #msg_variant_decl
             }
         })
@@ -296,6 +305,20 @@ pub(crate) fn gen_message_enum(
         }
     });
 
+    let sinces = messages.iter().map(|msg| {
+        let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
+        let since = msg.since;
+        if msg.args.is_empty() {
+            quote! {
+                #name::#msg_name => #since
+            }
+        } else {
+            quote! {
+                #name::#msg_name { .. } => #since
+            }
+        }
+    });
+
     // Placeholder to allow generic argument to be added later, without ABI
     // break.
     // TODO Use never type.
@@ -325,6 +348,14 @@ pub(crate) fn gen_message_enum(
                     #phantom_case
                 }
             }
+
+            #[doc="Get the minimal version of the interface supporting this message"]
+            pub fn since(&self) -> u32 {
+                match *self {
+                    #(#sinces,)*
+                    #phantom_case
+                }
+            }
         }
     }
 }
@@ -629,3 +660,71 @@ pub(crate) fn gen_write_body(interface: &Interface, side: Side) -> TokenStream {
         }
     }
 }
+
+/// Type of the field of a message enum for this argument, `None` if the argument is skipped
+pub(crate) fn message_field_type(arg: &Arg, side: Side, receiver: bool) -> Option<TokenStream> {
+    let field_type_inner = if let Some(ref enu) = arg.enum_ {
+        let enum_type = dotted_to_relname(enu);
+        quote! { WEnum<#enum_type> }
+    } else {
+        match arg.typ {
+            Type::Uint => quote! { u32 },
+            Type::Int => quote! { i32 },
+            Type::Fixed => quote! { f64 },
+            Type::String => quote! { String },
+            Type::Array => quote! { Vec<u8> },
+            Type::Fd => {
+                if receiver {
+                    quote! { OwnedFd }
+                } else {
+                    quote! { std::os::unix::io::BorrowedFd<'a> }
+                }
+            }
+            Type::Object => {
+                if let Some(ref iface) = arg.interface {
+                    let iface_mod = Ident::new(iface, Span::call_site());
+                    let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
+                    quote! { super::#iface_mod::#iface_type }
+                } else if side == Side::Client {
+                    quote! { super::wayland_client::ObjectId }
+                } else {
+                    quote! { super::wayland_server::ObjectId }
+                }
+            }
+            Type::NewId if !receiver && side == Side::Client => {
+                // Client-side sending does not have a pre-existing object
+                // so skip serializing it
+                if arg.interface.is_some() {
+                    return None;
+                } else {
+                    quote! { (&'static Interface, u32) }
+                }
+            }
+            Type::NewId => {
+                if let Some(ref iface) = arg.interface {
+                    let iface_mod = Ident::new(iface, Span::call_site());
+                    let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
+                    if receiver && side == Side::Server {
+                        quote! { New<super::#iface_mod::#iface_type> }
+                    } else {
+                        quote! { super::#iface_mod::#iface_type }
+                    }
+                } else {
+                    // bind-like function
+                    if side == Side::Client {
+                        quote! { (String, u32, super::wayland_client::ObjectId) }
+                    } else {
+                        quote! { (String, u32, super::wayland_server::ObjectId) }
+                    }
+                }
+            }
+            Type::Destructor => panic!("An argument cannot have type \"destructor\"."),
+        }
+    };
+
+    Some(if arg.allow_null {
+        quote! { Option<#field_type_inner> }
+    } else {
+        field_type_inner.into_token_stream()
+    })
+}
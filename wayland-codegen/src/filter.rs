@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+
+use crate::{
+    protocol::{Interface, Protocol},
+    CodeKind, Options,
+};
+
+/// Names of the other interfaces an interface refers to, through its arguments or enums
+fn references(interface: &Interface) -> impl Iterator<Item = &str> {
+    interface.requests.iter().chain(&interface.events).flat_map(|msg| &msg.args).flat_map(|arg| {
+        let enum_interface =
+            arg.enum_.as_ref().and_then(|enu| enu.split_once('.')).map(|(iface, _)| iface);
+        arg.interface.as_deref().into_iter().chain(enum_interface)
+    })
+}
+
+/// Apply the `only`, `exclude` and `extern` options to a protocol
+///
+/// Returns the interfaces to generate, and the imports of the `extern` interfaces.
+pub(crate) fn apply(
+    mut protocol: Protocol,
+    kind: CodeKind,
+    options: &Options,
+) -> Result<(Protocol, TokenStream), String> {
+    if options.only.is_some() && !options.exclude.is_empty() {
+        return Err("`only` and `exclude` cannot be used together".into());
+    }
+
+    let defined =
+        protocol.interfaces.iter().map(|iface| iface.name.clone()).collect::<HashSet<_>>();
+    let referenced =
+        protocol.interfaces.iter().flat_map(references).map(String::from).collect::<HashSet<_>>();
+
+    for (option, names) in
+        [("only", options.only.as_deref().unwrap_or(&[])), ("exclude", &options.exclude)]
+    {
+        if let Some(name) = names.iter().find(|name| !defined.contains(*name)) {
+            return Err(format!("Unknown interface `{}` in `{}`", name, option));
+        }
+    }
+
+    let mut externs = HashSet::new();
+    let mut imports = TokenStream::new();
+    for (name, path) in &options.externs {
+        if !defined.contains(name) && !referenced.contains(name) {
+            return Err(format!("Unknown interface `{}` in `extern`", name));
+        }
+        if !externs.insert(name.as_str()) {
+            return Err(format!("Duplicate interface `{}` in `extern`", name));
+        }
+        if options.only.as_ref().map_or(false, |only| only.contains(name)) {
+            return Err(format!("Interface `{}` cannot be both in `only` and in `extern`", name));
+        }
+        let path = path
+            .parse::<TokenStream>()
+            .map_err(|_| format!("Invalid path `{}` for interface `{}` in `extern`", path, name))?;
+        let items = match kind {
+            CodeKind::Interfaces => {
+                let const_name = format_ident!("{}_INTERFACE", name.to_ascii_uppercase());
+                let c_name = format_ident!("{}_interface", name);
+                quote! { {#const_name, #c_name} }
+            }
+            CodeKind::Client | CodeKind::Server => {
+                Ident::new(name, Span::call_site()).into_token_stream()
+            }
+        };
+        imports.extend(quote! {
+            #[allow(unused_imports)]
+            use #path::#items;
+        });
+    }
+
+    let generated = |name: &str| {
+        !externs.contains(name)
+            && !options.exclude.iter().any(|excluded| excluded == name)
+            && options.only.as_ref().map_or(true, |only| only.iter().any(|n| n == name))
+    };
+    protocol.interfaces.retain(|iface| generated(&iface.name));
+
+    // interfaces of other protocols are expected to be imported, but the ones of this protocol that are
+    // not generated must be mapped to their module
+    for interface in &protocol.interfaces {
+        if let Some(missing) = references(interface)
+            .find(|name| defined.contains(*name) && !generated(name) && !externs.contains(name))
+        {
+            return Err(format!(
+                "Interface `{}` refers to `{}`, which is not generated: generate it too, or map it using `extern`",
+                interface.name, missing
+            ));
+        }
+    }
+
+    Ok((protocol, imports))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CodeKind, Options};
+
+    fn apply(options: Options) -> Result<(Vec<String>, String), String> {
+        let protocol_file = std::fs::read("./tests/scanner_assets/test-protocol.xml").unwrap();
+        let protocol = crate::parse::parse(&protocol_file).unwrap();
+        let (protocol, imports) = super::apply(protocol, CodeKind::Client, &options)?;
+        Ok((protocol.interfaces.into_iter().map(|i| i.name).collect(), imports.to_string()))
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn filter_interfaces() {
+        let (interfaces, imports) =
+            apply(Options { only: Some(names(&["wl_callback"])), ..Default::default() }).unwrap();
+        assert_eq!(interfaces, ["wl_callback"]);
+        assert!(imports.is_empty());
+
+        let (interfaces, _) =
+            apply(Options { exclude: names(&["test_global", "wl_display"]), ..Default::default() })
+                .unwrap();
+        assert_eq!(interfaces, ["wl_registry", "wl_callback", "secondary", "tertiary", "quad"]);
+
+        let (interfaces, imports) = apply(Options {
+            only: Some(names(&["test_global", "secondary"])),
+            externs: vec![
+                ("tertiary".into(), "crate::other".into()),
+                ("quad".into(), "crate::other".into()),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(interfaces, ["test_global", "secondary"]);
+        assert_eq!(
+            imports,
+            "# [allow (unused_imports)] use crate :: other :: tertiary ; \
+             # [allow (unused_imports)] use crate :: other :: quad ;"
+        );
+    }
+
+    #[test]
+    fn invalid_options() {
+        let error = |options| apply(options).unwrap_err();
+        assert_eq!(
+            error(Options {
+                only: Some(names(&["wl_callback"])),
+                exclude: names(&["wl_display"]),
+                ..Default::default()
+            }),
+            "`only` and `exclude` cannot be used together"
+        );
+        assert_eq!(
+            error(Options { exclude: names(&["wl_foo"]), ..Default::default() }),
+            "Unknown interface `wl_foo` in `exclude`"
+        );
+        assert_eq!(
+            error(Options {
+                externs: vec![("wl_foo".into(), "crate".into())],
+                ..Default::default()
+            }),
+            "Unknown interface `wl_foo` in `extern`"
+        );
+        assert_eq!(
+            error(Options {
+                externs: vec![("quad".into(), "crate::other)".into())],
+                ..Default::default()
+            }),
+            "Invalid path `crate::other)` for interface `quad` in `extern`"
+        );
+        assert_eq!(
+            error(Options { exclude: names(&["quad"]), ..Default::default() }),
+            "Interface `test_global` refers to `quad`, which is not generated: \
+             generate it too, or map it using `extern`"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use proc_macro2::{Ident, Span, TokenStream};
+
+use quote::{format_ident, quote};
+
+use crate::{
+    common::message_field_type,
+    protocol::{Arg, Interface, Message, Type},
+    util::{deprecated_attr, description_to_doc_attr, is_keyword, snake_to_camel, to_doc_attr},
+    Side,
+};
+
+/// Names of the arguments of the handler methods and of the `Dispatch` adapter, that message
+/// arguments must not shadow
+const RESERVED_NAMES: &[&str] = &[
+    "state",
+    "client",
+    "resource",
+    "request",
+    "data",
+    "dhandle",
+    "data_init",
+    "proxy",
+    "event",
+    "conn",
+    "qhandle",
+];
+
+fn arg_binding(arg: &Arg) -> Ident {
+    let prefix =
+        if is_keyword(&arg.name) || RESERVED_NAMES.contains(&arg.name.as_str()) { "_" } else { "" };
+    format_ident!("{}{}", prefix, arg.name)
+}
+
+/// Generates the trait method for a message, and the `match` arm forwarding it from the adapter
+fn gen_message(
+    message: &Message,
+    side: Side,
+    msg_type: &Ident,
+    context_args: &TokenStream,
+    call_args: &TokenStream,
+) -> (TokenStream, TokenStream) {
+    let method_name =
+        format_ident!("{}{}", if is_keyword(&message.name) { "_" } else { "" }, message.name);
+    let variant = Ident::new(&snake_to_camel(&message.name), Span::call_site());
+
+    let mut docs = match message.description {
+        Some(ref description) => description_to_doc_attr(description),
+        None => to_doc_attr(&message.name),
+    };
+    if let Some(deprecated) = deprecated_attr(message.deprecated_since) {
+        docs.extend(deprecated);
+    }
+
+    let params = message.args.iter().map(|arg| {
+        let binding = arg_binding(arg);
+        let typ = message_field_type(arg, side, true).unwrap();
+        quote! { #binding: #typ }
+    });
+    let bindings = message.args.iter().map(arg_binding).collect::<Vec<_>>();
+    let fields = message.args.iter().zip(&bindings).map(|(arg, binding)| {
+        let field = format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+        if &field == binding {
+            quote! { #field }
+        } else {
+            quote! { #field: #binding }
+        }
+    });
+    let pattern = if message.args.is_empty() {
+        quote! { #msg_type::#variant }
+    } else {
+        quote! { #msg_type::#variant { #(#fields),* } }
+    };
+
+    let method = quote! {
+        #docs
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn #method_name(&mut self, #context_args, #(#params),*) {}
+    };
+    let arm = quote! {
+        #pattern => <D as Handler<U>>::#method_name(state, #call_args, #(#bindings),*)
+    };
+    (method, arm)
+}
+
+pub(crate) fn generate_client_handler(interface: &Interface) -> TokenStream {
+    let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
+    let docs = format!(
+        "Handler for the events of [{iface}], with one method per event
+
+All methods do nothing by default. To use it, implement it for your state and delegate the
+dispatching of this interface to [{iface}], using `delegate_dispatch!(State: [{iface}: UserData] => {iface});`.",
+        iface = iface_name
+    );
+
+    let context_args = quote! {
+        proxy: &#iface_name,
+        data: &U,
+        conn: &Connection,
+        qhandle: &QueueHandle<Self>
+    };
+    let call_args = quote! { proxy, data, conn, qhandle };
+    let (methods, arms): (Vec<_>, Vec<_>) = interface
+        .events
+        .iter()
+        .map(|event| {
+            gen_message(event, Side::Client, &format_ident!("Event"), &context_args, &call_args)
+        })
+        .unzip();
+
+    let creates_children = interface.events.iter().any(|event| {
+        event.args.iter().any(|arg| arg.typ == Type::NewId && arg.interface.is_some())
+    });
+    let (child_method, child_adapter) = if creates_children {
+        (
+            quote! {
+                /// Initialize the user data of objects created by events
+                ///
+                /// See [`Dispatch::event_created_child()`].
+                fn event_created_child(opcode: u16, _qhandle: &QueueHandle<Self>) -> Arc<dyn ObjectData> {
+                    panic!(
+                        "Missing event_created_child specialization for event opcode {} of {}",
+                        opcode,
+                        <#iface_name as Proxy>::interface().name
+                    );
+                }
+            },
+            quote! {
+                fn event_created_child(opcode: u16, qhandle: &QueueHandle<D>) -> Arc<dyn ObjectData> {
+                    <D as Handler<U>>::event_created_child(opcode, qhandle)
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    quote! {
+        #[doc = #docs]
+        pub trait Handler<U>: Sized {
+            #(#methods)*
+            #child_method
+        }
+
+        impl<U, D> Dispatch<#iface_name, U, D> for #iface_name
+        where
+            D: Handler<U> + Dispatch<#iface_name, U, D>,
+        {
+            fn event(
+                state: &mut D,
+                proxy: &#iface_name,
+                event: Event,
+                data: &U,
+                conn: &Connection,
+                qhandle: &QueueHandle<D>,
+            ) {
+                match event {
+                    #(#arms,)*
+                }
+            }
+
+            #child_adapter
+        }
+    }
+}
+
+pub(crate) fn generate_server_handler(interface: &Interface) -> TokenStream {
+    let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
+    let docs = format!(
+        "Handler for the requests of [{iface}], with one method per request
+
+All methods do nothing by default. To use it, implement it for your state and delegate the
+dispatching of this interface to [{iface}], using `delegate_dispatch!(State: [{iface}: UserData] => {iface});`.",
+        iface = iface_name
+    );
+
+    let context_args = quote! {
+        client: &super::wayland_server::Client,
+        resource: &#iface_name,
+        data: &U,
+        dhandle: &DisplayHandle,
+        data_init: &mut super::wayland_server::DataInit<'_, Self>
+    };
+    let call_args = quote! { client, resource, data, dhandle, data_init };
+    let (methods, arms): (Vec<_>, Vec<_>) = interface
+        .requests
+        .iter()
+        .map(|request| {
+            gen_message(request, Side::Server, &format_ident!("Request"), &context_args, &call_args)
+        })
+        .unzip();
+
+    quote! {
+        #[doc = #docs]
+        pub trait Handler<U>: Sized {
+            #(#methods)*
+
+            /// Called when the object has been destroyed, see [`Dispatch::destroyed()`]
+            #[allow(unused_variables)]
+            fn destroyed(
+                &mut self,
+                client: super::wayland_server::backend::ClientId,
+                resource: &#iface_name,
+                data: &U,
+            ) {
+            }
+        }
+
+        impl<U, D> Dispatch<#iface_name, U, D> for #iface_name
+        where
+            D: Handler<U>,
+        {
+            fn request(
+                state: &mut D,
+                client: &super::wayland_server::Client,
+                resource: &#iface_name,
+                request: Request,
+                data: &U,
+                dhandle: &DisplayHandle,
+                data_init: &mut super::wayland_server::DataInit<'_, D>,
+            ) {
+                match request {
+                    #(#arms,)*
+                }
+            }
+
+            fn destroyed(
+                state: &mut D,
+                client: super::wayland_server::backend::ClientId,
+                resource: &#iface_name,
+                data: &U,
+            ) {
+                <D as Handler<U>>::destroyed(state, client, resource, data)
+            }
+        }
+    }
+}
@@ -102,15 +102,18 @@ fn build_messagedesc_list(list: &[Message]) -> TokenStream {
                 }
             }
         });
+        let deprecated_since = message.deprecated_since.map(|version| {
+            quote! { .deprecated_since(#version) }
+        });
         quote! {
-            wayland_backend::protocol::MessageDesc {
-                name: #name,
-                signature: &[ #(#signature),* ],
-                since: #since,
-                is_destructor: #is_destructor,
-                child_interface: #child_interface,
-                arg_interfaces: &[ #(#arg_interfaces),* ],
-            }
+            wayland_backend::protocol::MessageDesc::new(
+                #name,
+                &[ #(#signature),* ],
+                #since,
+                #is_destructor,
+                #child_interface,
+                &[ #(#arg_interfaces),* ],
+            ) #deprecated_since
         }
     });
 
@@ -123,9 +126,8 @@ fn build_messagedesc_list(list: &[Message]) -> TokenStream {
 mod tests {
     #[test]
     fn interface_gen() {
-        let protocol_file =
-            std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
-        let protocol_parsed = crate::parse::parse(protocol_file);
+        let protocol_file = std::fs::read("./tests/scanner_assets/test-protocol.xml").unwrap();
+        let protocol_parsed = crate::parse::parse(&protocol_file).unwrap();
         let generated: String = super::generate(&protocol_parsed, true).to_string();
         let generated = crate::format_rust_code(&generated);
 
@@ -0,0 +1,181 @@
+//! Wayland protocol code-generation machinnery, for use in build scripts
+//!
+//! This crate generates the rust code associated with a Wayland XML protocol specification, for use
+//! with the `wayland-client`, `wayland-server` and `wayland-backend` crates. It is the engine behind
+//! the procedural macros of `wayland-scanner`, but unlike them it writes the generated code to files,
+//! which can be inspected, checked in, or shared between crates, and are only regenerated when the
+//! protocol files change.
+//!
+//! ## Example usage
+//!
+//! In your `build.rs`, generate the code for your protocol files using the [`Builder`]:
+//!
+//! ```rust,no_run
+//! wayland_codegen::Builder::new()
+//!     .protocol("./path/to/the/protocol.xml")
+//!     .client(true)
+//!     .generate()
+//!     .unwrap();
+//! ```
+//!
+//! For each protocol file, this writes `protocol_interfaces.rs` and `protocol_client.rs` into
+//! the `OUT_DIR` of your crate (named after the file stem of the XML file). You can then include them in
+//! the same module layout as the one used with the `wayland-scanner` macros:
+//!
+//! ```rust,ignore
+//! pub mod my_protocol {
+//!     use wayland_client;
+//!     // import objects from the core protocol if needed
+//!     use wayland_client::protocol::*;
+//!
+//!     pub mod __interfaces {
+//!         // import the interfaces from the core protocol if needed
+//!         use wayland_client::protocol::__interfaces::*;
+//!         include!(concat!(env!("OUT_DIR"), "/protocol_interfaces.rs"));
+//!     }
+//!     use self::__interfaces::*;
+//!
+//!     include!(concat!(env!("OUT_DIR"), "/protocol_client.rs"));
+//! }
+//! ```
+
+use std::fmt;
+
+use proc_macro2::TokenStream;
+
+mod builder;
+mod c_interfaces;
+mod client_gen;
+mod common;
+mod filter;
+mod handlers;
+mod interfaces;
+mod mock;
+mod parse;
+pub mod protocol;
+mod serialize;
+mod server_gen;
+mod util;
+
+pub use builder::{BuildError, Builder};
+pub use parse::{parse, parse_with_dependencies, warnings, ParseError};
+
+/// The kind of code to generate from a protocol file
+#[doc(hidden)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CodeKind {
+    /// Low-level interfaces, see `wayland_scanner::generate_interfaces!`
+    Interfaces,
+    /// Client-side API, see `wayland_scanner::generate_client_code!`
+    Client,
+    /// Server-side API, see `wayland_scanner::generate_server_code!`
+    Server,
+}
+
+/// Options of the generated code, see the `wayland-scanner` macros and [`Builder`]
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Generate a `Handler` trait in the module of each interface
+    pub handlers: bool,
+    /// Only generate these interfaces
+    pub only: Option<Vec<String>>,
+    /// Do not generate these interfaces
+    pub exclude: Vec<String>,
+    /// Interfaces generated in other modules, with the path of these modules
+    pub externs: Vec<(String, String)>,
+    /// Implement `serde::Serialize` for the generated types
+    pub serde: bool,
+    /// Generate a `mock` module with a recording server state
+    pub mock: bool,
+}
+
+/// An error while generating code, see [`generate_code()`]
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum GenerateError {
+    /// The protocol file is invalid
+    Protocol(ParseError),
+    /// The options are invalid for this protocol file
+    Options(String),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::Protocol(e) => write!(f, "{}", e),
+            GenerateError::Options(message) => f.write_str(message),
+        }
+    }
+}
+
+// Entry point of the procedural macros of `wayland-scanner`
+#[doc(hidden)]
+pub fn generate_code(
+    source: &[u8],
+    kind: CodeKind,
+    options: &Options,
+) -> Result<TokenStream, GenerateError> {
+    let protocol = parse::parse(source).map_err(GenerateError::Protocol)?;
+    generate_protocol_code(protocol, kind, options)
+}
+
+fn generate_protocol_code(
+    protocol: protocol::Protocol,
+    kind: CodeKind,
+    options: &Options,
+) -> Result<TokenStream, GenerateError> {
+    let (protocol, imports) =
+        filter::apply(protocol, kind, options).map_err(GenerateError::Options)?;
+    let code = match kind {
+        CodeKind::Interfaces => interfaces::generate(&protocol, true),
+        CodeKind::Client => client_gen::generate_client_objects(&protocol, options),
+        CodeKind::Server => server_gen::generate_server_objects(&protocol, options),
+    };
+    Ok(quote::quote! {
+        #imports
+        #code
+    })
+}
+
+/// Format rust code using `rustfmt`, returns `None` if it is not available or failed
+fn try_format_rust_code(code: &str) -> Option<String> {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+    let mut proc = Command::new("rustfmt")
+        .arg("--emit=stdout")
+        .arg("--edition=2018")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    {
+        let stdin = proc.stdin.as_mut().unwrap();
+        stdin.write_all(code.as_bytes()).ok()?;
+    }
+    let output = proc.wait_with_output().ok()?;
+    if output.status.success() {
+        String::from_utf8(output.stdout).ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+fn format_rust_code(code: &str) -> String {
+    match try_format_rust_code(code) {
+        Some(code) => code,
+        None => panic!("Rustfmt failed!"),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Side {
+    /// wayland client applications
+    Client,
+    /// wayland compositors
+    Server,
+}
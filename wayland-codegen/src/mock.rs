@@ -0,0 +1,360 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, Span, TokenStream};
+
+use quote::{format_ident, quote};
+
+use crate::{
+    protocol::{Interface, Message, Type},
+    util::{is_keyword, snake_to_camel},
+};
+
+/// Generates the initialization of the objects created by a request, rebuilding the request with them
+///
+/// The objects of the interfaces implemented by the mock state are given a `()` user data, and the other
+/// ones, from other protocols or not generated, are given a `ForeignObject` object data.
+fn gen_request_init(
+    interface: &Interface,
+    request: &Message,
+    implemented: &HashSet<&str>,
+) -> Option<TokenStream> {
+    let iface_mod = Ident::new(&interface.name, Span::call_site());
+    let variant = Ident::new(&snake_to_camel(&request.name), Span::call_site());
+    let created = request
+        .args
+        .iter()
+        .filter(|arg| arg.typ == Type::NewId && arg.interface.is_some())
+        .collect::<Vec<_>>();
+    if created.is_empty() {
+        return None;
+    }
+
+    let fields = request
+        .args
+        .iter()
+        .map(|arg| format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name))
+        .collect::<Vec<_>>();
+    let inits = created.iter().map(|arg| {
+        let field = format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+        let init = if implemented.contains(arg.interface.as_deref().unwrap()) {
+            quote! { data_init.init(#field, ()) }
+        } else {
+            quote! { data_init.custom_init(#field, std::sync::Arc::new(ForeignObject)) }
+        };
+        quote! {
+            let #field = #init;
+            created.push(Resource::id(&#field));
+            let #field = New::wrap(#field);
+        }
+    });
+    Some(quote! {
+        super::#iface_mod::Request::#variant { #(#fields),* } => {
+            #(#inits)*
+            super::#iface_mod::Request::#variant { #(#fields),* }
+        }
+    })
+}
+
+/// Whether a request of these interfaces creates objects of an interface they do not include
+fn creates_foreign_objects(interfaces: &[&Interface], implemented: &HashSet<&str>) -> bool {
+    interfaces
+        .iter()
+        .flat_map(|interface| &interface.requests)
+        .flat_map(|request| &request.args)
+        .any(|arg| {
+            arg.typ == Type::NewId
+                && arg.interface.as_deref().map_or(false, |name| !implemented.contains(name))
+        })
+}
+
+fn gen_dispatch(interface: &Interface, implemented: &HashSet<&str>) -> TokenStream {
+    let iface_mod = Ident::new(&interface.name, Span::call_site());
+    let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
+
+    let record = if interface.requests.is_empty() {
+        quote! { match request {} }
+    } else {
+        let inits = interface
+            .requests
+            .iter()
+            .filter_map(|request| gen_request_init(interface, request, implemented));
+        quote! {
+            let mut created = Vec::new();
+            let request = match request {
+                #(#inits,)*
+                request => request,
+            };
+            state.record(dhandle, Recorded {
+                client: client.id(),
+                resource: Resource::id(resource),
+                request: MockRequest::#iface_name(request),
+                created,
+            });
+        }
+    };
+
+    quote! {
+        impl GlobalDispatch<super::#iface_mod::#iface_name, ()> for MockState {
+            fn bind(
+                state: &mut Self,
+                _: &DisplayHandle,
+                _: &Client,
+                resource: New<super::#iface_mod::#iface_name>,
+                _: &(),
+                data_init: &mut DataInit<'_, Self>,
+            ) {
+                let resource = data_init.init(resource, ());
+                state.bound.push(Resource::id(&resource));
+            }
+        }
+
+        impl Dispatch<super::#iface_mod::#iface_name, ()> for MockState {
+            #[allow(unused_variables, unused_mut)]
+            fn request(
+                state: &mut Self,
+                client: &Client,
+                resource: &super::#iface_mod::#iface_name,
+                request: super::#iface_mod::Request,
+                _: &(),
+                dhandle: &DisplayHandle,
+                data_init: &mut DataInit<'_, Self>,
+            ) {
+                #record
+            }
+        }
+    }
+}
+
+/// Generates a `mock` module, with a server state implementing all the interfaces of the protocol by
+/// recording the requests it receives
+pub(crate) fn generate_mock<'a>(interfaces: impl Iterator<Item = &'a Interface>) -> TokenStream {
+    let interfaces = interfaces.collect::<Vec<_>>();
+    let implemented = interfaces.iter().map(|iface| iface.name.as_str()).collect::<HashSet<_>>();
+    let variants = interfaces.iter().map(|interface| {
+        let iface_mod = Ident::new(&interface.name, Span::call_site());
+        let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
+        let doc = format!("A request of [`{0}`](super::{1}::{0})", iface_name, iface_mod);
+        quote! {
+            #[doc = #doc]
+            #iface_name(super::#iface_mod::Request)
+        }
+    });
+    let dispatches = interfaces.iter().map(|interface| gen_dispatch(interface, &implemented));
+    let foreign = creates_foreign_objects(&interfaces, &implemented);
+    let foreign_doc = foreign.then(|| {
+        quote! {
+            ///
+            /// The objects of other protocols created by the requests of this one are not implemented by
+            /// [`MockState`], their requests are recorded as [`MockRequest::Foreign`].
+        }
+    });
+    let foreign_variant = foreign.then(|| {
+        quote! {
+            /// A request of an object of another protocol
+            Foreign {
+                /// Name of the interface of the object
+                interface: &'static str,
+                /// Opcode of the request
+                opcode: u16,
+            },
+        }
+    });
+    let foreign_object = foreign.then(|| {
+        quote! {
+            /// Object data of the objects of other protocols, recording their requests
+            struct ForeignObject;
+
+            impl ObjectData<MockState> for ForeignObject {
+                fn request(
+                    self: std::sync::Arc<Self>,
+                    handle: &Handle,
+                    state: &mut MockState,
+                    client: ClientId,
+                    msg: Message<ObjectId, std::os::unix::io::OwnedFd>,
+                ) -> Option<std::sync::Arc<dyn ObjectData<MockState>>> {
+                    let created = msg
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            Argument::NewId(id) => Some(id.clone()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    // the objects created by foreign objects are foreign too
+                    let child = if created.is_empty() {
+                        None
+                    } else {
+                        Some(self as std::sync::Arc<dyn ObjectData<MockState>>)
+                    };
+                    state.record(&DisplayHandle::from(handle.clone()), Recorded {
+                        client,
+                        resource: msg.sender_id.clone(),
+                        request: MockRequest::Foreign {
+                            interface: msg.sender_id.interface().name,
+                            opcode: msg.opcode,
+                        },
+                        created,
+                    });
+                    child
+                }
+
+                fn destroyed(
+                    self: std::sync::Arc<Self>,
+                    _: &Handle,
+                    _: &mut MockState,
+                    _: ClientId,
+                    _: ObjectId,
+                ) {
+                }
+            }
+        }
+    });
+    let foreign_imports = foreign.then(|| {
+        quote! {
+            use super::wayland_server::backend::{
+                protocol::{Argument, Message},
+                Handle, ObjectData,
+            };
+        }
+    });
+    // the generated code itself uses the deprecated items
+    let allow_deprecated = interfaces
+        .iter()
+        .any(|iface| iface.has_deprecated())
+        .then(|| quote! { #[allow(deprecated)] });
+
+    quote! {
+        /// Recording implementations of the interfaces of this protocol, for tests
+        ///
+        /// [`MockState`] can be used as the state of a `Display` to implement a test server without
+        /// writing the `Dispatch` and `GlobalDispatch` implementations of the interfaces it uses: it
+        /// initializes all the objects it receives with a `()` user data, and records their requests.
+        /// Responses can be scripted with [`MockState::on_request()`].
+        #foreign_doc
+        #allow_deprecated
+        pub mod mock {
+            use super::wayland_server::{
+                backend::{ClientId, ObjectId},
+                Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+            };
+            #foreign_imports
+
+            /// A request received by a [`MockState`]
+            #[derive(Debug)]
+            #[non_exhaustive]
+            pub enum MockRequest {
+                #(#variants,)*
+                #foreign_variant
+            }
+
+            /// A request recorded by a [`MockState`]
+            #[derive(Debug)]
+            pub struct Recorded {
+                /// The client which sent the request
+                pub client: ClientId,
+                /// The object which received the request
+                pub resource: ObjectId,
+                /// The request
+                ///
+                /// The objects it creates have already been initialized, they are also listed in
+                /// [`created`](Recorded::created).
+                pub request: MockRequest,
+                /// The objects created by the request
+                pub created: Vec<ObjectId>,
+            }
+
+            type Responder = Box<dyn FnMut(&DisplayHandle, &Recorded)>;
+
+            /// A server state recording the requests it receives
+            ///
+            /// Create the globals of the protocol with it as the state, like
+            /// `display.handle().create_global::<MockState, WlFoo, ()>(1, ())`.
+            #[derive(Default)]
+            pub struct MockState {
+                /// The recorded requests, in the order they were received
+                pub log: Vec<Recorded>,
+                /// The objects created by binding globals, in the order they were created
+                pub bound: Vec<ObjectId>,
+                responders: Vec<Responder>,
+            }
+
+            impl MockState {
+                /// Create a new mock state with no recorded requests
+                pub fn new() -> MockState {
+                    MockState::default()
+                }
+
+                /// Script the responses to requests
+                ///
+                /// The responder is called for every request, once its new objects have been initialized
+                /// and before it is added to the log. It can send events to the objects of the request,
+                /// which can be retrieved using `Resource::from_id()`.
+                pub fn on_request(&mut self, responder: impl FnMut(&DisplayHandle, &Recorded) + 'static) {
+                    self.responders.push(Box::new(responder));
+                }
+
+                /// Take the recorded requests, leaving the log empty
+                pub fn take_log(&mut self) -> Vec<Recorded> {
+                    std::mem::take(&mut self.log)
+                }
+
+                fn record(&mut self, dhandle: &DisplayHandle, recorded: Recorded) {
+                    for responder in &mut self.responders {
+                        responder(dhandle, &recorded);
+                    }
+                    self.log.push(recorded);
+                }
+            }
+
+            impl std::fmt::Debug for MockState {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.debug_struct("MockState")
+                        .field("log", &self.log)
+                        .field("bound", &self.bound)
+                        .finish_non_exhaustive()
+                }
+            }
+
+            #foreign_object
+
+            #(#dispatches)*
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn foreign_new_id() {
+        let protocol_file = br#"<protocol name="foreign">
+            <interface name="foo_manager" version="1">
+                <request name="get_foo">
+                    <arg name="id" type="new_id" interface="foo"/>
+                </request>
+                <request name="create_buffer">
+                    <arg name="id" type="new_id" interface="wl_buffer"/>
+                </request>
+            </interface>
+            <interface name="foo" version="1">
+                <request name="destroy" type="destructor"/>
+            </interface>
+        </protocol>"#;
+        let protocol = crate::parse::parse(&protocol_file[..]).unwrap();
+        let generated = super::generate_mock(protocol.interfaces.iter()).to_string();
+
+        // the interfaces of the protocol are implemented by the mock state
+        assert!(generated.contains("data_init . init (id , ())"));
+        // the objects of other protocols are given a foreign object data
+        assert!(generated
+            .contains("data_init . custom_init (id , std :: sync :: Arc :: new (ForeignObject))"));
+        assert!(generated.contains("struct ForeignObject"));
+        assert!(generated.contains("Foreign {"));
+        assert!(!generated.contains("Dispatch < super :: wl_buffer"));
+
+        // no foreign object data when all created objects are implemented
+        let generated =
+            super::generate_mock(protocol.interfaces.iter().filter(|iface| iface.name == "foo"))
+                .to_string();
+        assert!(!generated.contains("ForeignObject"));
+    }
+}
@@ -0,0 +1,946 @@
+use super::protocol::*;
+use std::{collections::HashSet, fmt, str::FromStr};
+
+use quick_xml::{
+    events::{attributes::Attributes, BytesStart, Event},
+    Reader,
+};
+
+/// Interfaces of the core Wayland protocol, that other protocols can refer to
+const CORE_INTERFACES: &[&str] = &[
+    "wl_display",
+    "wl_registry",
+    "wl_callback",
+    "wl_compositor",
+    "wl_shm_pool",
+    "wl_shm",
+    "wl_buffer",
+    "wl_data_offer",
+    "wl_data_source",
+    "wl_data_device",
+    "wl_data_device_manager",
+    "wl_shell",
+    "wl_shell_surface",
+    "wl_surface",
+    "wl_seat",
+    "wl_pointer",
+    "wl_keyboard",
+    "wl_touch",
+    "wl_output",
+    "wl_region",
+    "wl_subcompositor",
+    "wl_subsurface",
+    "wl_fixes",
+];
+
+/// An error in a protocol file
+///
+/// This can either be a malformed XML file, or a protocol that is well-formed but semantically invalid,
+/// such as one containing duplicate names or referring to unknown interfaces. This type is also used for
+/// the issues reported by [`warnings()`], which do not prevent generating code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line of the error in the XML file, starting at 1
+    pub line: usize,
+    /// Column of the error in the XML file, starting at 1
+    pub column: usize,
+    /// Path of the element containing the error, like `interface[wl_surface] > request[attach]`
+    pub path: Vec<String>,
+    /// Description of the error
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        if !self.path.is_empty() {
+            write!(f, "in `{}`: ", self.path.join(" > "))?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse and validate the contents of a protocol file
+///
+/// This gives access to the [`Protocol`] model used to generate the code, for tools inspecting
+/// protocol files.
+///
+/// A protocol file does not declare the other protocols it refers to, so this function only checks the
+/// references to its own interfaces and to the ones of the core Wayland protocol (named `wl_*`). The
+/// references to any other interface are assumed to be to another protocol and are not checked, use
+/// [`parse_with_dependencies()`] to check them too.
+pub fn parse(source: &[u8]) -> Result<Protocol, ParseError> {
+    parse_inner(source, None).map(|(protocol, _)| protocol)
+}
+
+/// Parse and validate the contents of a protocol file, resolving its references to other protocols
+///
+/// Unlike [`parse()`], all the `interface` and `enum` references of the protocol must be to its own
+/// interfaces, to the ones of the core Wayland protocol, or to the ones of the `dependencies`.
+pub fn parse_with_dependencies(
+    source: &[u8],
+    dependencies: &[Protocol],
+) -> Result<Protocol, ParseError> {
+    parse_inner(source, Some(dependencies)).map(|(protocol, _)| protocol)
+}
+
+/// Find the issues of a protocol file that do not prevent generating its code
+///
+/// Like the C `wayland-scanner`, messages whose `since` attribute is lower than the one of the previous
+/// message of the same kind are accepted, as the opcodes of existing messages can never be changed.
+/// They are reported by this function instead. Invalid protocol files are reported by [`parse()`], for
+/// which this function returns no warnings.
+pub fn warnings(source: &[u8]) -> Vec<ParseError> {
+    parse_inner(source, None).map(|(_, warnings)| warnings).unwrap_or_default()
+}
+
+/// Parse a protocol file, returning it together with its warnings
+pub(crate) fn parse_inner(
+    source: &[u8],
+    dependencies: Option<&[Protocol]>,
+) -> Result<(Protocol, Vec<ParseError>), ParseError> {
+    let mut reader = Reader::from_reader(source);
+    reader.trim_text(true).expand_empty_elements(true);
+    let mut parser = Parser {
+        reader,
+        source,
+        path: Vec::new(),
+        position: 0,
+        references: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let protocol = parser.parse_protocol()?;
+    parser.check_references(&protocol, dependencies)?;
+    Ok((protocol, parser.warnings))
+}
+
+/// Location of an element, used to report errors found after it was parsed
+#[derive(Clone, Debug)]
+struct Location {
+    position: usize,
+    path: Vec<String>,
+}
+
+/// A reference to an other interface or enum, that can only be checked once the whole file is parsed
+struct Reference {
+    location: Location,
+    interface: String,
+    target: ReferenceTarget,
+}
+
+enum ReferenceTarget {
+    Interface(String),
+    Enum(String),
+}
+
+struct Parser<'a> {
+    reader: Reader<&'a [u8]>,
+    source: &'a [u8],
+    path: Vec<String>,
+    // offset of the last event read from the source
+    position: usize,
+    references: Vec<Reference>,
+    warnings: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    fn next_event(&mut self) -> Result<Event<'a>, ParseError> {
+        self.position = self.reader.buffer_position();
+        match self.reader.read_event() {
+            Ok(Event::Eof) => Err(self.error("Ill-formed protocol file: unexpected end of file")),
+            Ok(event) => Ok(event),
+            Err(e) => Err(self.error(format!("Ill-formed protocol file: {}", e))),
+        }
+    }
+
+    fn location(&self) -> Location {
+        Location { position: self.position, path: self.path.clone() }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(&self.location(), message)
+    }
+
+    fn error_at(&self, location: &Location, message: impl Into<String>) -> ParseError {
+        // skip the whitespace trimmed by the reader, to point at the element itself
+        let mut position = location.position.min(self.source.len());
+        while position < self.source.len() && self.source[position].is_ascii_whitespace() {
+            position += 1;
+        }
+        let before = &self.source[..position];
+        let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+        let line_start = before.iter().rposition(|&c| c == b'\n').map(|i| i + 1).unwrap_or(0);
+        let column = String::from_utf8_lossy(&before[line_start..]).chars().count() + 1;
+        ParseError { line, column, path: location.path.clone(), message: message.into() }
+    }
+
+    fn enter(&mut self, tag: &str, name: &str) {
+        if name.is_empty() {
+            self.path.push(tag.into());
+        } else {
+            self.path.push(format!("{}[{}]", tag, name));
+        }
+    }
+
+    fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    fn decode_utf8(&self, txt: &[u8]) -> Result<String, ParseError> {
+        match std::str::from_utf8(txt) {
+            Ok(txt) => Ok(txt.into()),
+            Err(_) => Err(self.error(format!("Invalid UTF8: '{}'", String::from_utf8_lossy(txt)))),
+        }
+    }
+
+    fn parse_value<T: FromStr>(&self, txt: &[u8]) -> Result<T, ParseError> {
+        match std::str::from_utf8(txt).ok().and_then(|val| val.parse().ok()) {
+            Some(value) => Ok(value),
+            None => Err(self.error(format!(
+                "Invalid value '{}' for parsing type '{}'",
+                String::from_utf8_lossy(txt),
+                std::any::type_name::<T>()
+            ))),
+        }
+    }
+
+    fn parse_type(&self, txt: &[u8]) -> Result<Type, ParseError> {
+        Ok(match txt {
+            b"int" => Type::Int,
+            b"uint" => Type::Uint,
+            b"fixed" => Type::Fixed,
+            b"string" => Type::String,
+            b"object" => Type::Object,
+            b"new_id" => Type::NewId,
+            b"array" => Type::Array,
+            b"fd" => Type::Fd,
+            b"destructor" => Type::Destructor,
+            e => return Err(self.error(format!("Unexpected type: {}", String::from_utf8_lossy(e)))),
+        })
+    }
+
+    fn unexpected_token(&self, name: &[u8]) -> ParseError {
+        self.error(format!("Unexpected token: `{}`", String::from_utf8_lossy(name)))
+    }
+
+    fn name_of(&self, bytes: &BytesStart) -> Result<String, ParseError> {
+        match bytes.try_get_attribute("name") {
+            Ok(Some(attr)) => self.decode_utf8(&attr.value),
+            _ => Ok(String::new()),
+        }
+    }
+
+    fn parse_protocol(&mut self) -> Result<Protocol, ParseError> {
+        // Skip the <?xml ... ?> declaration and any comment before the toplevel tag
+        let bytes = loop {
+            match self.next_event()? {
+                Event::Decl(_) | Event::Comment(_) | Event::DocType(_) => {}
+                Event::Start(bytes) => break bytes,
+                e => return Err(self.error(format!("Ill-formed protocol file: {:?}", e))),
+            }
+        };
+        if bytes.name().into_inner() != b"protocol" {
+            return Err(self.error("Missing protocol toplevel tag"));
+        }
+        let name = self.name_of(&bytes)?;
+        if name.is_empty() {
+            return Err(self.error("Protocol must have a name"));
+        }
+        self.enter("protocol", &name);
+        let mut protocol = Protocol::new(name);
+        let mut interface_names = HashSet::new();
+
+        loop {
+            match self.next_event()? {
+                Event::Start(bytes) => {
+                    match bytes.name().into_inner() {
+                        b"copyright" => {
+                            // parse the copyright
+                            let copyright = match self.next_event()? {
+                                Event::Text(copyright) => {
+                                    copyright.unescape().ok().map(|x| x.to_string())
+                                }
+                                Event::CData(copyright) => {
+                                    String::from_utf8(copyright.into_inner().into()).ok()
+                                }
+                                e => {
+                                    return Err(
+                                        self.error(format!("Ill-formed protocol file: {:?}", e))
+                                    )
+                                }
+                            };
+
+                            self.expect_end("copyright")?;
+                            protocol.copyright = copyright
+                        }
+                        b"interface" => {
+                            let location = self.location();
+                            let interface = self.parse_interface(bytes.attributes())?;
+                            if !interface_names.insert(interface.name.clone()) {
+                                return Err(self.error_at(
+                                    &location,
+                                    format!("Duplicate interface `{}`", interface.name),
+                                ));
+                            }
+                            protocol.interfaces.push(interface);
+                        }
+                        b"description" => {
+                            protocol.description =
+                                Some(self.parse_description(bytes.attributes())?);
+                        }
+                        name => {
+                            return Err(self.error(format!(
+                                "Ill-formed protocol file: unexpected token `{}` in protocol {}",
+                                String::from_utf8_lossy(name),
+                                protocol.name
+                            )))
+                        }
+                    }
+                }
+                Event::End(bytes) => {
+                    let name = bytes.name().into_inner();
+                    if name != b"protocol" {
+                        return Err(self.error(format!(
+                            "Unexpected closing token `{}`",
+                            String::from_utf8_lossy(name)
+                        )));
+                    }
+                    break;
+                }
+                // ignore comments
+                Event::Comment(_) => {}
+                e => {
+                    return Err(
+                        self.error(format!("Ill-formed protocol file: unexpected token {:?}", e))
+                    )
+                }
+            }
+        }
+
+        self.leave();
+        Ok(protocol)
+    }
+
+    fn expect_end(&mut self, tag: &str) -> Result<(), ParseError> {
+        match self.next_event()? {
+            Event::End(bytes) if bytes.name().into_inner() == tag.as_bytes() => Ok(()),
+            e => Err(self.error(format!(
+                "Ill-formed protocol file: expected end of `{}`, found {:?}",
+                tag, e
+            ))),
+        }
+    }
+
+    fn parse_interface(&mut self, attrs: Attributes) -> Result<Interface, ParseError> {
+        let mut interface = Interface::new();
+        for attr in attrs.filter_map(|res| res.ok()) {
+            match attr.key.into_inner() {
+                b"name" => interface.name = self.decode_utf8(&attr.value)?,
+                b"version" => interface.version = self.parse_value(&attr.value)?,
+                b"frozen" => interface.frozen = &attr.value[..] == b"true",
+                _ => {}
+            }
+        }
+        if interface.name.is_empty() {
+            return Err(self.error("Interface must have a name"));
+        }
+        self.enter("interface", &interface.name);
+
+        let mut request_locations = Vec::new();
+        let mut event_locations = Vec::new();
+        let mut enum_locations = Vec::new();
+        loop {
+            match self.next_event()? {
+                Event::Start(bytes) => match bytes.name().into_inner() {
+                    b"description" => {
+                        interface.description = Some(self.parse_description(bytes.attributes())?)
+                    }
+                    b"request" => {
+                        request_locations.push(self.location());
+                        let name = self.name_of(&bytes)?;
+                        self.enter("request", &name);
+                        let request =
+                            self.parse_message(&interface, "request", bytes.attributes())?;
+                        self.leave();
+                        interface.requests.push(request);
+                    }
+                    b"event" => {
+                        event_locations.push(self.location());
+                        let name = self.name_of(&bytes)?;
+                        self.enter("event", &name);
+                        let event = self.parse_message(&interface, "event", bytes.attributes())?;
+                        self.leave();
+                        interface.events.push(event);
+                    }
+                    b"enum" => {
+                        enum_locations.push(self.location());
+                        let enu = self.parse_enum(&interface, bytes.attributes())?;
+                        interface.enums.push(enu);
+                    }
+                    name => return Err(self.unexpected_token(name)),
+                },
+                Event::End(bytes) if bytes.name().into_inner() == b"interface" => break,
+                _ => {}
+            }
+        }
+
+        self.check_messages(&interface.requests, &request_locations, "request")?;
+        self.check_messages(&interface.events, &event_locations, "event")?;
+        let mut enum_names = HashSet::new();
+        for (enu, location) in interface.enums.iter().zip(&enum_locations) {
+            if !enum_names.insert(&enu.name) {
+                return Err(self.error_at(location, format!("Duplicate enum `{}`", enu.name)));
+            }
+        }
+
+        self.leave();
+        Ok(interface)
+    }
+
+    /// Check that the names of the messages are unique, and warn if their `since` attributes are not
+    /// increasing
+    fn check_messages(
+        &mut self,
+        messages: &[Message],
+        locations: &[Location],
+        kind: &str,
+    ) -> Result<(), ParseError> {
+        let mut names = HashSet::new();
+        let mut since = 1;
+        for (message, location) in messages.iter().zip(locations) {
+            if !names.insert(&message.name) {
+                return Err(
+                    self.error_at(location, format!("Duplicate {} `{}`", kind, message.name))
+                );
+            }
+            if message.since < since {
+                let warning = self.error_at(
+                    location,
+                    format!(
+                        "{} `{}` has since={} but the previous {} has since={}, messages should be ordered by version",
+                        kind, message.name, message.since, kind, since
+                    ),
+                );
+                self.warnings.push(warning);
+            }
+            since = message.since;
+        }
+        Ok(())
+    }
+
+    fn check_since(&self, since: u32, interface: &Interface) -> Result<(), ParseError> {
+        if since > interface.version {
+            return Err(self.error(format!(
+                "since={} is greater than the version {} of interface `{}`",
+                since, interface.version, interface.name
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_deprecated_since(
+        &self,
+        deprecated_since: u32,
+        since: u32,
+        interface: &Interface,
+    ) -> Result<(), ParseError> {
+        self.check_since(deprecated_since, interface)?;
+        if deprecated_since <= since {
+            return Err(self.error(format!(
+                "deprecated-since={} must be greater than since={}",
+                deprecated_since, since
+            )));
+        }
+        Ok(())
+    }
+
+    fn parse_description(&mut self, attrs: Attributes) -> Result<(String, String), ParseError> {
+        let mut summary = String::new();
+        for attr in attrs.filter_map(|res| res.ok()) {
+            if attr.key.into_inner() == b"summary" {
+                summary = String::from_utf8_lossy(&attr.value)
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+        }
+
+        let mut description = String::new();
+        // Some protocols have comments inside their descriptions, so we need to parse them in a loop and
+        // concatenate the parts into a single block of text
+        loop {
+            match self.next_event()? {
+                Event::Text(bytes) => {
+                    if !description.is_empty() {
+                        description.push_str("\n\n");
+                    }
+                    description.push_str(&bytes.unescape().unwrap_or_default())
+                }
+                Event::End(bytes) if bytes.name().into_inner() == b"description" => break,
+                Event::Comment(_) => {}
+                e => return Err(self.error(format!("Ill-formed protocol file: {:?}", e))),
+            }
+        }
+
+        Ok((summary, description))
+    }
+
+    fn parse_message(
+        &mut self,
+        interface: &Interface,
+        tag: &str,
+        attrs: Attributes,
+    ) -> Result<Message, ParseError> {
+        let mut message = Message::new();
+        for attr in attrs.filter_map(|res| res.ok()) {
+            match attr.key.into_inner() {
+                b"name" => message.name = self.decode_utf8(&attr.value)?,
+                b"type" => message.typ = Some(self.parse_type(&attr.value)?),
+                b"since" => message.since = self.parse_value(&attr.value)?,
+                b"deprecated-since" => {
+                    message.deprecated_since = Some(self.parse_value(&attr.value)?)
+                }
+                _ => {}
+            }
+        }
+        self.check_since(message.since, interface)?;
+        if let Some(deprecated_since) = message.deprecated_since {
+            self.check_deprecated_since(deprecated_since, message.since, interface)?;
+        }
+
+        let mut arg_names = HashSet::new();
+        loop {
+            match self.next_event()? {
+                Event::Start(bytes) => match bytes.name().into_inner() {
+                    b"description" => {
+                        message.description = Some(self.parse_description(bytes.attributes())?)
+                    }
+                    b"arg" => {
+                        let name = self.name_of(&bytes)?;
+                        self.enter("arg", &name);
+                        let arg = self.parse_arg(interface, bytes.attributes())?;
+                        if !arg_names.insert(arg.name.clone()) {
+                            return Err(self.error(format!("Duplicate argument `{}`", arg.name)));
+                        }
+                        self.leave();
+                        message.args.push(arg);
+                    }
+                    name => return Err(self.unexpected_token(name)),
+                },
+                Event::End(bytes) if bytes.name().into_inner() == tag.as_bytes() => break,
+                _ => {}
+            }
+        }
+
+        Ok(message)
+    }
+
+    fn parse_enum(&mut self, interface: &Interface, attrs: Attributes) -> Result<Enum, ParseError> {
+        let mut enu = Enum::new();
+        for attr in attrs.filter_map(|res| res.ok()) {
+            match attr.key.into_inner() {
+                b"name" => enu.name = self.decode_utf8(&attr.value)?,
+                b"since" => enu.since = self.parse_value(&attr.value)?,
+                b"bitfield" => {
+                    if &attr.value[..] == b"true" {
+                        enu.bitfield = true
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.enter("enum", &enu.name);
+        self.check_since(enu.since.into(), interface)?;
+
+        let mut entry_names = HashSet::new();
+        loop {
+            match self.next_event()? {
+                Event::Start(bytes) => match bytes.name().into_inner() {
+                    b"description" => {
+                        enu.description = Some(self.parse_description(bytes.attributes())?)
+                    }
+                    b"entry" => {
+                        let name = self.name_of(&bytes)?;
+                        self.enter("entry", &name);
+                        let entry = self.parse_entry(interface, bytes.attributes())?;
+                        if !entry_names.insert(entry.name.clone()) {
+                            return Err(self.error(format!("Duplicate entry `{}`", entry.name)));
+                        }
+                        self.leave();
+                        enu.entries.push(entry);
+                    }
+                    name => return Err(self.unexpected_token(name)),
+                },
+                Event::End(bytes) if bytes.name().into_inner() == b"enum" => break,
+                _ => {}
+            }
+        }
+
+        self.leave();
+        Ok(enu)
+    }
+
+    fn parse_arg(&mut self, interface: &Interface, attrs: Attributes) -> Result<Arg, ParseError> {
+        let mut arg = Arg::new();
+        for attr in attrs.filter_map(|res| res.ok()) {
+            match attr.key.into_inner() {
+                b"name" => arg.name = self.decode_utf8(&attr.value)?,
+                b"type" => arg.typ = self.parse_type(&attr.value)?,
+                b"summary" => {
+                    arg.summary = Some(
+                        String::from_utf8_lossy(&attr.value)
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    )
+                }
+                b"interface" => arg.interface = Some(self.parse_value(&attr.value)?),
+                b"allow-null" => {
+                    if &*attr.value == b"true" {
+                        arg.allow_null = true
+                    }
+                }
+                b"enum" => arg.enum_ = Some(self.decode_utf8(&attr.value)?),
+                _ => {}
+            }
+        }
+
+        if let Some(ref target) = arg.interface {
+            self.references.push(Reference {
+                location: self.location(),
+                interface: interface.name.clone(),
+                target: ReferenceTarget::Interface(target.clone()),
+            });
+        }
+        if let Some(ref target) = arg.enum_ {
+            self.references.push(Reference {
+                location: self.location(),
+                interface: interface.name.clone(),
+                target: ReferenceTarget::Enum(target.clone()),
+            });
+        }
+
+        loop {
+            match self.next_event()? {
+                Event::Start(bytes) => match bytes.name().into_inner() {
+                    b"description" => {
+                        arg.description = Some(self.parse_description(bytes.attributes())?)
+                    }
+                    name => return Err(self.unexpected_token(name)),
+                },
+                Event::End(bytes) if bytes.name().into_inner() == b"arg" => break,
+                _ => {}
+            }
+        }
+
+        Ok(arg)
+    }
+
+    fn parse_entry(
+        &mut self,
+        interface: &Interface,
+        attrs: Attributes,
+    ) -> Result<Entry, ParseError> {
+        let mut entry = Entry::new();
+        for attr in attrs.filter_map(|res| res.ok()) {
+            match attr.key.into_inner() {
+                b"name" => entry.name = self.decode_utf8(&attr.value)?,
+                b"value" => {
+                    entry.value = if attr.value.starts_with(b"0x") {
+                        if let Some(val) = std::str::from_utf8(&attr.value[2..])
+                            .ok()
+                            .and_then(|s| u32::from_str_radix(s, 16).ok())
+                        {
+                            val
+                        } else {
+                            return Err(self.error(format!(
+                                "Invalid number: {}",
+                                String::from_utf8_lossy(&attr.value)
+                            )));
+                        }
+                    } else {
+                        self.parse_value(&attr.value)?
+                    };
+                }
+                b"since" => entry.since = self.parse_value(&attr.value)?,
+                b"deprecated-since" => {
+                    entry.deprecated_since = Some(self.parse_value(&attr.value)?)
+                }
+                b"summary" => {
+                    entry.summary = Some(
+                        String::from_utf8_lossy(&attr.value)
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    )
+                }
+                _ => {}
+            }
+        }
+        self.check_since(entry.since.into(), interface)?;
+        if let Some(deprecated_since) = entry.deprecated_since {
+            self.check_deprecated_since(deprecated_since.into(), entry.since.into(), interface)?;
+        }
+
+        loop {
+            match self.next_event()? {
+                Event::Start(bytes) => match bytes.name().into_inner() {
+                    b"description" => {
+                        entry.description = Some(self.parse_description(bytes.attributes())?)
+                    }
+                    name => return Err(self.unexpected_token(name)),
+                },
+                Event::End(bytes) if bytes.name().into_inner() == b"entry" => break,
+                _ => {}
+            }
+        }
+
+        Ok(entry)
+    }
+
+    /// Check the references to interfaces and enums made by the arguments
+    ///
+    /// Without dependencies, the references to interfaces that are neither defined by the protocol nor
+    /// part of the core protocol cannot be checked.
+    fn check_references(
+        &self,
+        protocol: &Protocol,
+        dependencies: Option<&[Protocol]>,
+    ) -> Result<(), ParseError> {
+        let find = |name: &str| {
+            std::iter::once(protocol)
+                .chain(dependencies.unwrap_or_default())
+                .flat_map(|protocol| &protocol.interfaces)
+                .find(|iface| iface.name == name)
+        };
+        // whether an interface that is not defined can be referred to
+        let is_external = |name: &str| {
+            CORE_INTERFACES.contains(&name) || (dependencies.is_none() && !name.starts_with("wl_"))
+        };
+        for reference in &self.references {
+            match reference.target {
+                ReferenceTarget::Interface(ref target) => {
+                    if find(target).is_none() && !is_external(target) {
+                        return Err(self.error_at(
+                            &reference.location,
+                            format!("Unknown interface `{}`", target),
+                        ));
+                    }
+                }
+                ReferenceTarget::Enum(ref target) => {
+                    let (iface, enu) = match target.split_once('.') {
+                        Some((iface, enu)) => (iface, enu),
+                        None => (&reference.interface[..], &target[..]),
+                    };
+                    match find(iface) {
+                        Some(interface) if !interface.enums.iter().any(|e| e.name == enu) => {
+                            return Err(self.error_at(
+                                &reference.location,
+                                format!("Unknown enum `{}` in interface `{}`", enu, iface),
+                            ));
+                        }
+                        Some(_) => {}
+                        None if !is_external(iface) => {
+                            return Err(self.error_at(
+                                &reference.location,
+                                format!("Unknown interface `{}` in enum `{}`", iface, target),
+                            ));
+                        }
+                        // defined in an other protocol
+                        None => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn parse_error(xml: &str) -> super::ParseError {
+        super::parse(xml.as_bytes()).unwrap_err()
+    }
+
+    #[test]
+    fn malformed_xml() {
+        let error = parse_error(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="x" type="integer"/>
+    </request>
+  </interface>
+</protocol>"#,
+        );
+        assert_eq!((error.line, error.column), (5, 7));
+        assert_eq!(error.path, ["protocol[test]", "interface[foo]", "request[bar]", "arg[x]"]);
+        assert_eq!(error.message, "Unexpected type: integer");
+
+        let error = parse_error(
+            r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+  </interface>
+</protocol>"#,
+        );
+        assert_eq!(error.line, 4);
+    }
+
+    #[test]
+    fn semantic_errors() {
+        let error = parse_error(
+            r#"<protocol name="test">
+  <interface name="foo" version="2">
+    <request name="bar"/>
+    <request name="bar"/>
+  </interface>
+</protocol>"#,
+        );
+        assert_eq!((error.line, error.column), (4, 5));
+        assert_eq!(error.message, "Duplicate request `bar`");
+
+        let error = parse_error(
+            r#"<protocol name="test">
+  <interface name="foo" version="2">
+    <event name="bar" since="3"/>
+  </interface>
+</protocol>"#,
+        );
+        assert_eq!(error.message, "since=3 is greater than the version 2 of interface `foo`");
+
+        let error = parse_error(
+            r#"<protocol name="test">
+  <interface name="foo" version="3">
+    <request name="bar" since="2" deprecated-since="2"/>
+  </interface>
+</protocol>"#,
+        );
+        assert_eq!(error.message, "deprecated-since=2 must be greater than since=2");
+
+        let error = parse_error(
+            r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="x" type="uint" enum="qux"/>
+    </request>
+  </interface>
+</protocol>"#,
+        );
+        assert_eq!((error.line, error.column), (4, 7));
+        assert_eq!(error.message, "Unknown enum `qux` in interface `foo`");
+
+        let error = parse_error(
+            r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="x" type="object" interface="wl_surfce"/>
+    </request>
+  </interface>
+</protocol>"#,
+        );
+        assert_eq!(error.message, "Unknown interface `wl_surfce`");
+
+        // without dependencies, interfaces defined in other protocols cannot be checked
+        super::parse(
+            br#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="x" type="object" interface="xdg_surface"/>
+      <arg name="y" type="uint" enum="xdg_toplevel.state"/>
+    </request>
+  </interface>
+</protocol>"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn warnings() {
+        let source = r#"<protocol name="test">
+  <interface name="foo" version="2">
+    <event name="bar" since="2"/>
+    <event name="baz"/>
+  </interface>
+</protocol>"#;
+        // messages out of version order are accepted, like by the C wayland-scanner
+        assert!(super::parse(source.as_bytes()).is_ok());
+        let warnings = super::warnings(source.as_bytes());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].line, warnings[0].column), (4, 5));
+        assert_eq!(
+            warnings[0].message,
+            "event `baz` has since=1 but the previous event has since=2, messages should be ordered by version"
+        );
+
+        assert!(super::warnings(b"<protocol name=\"test\"></protocol>").is_empty());
+    }
+
+    #[test]
+    fn dependencies() {
+        let dependencies = [super::parse(
+            br#"<protocol name="dep">
+  <interface name="xdg_toplevel" version="1">
+    <enum name="state">
+      <entry name="maximized" value="1"/>
+    </enum>
+  </interface>
+</protocol>"#,
+        )
+        .unwrap()];
+        let parse = |xml: &str| super::parse_with_dependencies(xml.as_bytes(), &dependencies);
+
+        parse(
+            r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="x" type="object" interface="xdg_toplevel"/>
+      <arg name="y" type="uint" enum="xdg_toplevel.state"/>
+      <arg name="z" type="object" interface="wl_surface"/>
+    </request>
+  </interface>
+</protocol>"#,
+        )
+        .unwrap();
+
+        let error = parse(
+            r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="x" type="object" interface="xdg_toplevl"/>
+    </request>
+  </interface>
+</protocol>"#,
+        )
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (4, 7));
+        assert_eq!(error.message, "Unknown interface `xdg_toplevl`");
+
+        let error = parse(
+            r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="y" type="uint" enum="xdg_toplevel.states"/>
+    </request>
+  </interface>
+</protocol>"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.message, "Unknown enum `states` in interface `xdg_toplevel`");
+
+        let error = parse(
+            r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="y" type="uint" enum="xdg_popup.state"/>
+    </request>
+  </interface>
+</protocol>"#,
+        )
+        .unwrap_err();
+        assert_eq!(error.message, "Unknown interface `xdg_popup` in enum `xdg_popup.state`");
+    }
+}
@@ -0,0 +1,236 @@
+//! Representation of the contents of a protocol file
+//!
+//! Descriptions are stored as `(summary, description)` pairs.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// A protocol file
+#[derive(Clone, Debug)]
+pub struct Protocol {
+    /// Name of the protocol
+    pub name: String,
+    /// Copyright notice of the protocol
+    pub copyright: Option<String>,
+    /// Description of the protocol
+    pub description: Option<(String, String)>,
+    /// Interfaces defined by the protocol
+    pub interfaces: Vec<Interface>,
+}
+
+impl Protocol {
+    pub(crate) fn new(name: String) -> Protocol {
+        Protocol { name, copyright: None, description: None, interfaces: Vec::new() }
+    }
+}
+
+/// An interface of a protocol
+#[derive(Clone, Debug)]
+pub struct Interface {
+    /// Name of the interface
+    pub name: String,
+    /// Current version of the interface
+    pub version: u32,
+    /// Whether the interface is frozen, meaning its version will never be increased
+    pub frozen: bool,
+    /// Description of the interface
+    pub description: Option<(String, String)>,
+    /// Requests of the interface, in opcode order
+    pub requests: Vec<Message>,
+    /// Events of the interface, in opcode order
+    pub events: Vec<Message>,
+    /// Enums defined by the interface
+    pub enums: Vec<Enum>,
+}
+
+impl Interface {
+    pub(crate) fn new() -> Interface {
+        Interface {
+            name: String::new(),
+            version: 1,
+            frozen: false,
+            description: None,
+            requests: Vec::new(),
+            events: Vec::new(),
+            enums: Vec::new(),
+        }
+    }
+}
+
+/// A request or an event
+#[derive(Clone, Debug)]
+pub struct Message {
+    /// Name of the message
+    pub name: String,
+    /// Type of the message, `Some(Type::Destructor)` for destructors
+    pub typ: Option<Type>,
+    /// Version of the interface in which the message was introduced
+    pub since: u32,
+    /// Version of the interface from which the message is deprecated
+    pub deprecated_since: Option<u32>,
+    /// Description of the message
+    pub description: Option<(String, String)>,
+    /// Arguments of the message
+    pub args: Vec<Arg>,
+}
+
+impl Message {
+    pub(crate) fn new() -> Message {
+        Message {
+            name: String::new(),
+            typ: None,
+            since: 1,
+            deprecated_since: None,
+            description: None,
+            args: Vec::new(),
+        }
+    }
+
+    pub(crate) fn all_null(&self) -> bool {
+        self.args
+            .iter()
+            .all(|a| !((a.typ == Type::Object || a.typ == Type::NewId) && a.interface.is_some()))
+    }
+}
+
+/// An argument of a message
+#[derive(Clone, Debug)]
+pub struct Arg {
+    /// Name of the argument
+    pub name: String,
+    /// Type of the argument
+    pub typ: Type,
+    /// Interface of the object, for `object` and `new_id` arguments
+    pub interface: Option<String>,
+    /// Summary of the argument
+    pub summary: Option<String>,
+    /// Description of the argument
+    pub description: Option<(String, String)>,
+    /// Whether a null value is allowed, for `object` and `string` arguments
+    pub allow_null: bool,
+    /// Enum of the argument, like `wl_output.transform` for an enum of an other interface
+    pub enum_: Option<String>,
+}
+
+impl Arg {
+    pub(crate) fn new() -> Arg {
+        Arg {
+            name: String::new(),
+            typ: Type::Object,
+            interface: None,
+            summary: None,
+            description: None,
+            allow_null: false,
+            enum_: None,
+        }
+    }
+}
+
+/// An enum of an interface
+#[derive(Clone, Debug)]
+pub struct Enum {
+    /// Name of the enum
+    pub name: String,
+    /// Version of the interface in which the enum was introduced
+    pub since: u16,
+    /// Description of the enum
+    pub description: Option<(String, String)>,
+    /// Entries of the enum
+    pub entries: Vec<Entry>,
+    /// Whether the enum is a bitfield
+    pub bitfield: bool,
+}
+
+impl Enum {
+    pub(crate) fn new() -> Enum {
+        Enum {
+            name: String::new(),
+            since: 1,
+            description: None,
+            entries: Vec::new(),
+            bitfield: false,
+        }
+    }
+}
+
+/// An entry of an enum
+#[derive(Clone, Debug)]
+pub struct Entry {
+    /// Name of the entry
+    pub name: String,
+    /// Value of the entry
+    pub value: u32,
+    /// Version of the interface in which the entry was introduced
+    pub since: u16,
+    /// Version of the interface from which the entry is deprecated
+    pub deprecated_since: Option<u16>,
+    /// Description of the entry
+    pub description: Option<(String, String)>,
+    /// Summary of the entry
+    pub summary: Option<String>,
+}
+
+impl Entry {
+    pub(crate) fn new() -> Entry {
+        Entry {
+            name: String::new(),
+            value: 0,
+            since: 1,
+            deprecated_since: None,
+            description: None,
+            summary: None,
+        }
+    }
+}
+
+/// Type of an argument, or of a message for destructors
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Type {
+    /// `int`
+    Int,
+    /// `uint`
+    Uint,
+    /// `fixed`
+    Fixed,
+    /// `string`
+    String,
+    /// `object`
+    Object,
+    /// `new_id`
+    NewId,
+    /// `array`
+    Array,
+    /// `fd`
+    Fd,
+    /// `destructor`
+    Destructor,
+}
+
+impl Interface {
+    /// Whether some messages or enum entries of this interface are deprecated
+    pub(crate) fn has_deprecated(&self) -> bool {
+        self.requests.iter().chain(&self.events).any(|msg| msg.deprecated_since.is_some())
+            || self.enums.iter().flat_map(|enu| &enu.entries).any(|e| e.deprecated_since.is_some())
+    }
+}
+
+impl Type {
+    /// Whether arguments of this type can be null
+    pub fn nullable(self) -> bool {
+        matches!(self, Type::String | Type::Object)
+    }
+
+    pub(crate) fn common_type(self) -> TokenStream {
+        match self {
+            Type::Int => quote!(Int),
+            Type::Uint => quote!(Uint),
+            Type::Fixed => quote!(Fixed),
+            Type::Array => quote!(Array),
+            Type::Fd => quote!(Fd),
+            Type::String => quote!(Str),
+            Type::Object => quote!(Object),
+            Type::NewId => quote!(NewId),
+            Type::Destructor => panic!("Destructor is not a valid argument type."),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+
+use quote::{format_ident, quote};
+
+use crate::{
+    common::message_field_type,
+    protocol::{Enum, Interface, Message, Type},
+    util::{is_keyword, snake_to_camel},
+    Side,
+};
+
+/// Names of the variables of the generated `serialize()` methods
+const RESERVED_NAMES: &[&str] = &["serializer", "state"];
+
+/// Generates the `serde::Serialize` implementations for the types of an interface
+///
+/// Objects serialize as `interface@id`, enums as the name of their entry (or the raw value of a
+/// bitfield), and messages as struct variants named after the protocol message and its arguments.
+/// File descriptors are replaced by a `"<fd>"` placeholder.
+pub(crate) fn generate_serialize(interface: &Interface, side: Side) -> TokenStream {
+    let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
+    let enums = interface.enums.iter().map(gen_enum);
+    let requests = gen_message_enum(
+        &format_ident!("Request"),
+        side,
+        side == Side::Server,
+        &interface.requests,
+    );
+    let events =
+        gen_message_enum(&format_ident!("Event"), side, side == Side::Client, &interface.events);
+    let serde_path = match side {
+        Side::Client => quote! { super::wayland_client::backend::serde },
+        Side::Server => quote! { super::wayland_server::backend::serde },
+    };
+
+    quote! {
+        const _: () = {
+            use #serde_path as serde;
+
+            impl serde::Serialize for #iface_name {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serde::Serialize::serialize(&self.id, serializer)
+                }
+            }
+
+            #(#enums)*
+            #requests
+            #events
+        };
+    }
+}
+
+fn gen_enum(enu: &Enum) -> TokenStream {
+    let ident = Ident::new(&snake_to_camel(&enu.name), Span::call_site());
+    let body = if enu.bitfield {
+        quote! { serializer.serialize_u32(self.bits()) }
+    } else {
+        let name = &enu.name;
+        let arms = enu.entries.iter().enumerate().map(|(index, entry)| {
+            let prefix = if entry.name.chars().next().unwrap().is_numeric() { "_" } else { "" };
+            let variant = format_ident!("{}{}", prefix, snake_to_camel(&entry.name));
+            let index = index as u32;
+            let entry_name = &entry.name;
+            quote! {
+                #ident::#variant => serializer.serialize_unit_variant(#name, #index, #entry_name)
+            }
+        });
+        quote! {
+            match *self {
+                #(#arms,)*
+            }
+        }
+    };
+    quote! {
+        impl serde::Serialize for #ident {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #body
+            }
+        }
+    }
+}
+
+fn gen_message_enum(name: &Ident, side: Side, receiver: bool, messages: &[Message]) -> TokenStream {
+    let type_name = name.to_string();
+    let arms = messages.iter().enumerate().map(|(opcode, msg)| {
+        let variant = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
+        let opcode = opcode as u32;
+        let msg_name = &msg.name;
+        // the arguments which are not fields of the variant are not serialized
+        let args = msg
+            .args
+            .iter()
+            .filter(|arg| message_field_type(arg, side, receiver).is_some())
+            .collect::<Vec<_>>();
+        if args.is_empty() {
+            let pattern = if msg.args.is_empty() {
+                quote! { #name::#variant }
+            } else {
+                quote! { #name::#variant { .. } }
+            };
+            return quote! {
+                #pattern => serializer.serialize_unit_variant(#type_name, #opcode, #msg_name)
+            };
+        }
+
+        let len = Literal::usize_unsuffixed(args.len());
+        let mut fields = Vec::new();
+        let mut serialize_fields = Vec::new();
+        for arg in args {
+            let arg_name = &arg.name;
+            if arg.typ == Type::Fd {
+                serialize_fields.push(quote! { state.serialize_field(#arg_name, "<fd>")?; });
+            } else {
+                let field =
+                    format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+                // do not shadow the local variables of the generated method
+                let binding = if RESERVED_NAMES.contains(&arg.name.as_str()) {
+                    format_ident!("_{}", arg.name)
+                } else {
+                    field.clone()
+                };
+                serialize_fields.push(quote! { state.serialize_field(#arg_name, #binding)?; });
+                fields.push(if binding == field {
+                    quote! { #field }
+                } else {
+                    quote! { #field: #binding }
+                });
+            }
+        }
+        let rest = (fields.len() < msg.args.len()).then(|| quote! { .. });
+        quote! {
+            #name::#variant { #(#fields,)* #rest } => {
+                let mut state = serializer.serialize_struct_variant(#type_name, #opcode, #msg_name, #len)?;
+                #(#serialize_fields)*
+                state.end()
+            }
+        }
+    });
+
+    let (generic, phantom_case) = if !receiver {
+        (quote! { <'a> }, quote! { #name::__phantom_lifetime { never, .. } => match *never {}, })
+    } else {
+        (quote! {}, quote! {})
+    };
+    // an empty enum can only be matched by value
+    let matched = if messages.is_empty() && receiver {
+        quote! { *self }
+    } else {
+        quote! { self }
+    };
+    let has_fields = messages
+        .iter()
+        .any(|msg| msg.args.iter().any(|arg| message_field_type(arg, side, receiver).is_some()));
+    let import = has_fields.then(|| quote! { use serde::ser::SerializeStructVariant; });
+
+    quote! {
+        impl #generic serde::Serialize for #name #generic {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #import
+                match #matched {
+                    #(#arms,)*
+                    #phantom_case
+                }
+            }
+        }
+    }
+}
@@ -1,23 +1,34 @@
+use std::fmt::Write;
+
 use proc_macro2::{Ident, Span, TokenStream};
 
 use quote::{format_ident, quote};
 
 use crate::{
     protocol::{Interface, Protocol, Type},
-    util::{description_to_doc_attr, dotted_to_relname, is_keyword, snake_to_camel, to_doc_attr},
-    Side,
+    util::{
+        deprecated_attr, description_to_doc, description_to_doc_attr, dotted_to_relname,
+        is_keyword, snake_to_camel, to_doc_attr,
+    },
+    Options, Side,
 };
 
-pub fn generate_server_objects(protocol: &Protocol) -> TokenStream {
-    protocol
-        .interfaces
-        .iter()
-        .filter(|iface| iface.name != "wl_display" && iface.name != "wl_registry")
-        .map(generate_objects_for)
-        .collect()
+pub fn generate_server_objects(protocol: &Protocol, options: &Options) -> TokenStream {
+    let interfaces = || {
+        protocol
+            .interfaces
+            .iter()
+            .filter(|iface| iface.name != "wl_display" && iface.name != "wl_registry")
+    };
+    let objects = interfaces().map(|iface| generate_objects_for(iface, options));
+    let mock = options.mock.then(|| crate::mock::generate_mock(interfaces()));
+    quote! {
+        #(#objects)*
+        #mock
+    }
 }
 
-fn generate_objects_for(interface: &Interface) -> TokenStream {
+fn generate_objects_for(interface: &Interface, options: &Options) -> TokenStream {
     let mod_name = Ident::new(&interface.name, Span::call_site());
     let mod_doc = interface.description.as_ref().map(description_to_doc_attr);
     let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
@@ -42,20 +53,30 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
     let parse_body = crate::common::gen_parse_body(interface, Side::Server);
     let write_body = crate::common::gen_write_body(interface, Side::Server);
     let methods = gen_methods(interface);
+    let handler = options.handlers.then(|| crate::handlers::generate_server_handler(interface));
+    let serialize =
+        options.serde.then(|| crate::serialize::generate_serialize(interface, Side::Server));
 
     let event_ref = if interface.requests.is_empty() {
         "This interface has no requests."
     } else {
         "See also the [Request] enum for this interface."
     };
-    let docs = match &interface.description {
-        Some((short, long)) => format!("{}\n\n{}\n\n{}", short, long, event_ref),
-        None => format!("{}\n\n{}", interface.name, event_ref),
-    };
+    let mut docs = description_to_doc(interface.description.as_ref(), Some(&interface.name));
+    docs.push_str("\n\n");
+    docs.push_str(event_ref);
+    if interface.frozen {
+        docs.push_str("\n\nThis interface is frozen: no new versions of it will be released.");
+    }
+    docs.push_str("\n\n");
+    docs.push_str(&crate::common::versions_table(interface));
     let doc_attr = to_doc_attr(&docs);
+    // the generated code itself uses the deprecated items
+    let allow_deprecated = interface.has_deprecated().then(|| quote! { #[allow(deprecated)] });
 
     quote! {
         #mod_doc
+        #allow_deprecated
         pub mod #mod_name {
             use std::sync::Arc;
             use std::os::unix::io::OwnedFd;
@@ -172,6 +193,9 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
             impl #iface_name {
                 #methods
             }
+
+            #handler
+            #serialize
         }
     }
 }
@@ -250,10 +274,26 @@ fn gen_methods(interface: &Interface) -> TokenStream {
                 }
             });
 
-            let doc_attr = request.description.as_ref().map(description_to_doc_attr);
+            let mut docs = description_to_doc(request.description.as_ref(), None);
+            if let Some(note) = crate::common::destructor_note(request, true) {
+                write!(docs, "\n\n{}", note).unwrap();
+            }
+            if request.since > 1 {
+                let since_const = format!("EVT_{}_SINCE", request.name.to_ascii_uppercase());
+                write!(
+                    docs,
+                    "\n\nOnly available since version {} of the interface ([`{}`]), it must not be \
+                     sent to objects of a lower version.",
+                    request.since, since_const
+                )
+                .unwrap();
+            }
+            let doc_attr = (!docs.is_empty()).then(|| to_doc_attr(&docs));
+            let deprecated = deprecated_attr(request.deprecated_since);
 
             quote! {
                 #doc_attr
+                #deprecated
                 #[allow(clippy::too_many_arguments)]
                 pub fn #method_name(&self, #(#fn_args),*) {
                     let _ = self.send_event(
@@ -271,10 +311,13 @@ fn gen_methods(interface: &Interface) -> TokenStream {
 mod tests {
     #[test]
     fn server_gen() {
-        let protocol_file =
-            std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
-        let protocol_parsed = crate::parse::parse(protocol_file);
-        let generated: String = super::generate_server_objects(&protocol_parsed).to_string();
+        let protocol_file = std::fs::read("./tests/scanner_assets/test-protocol.xml").unwrap();
+        let protocol_parsed = crate::parse::parse(&protocol_file).unwrap();
+        let generated: String = super::generate_server_objects(
+            &protocol_parsed,
+            &crate::Options { handlers: true, serde: true, mock: true, ..Default::default() },
+        )
+        .to_string();
         let generated = crate::format_rust_code(&generated);
 
         let reference =
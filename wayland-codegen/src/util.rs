@@ -8,8 +8,31 @@ pub(crate) fn to_doc_attr(text: &str) -> TokenStream {
     quote!(#[doc = #text])
 }
 
-pub(crate) fn description_to_doc_attr((short, long): &(String, String)) -> TokenStream {
-    to_doc_attr(&format!("{}\n\n{}", short, long))
+pub(crate) fn deprecated_attr(deprecated_since: Option<u32>) -> Option<TokenStream> {
+    deprecated_since.map(|version| {
+        let note = format!("Deprecated since version {} of the interface", version);
+        quote!(#[deprecated(note = #note)])
+    })
+}
+
+pub(crate) fn description_to_doc_attr(description: &(String, String)) -> TokenStream {
+    to_doc_attr(&description_to_doc(Some(description), None))
+}
+
+/// The documentation text of an element, from its description or else its summary
+///
+/// The text is trimmed, so that more paragraphs can be appended to it.
+pub(crate) fn description_to_doc(
+    description: Option<&(String, String)>,
+    summary: Option<&String>,
+) -> String {
+    match (description, summary) {
+        (Some((short, long)), _) => {
+            format!("{}\n\n{}", short.trim(), long.trim()).trim().to_owned()
+        }
+        (None, Some(summary)) => summary.trim().to_owned(),
+        (None, None) => String::new(),
+    }
 }
 
 pub fn is_keyword(txt: &str) -> bool {
@@ -113,3 +136,19 @@ pub fn dotted_to_relname(input: &str) -> TokenStream {
         _ => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::description_to_doc;
+
+    #[test]
+    fn description_doc_is_trimmed() {
+        let description = ("short".to_owned(), "\n  long\n  ".to_owned());
+        assert_eq!(description_to_doc(Some(&description), None), "short\n\nlong");
+        let description = ("short".to_owned(), "\n    ".to_owned());
+        let summary = "summary".to_owned();
+        assert_eq!(description_to_doc(Some(&description), Some(&summary)), "short");
+        assert_eq!(description_to_doc(None, Some(&" summary ".to_owned())), "summary");
+        assert_eq!(description_to_doc(None, None), "");
+    }
+}
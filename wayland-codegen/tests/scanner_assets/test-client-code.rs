@@ -78,6 +78,14 @@ pub mod wl_display {
                 Request::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Sync { .. } => 1u32,
+                Request::GetRegistry { .. } => 1u32,
+                Request::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -105,8 +113,15 @@ pub mod wl_display {
                 Event::DeleteId { .. } => 1u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::Error { .. } => 1u32,
+                Event::DeleteId { .. } => 1u32,
+            }
+        }
     }
-    #[doc = "core global object\n\nThe core global object.  This is a special singleton object.  It\nis used for internal Wayland protocol features.\n\nSee also the [Event] enum for this interface."]
+    #[doc = "core global object\n\nThe core global object.  This is a special singleton object.  It\nis used for internal Wayland protocol features.\n\nSee also the [Event] enum for this interface.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | [`sync`](Request::Sync), [`get_registry`](Request::GetRegistry) | [`error`](Event::Error), [`delete_id`](Event::DeleteId) |"]
     #[derive(Debug, Clone)]
     pub struct WlDisplay {
         id: ObjectId,
@@ -311,6 +326,119 @@ pub mod wl_display {
             .unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
         }
     }
+    #[doc = "Handler for the events of [WlDisplay], with one method per event\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [WlDisplay], using `delegate_dispatch!(State: [WlDisplay: UserData] => WlDisplay);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "fatal error event\n\nThe error event is sent out when a fatal (non-recoverable)\nerror has occurred.  The object_id argument is the object\nwhere the error occurred, most often in response to a request\nto that object.  The code identifies the error and is defined\nby the object interface.  As such, each interface defines its\nown set of error codes.  The message is a brief description\nof the error, for (debugging) convenience."]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn error(
+            &mut self,
+            proxy: &WlDisplay,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            object_id: super::wayland_client::ObjectId,
+            code: u32,
+            message: String,
+        ) {
+        }
+        #[doc = "acknowledge object ID deletion\n\nThis event is used internally by the object ID management\nlogic. When a client deletes an object that it had created,\nthe server will send this event to acknowledge that it has\nseen the delete request. When the client receives this event,\nit will know that it can safely reuse the object ID."]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn delete_id(
+            &mut self,
+            proxy: &WlDisplay,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            id: u32,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<WlDisplay, U, D> for WlDisplay
+    where
+        D: Handler<U> + Dispatch<WlDisplay, U, D>,
+    {
+        fn event(
+            state: &mut D,
+            proxy: &WlDisplay,
+            event: Event,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<D>,
+        ) {
+            match event {
+                Event::Error { object_id, code, message } => <D as Handler<U>>::error(
+                    state, proxy, data, conn, qhandle, object_id, code, message,
+                ),
+                Event::DeleteId { id } => {
+                    <D as Handler<U>>::delete_id(state, proxy, data, conn, qhandle, id)
+                }
+            }
+        }
+    }
+    const _: () = {
+        use super::wayland_client::backend::serde;
+        impl serde::Serialize for WlDisplay {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl serde::Serialize for Error {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match *self {
+                    Error::InvalidObject => {
+                        serializer.serialize_unit_variant("error", 0u32, "invalid_object")
+                    }
+                    Error::InvalidMethod => {
+                        serializer.serialize_unit_variant("error", 1u32, "invalid_method")
+                    }
+                    Error::NoMemory => {
+                        serializer.serialize_unit_variant("error", 2u32, "no_memory")
+                    }
+                    Error::Implementation => {
+                        serializer.serialize_unit_variant("error", 3u32, "implementation")
+                    }
+                }
+            }
+        }
+        impl<'a> serde::Serialize for Request<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::Sync { .. } => {
+                        serializer.serialize_unit_variant("Request", 0u32, "sync")
+                    }
+                    Request::GetRegistry { .. } => {
+                        serializer.serialize_unit_variant("Request", 1u32, "get_registry")
+                    }
+                    Request::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+        impl serde::Serialize for Event {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Event::Error {
+                        object_id,
+                        code,
+                        message,
+                    } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Event", 0u32, "error", 3)?;
+                        state.serialize_field("object_id", object_id)?;
+                        state.serialize_field("code", code)?;
+                        state.serialize_field("message", message)?;
+                        state.end()
+                    }
+                    Event::DeleteId { id } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Event", 1u32, "delete_id", 1)?;
+                        state.serialize_field("id", id)?;
+                        state.end()
+                    }
+                }
+            }
+        }
+    };
 }
 #[doc = "global registry object\n\nThe singleton global registry object.  The server has a number of\nglobal objects that are available to all clients.  These objects\ntypically represent an actual object in the server (for example,\nan input device) or they are singleton objects that provide\nextension functionality.\n\nWhen a client creates a registry object, the registry object\nwill emit a global event for each global currently in the\nregistry.  Globals come and go as a result of device or\nmonitor hotplugs, reconfiguration or other events, and the\nregistry will send out global and global_remove events to\nkeep the client up to date with the changes.  To mark the end\nof the initial burst of events, the client can use the\nwl_display.sync request immediately after calling\nwl_display.get_registry.\n\nA client can bind to a global object by using the bind\nrequest.  This creates a client-side handle that lets the object\nemit events to the client and lets the client invoke requests on\nthe object."]
 pub mod wl_registry {
@@ -359,6 +487,13 @@ pub mod wl_registry {
                 Request::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Bind { .. } => 1u32,
+                Request::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -386,8 +521,15 @@ pub mod wl_registry {
                 Event::GlobalRemove { .. } => 1u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::Global { .. } => 1u32,
+                Event::GlobalRemove { .. } => 1u32,
+            }
+        }
     }
-    #[doc = "global registry object\n\nThe singleton global registry object.  The server has a number of\nglobal objects that are available to all clients.  These objects\ntypically represent an actual object in the server (for example,\nan input device) or they are singleton objects that provide\nextension functionality.\n\nWhen a client creates a registry object, the registry object\nwill emit a global event for each global currently in the\nregistry.  Globals come and go as a result of device or\nmonitor hotplugs, reconfiguration or other events, and the\nregistry will send out global and global_remove events to\nkeep the client up to date with the changes.  To mark the end\nof the initial burst of events, the client can use the\nwl_display.sync request immediately after calling\nwl_display.get_registry.\n\nA client can bind to a global object by using the bind\nrequest.  This creates a client-side handle that lets the object\nemit events to the client and lets the client invoke requests on\nthe object.\n\nSee also the [Event] enum for this interface."]
+    #[doc = "global registry object\n\nThe singleton global registry object.  The server has a number of\nglobal objects that are available to all clients.  These objects\ntypically represent an actual object in the server (for example,\nan input device) or they are singleton objects that provide\nextension functionality.\n\nWhen a client creates a registry object, the registry object\nwill emit a global event for each global currently in the\nregistry.  Globals come and go as a result of device or\nmonitor hotplugs, reconfiguration or other events, and the\nregistry will send out global and global_remove events to\nkeep the client up to date with the changes.  To mark the end\nof the initial burst of events, the client can use the\nwl_display.sync request immediately after calling\nwl_display.get_registry.\n\nA client can bind to a global object by using the bind\nrequest.  This creates a client-side handle that lets the object\nemit events to the client and lets the client invoke requests on\nthe object.\n\nSee also the [Event] enum for this interface.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | [`bind`](Request::Bind) | [`global`](Event::Global), [`global_remove`](Event::GlobalRemove) |"]
     #[derive(Debug, Clone)]
     pub struct WlRegistry {
         id: ObjectId,
@@ -565,6 +707,107 @@ pub mod wl_registry {
             .unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
         }
     }
+    #[doc = "Handler for the events of [WlRegistry], with one method per event\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [WlRegistry], using `delegate_dispatch!(State: [WlRegistry: UserData] => WlRegistry);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "announce global object\n\nNotify the client of global objects.\n\nThe event notifies the client that a global object with\nthe given name is now available, and it implements the\ngiven version of the given interface."]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn global(
+            &mut self,
+            proxy: &WlRegistry,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            name: u32,
+            interface: String,
+            version: u32,
+        ) {
+        }
+        #[doc = "announce removal of global object\n\nNotify the client of removed global objects.\n\nThis event notifies the client that the global identified\nby name is no longer available.  If the client bound to\nthe global using the bind request, the client should now\ndestroy that object.\n\nThe object remains valid and requests to the object will be\nignored until the client destroys it, to avoid races between\nthe global going away and a client sending a request to it."]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn global_remove(
+            &mut self,
+            proxy: &WlRegistry,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            name: u32,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<WlRegistry, U, D> for WlRegistry
+    where
+        D: Handler<U> + Dispatch<WlRegistry, U, D>,
+    {
+        fn event(
+            state: &mut D,
+            proxy: &WlRegistry,
+            event: Event,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<D>,
+        ) {
+            match event {
+                Event::Global { name, interface, version } => <D as Handler<U>>::global(
+                    state, proxy, data, conn, qhandle, name, interface, version,
+                ),
+                Event::GlobalRemove { name } => {
+                    <D as Handler<U>>::global_remove(state, proxy, data, conn, qhandle, name)
+                }
+            }
+        }
+    }
+    const _: () = {
+        use super::wayland_client::backend::serde;
+        impl serde::Serialize for WlRegistry {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl<'a> serde::Serialize for Request<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Request::Bind { name, id } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Request", 0u32, "bind", 2)?;
+                        state.serialize_field("name", name)?;
+                        state.serialize_field("id", id)?;
+                        state.end()
+                    }
+                    Request::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+        impl serde::Serialize for Event {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Event::Global {
+                        name,
+                        interface,
+                        version,
+                    } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Event", 0u32, "global", 3)?;
+                        state.serialize_field("name", name)?;
+                        state.serialize_field("interface", interface)?;
+                        state.serialize_field("version", version)?;
+                        state.end()
+                    }
+                    Event::GlobalRemove { name } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Event",
+                            1u32,
+                            "global_remove",
+                            1,
+                        )?;
+                        state.serialize_field("name", name)?;
+                        state.end()
+                    }
+                }
+            }
+        }
+    };
 }
 #[doc = "callback object\n\nClients can handle the 'done' event to get notified when\nthe related request is done."]
 pub mod wl_callback {
@@ -597,6 +840,12 @@ pub mod wl_callback {
                 Request::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -614,8 +863,14 @@ pub mod wl_callback {
                 Event::Done { .. } => 0u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::Done { .. } => 1u32,
+            }
+        }
     }
-    #[doc = "callback object\n\nClients can handle the 'done' event to get notified when\nthe related request is done.\n\nSee also the [Event] enum for this interface."]
+    #[doc = "callback object\n\nClients can handle the 'done' event to get notified when\nthe related request is done.\n\nSee also the [Event] enum for this interface.\n\nThis interface is frozen: no new versions of it will be released.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | [`done`](Event::Done) |"]
     #[derive(Debug, Clone)]
     pub struct WlCallback {
         id: ObjectId,
@@ -737,7 +992,69 @@ pub mod wl_callback {
         }
     }
     impl WlCallback {}
+    #[doc = "Handler for the events of [WlCallback], with one method per event\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [WlCallback], using `delegate_dispatch!(State: [WlCallback: UserData] => WlCallback);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "done event\n\nNotify the client when the related request is done."]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn done(
+            &mut self,
+            proxy: &WlCallback,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            callback_data: u32,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<WlCallback, U, D> for WlCallback
+    where
+        D: Handler<U> + Dispatch<WlCallback, U, D>,
+    {
+        fn event(
+            state: &mut D,
+            proxy: &WlCallback,
+            event: Event,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<D>,
+        ) {
+            match event {
+                Event::Done { callback_data } => {
+                    <D as Handler<U>>::done(state, proxy, data, conn, qhandle, callback_data)
+                }
+            }
+        }
+    }
+    const _: () = {
+        use super::wayland_client::backend::serde;
+        impl serde::Serialize for WlCallback {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl<'a> serde::Serialize for Request<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+        impl serde::Serialize for Event {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Event::Done { callback_data } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Event", 0u32, "done", 1)?;
+                        state.serialize_field("callback_data", callback_data)?;
+                        state.end()
+                    }
+                }
+            }
+        }
+    };
 }
+#[allow(deprecated)]
 pub mod test_global {
     use super::wayland_client::{
         backend::{
@@ -811,14 +1128,28 @@ pub mod test_global {
         #[doc = "Only available since version 3 of the interface"]
         GetTertiary {},
         #[doc = "link a secondary and a tertiary\n\n\n\nOnly available since version 3 of the interface"]
-        Link { sec: super::secondary::Secondary, ter: Option<super::tertiary::Tertiary>, time: u32 },
+        #[deprecated(note = "Deprecated since version 5 of the interface")]
+        Link {
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
+            sec: super::secondary::Secondary,
+            #[doc = "Object of interface [`Tertiary`](super::tertiary::Tertiary)."]
+            ter: Option<super::tertiary::Tertiary>,
+            time: u32,
+        },
         #[doc = "This is a destructor, once sent this object cannot be used any longer.\nOnly available since version 4 of the interface"]
         Destroy,
         #[doc = "reverse link a secondary and a tertiary\n\n\n\nOnly available since version 5 of the interface"]
-        ReverseLink { sec: Option<super::secondary::Secondary>, ter: super::tertiary::Tertiary },
+        ReverseLink {
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
+            sec: Option<super::secondary::Secondary>,
+            #[doc = "Object of interface [`Tertiary`](super::tertiary::Tertiary)."]
+            ter: super::tertiary::Tertiary,
+        },
         #[doc = "a newid request that also takes allow null arg\n\n\n\nOnly available since version 5 of the interface"]
         NewidAndAllowNull {
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
             sec: Option<super::secondary::Secondary>,
+            #[doc = "Object of interface [`Tertiary`](super::tertiary::Tertiary)."]
             ter: super::tertiary::Tertiary,
         },
         #[doc(hidden)]
@@ -841,6 +1172,19 @@ pub mod test_global {
                 Request::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::ManyArgs { .. } => 1u32,
+                Request::GetSecondary { .. } => 2u32,
+                Request::GetTertiary { .. } => 3u32,
+                Request::Link { .. } => 3u32,
+                Request::Destroy => 4u32,
+                Request::ReverseLink { .. } => 5u32,
+                Request::NewidAndAllowNull { .. } => 5u32,
+                Request::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -861,9 +1205,17 @@ pub mod test_global {
             file_descriptor: OwnedFd,
         },
         #[doc = "acking the creation of a secondary"]
-        AckSecondary { sec: super::secondary::Secondary },
+        AckSecondary {
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
+            sec: super::secondary::Secondary,
+        },
         #[doc = "create a new quad optionally replacing a previous one"]
-        CycleQuad { new_quad: super::quad::Quad, old_quad: Option<super::quad::Quad> },
+        CycleQuad {
+            #[doc = "Creates a new [`Quad`](super::quad::Quad) object, see the [`quad`](super::quad) module."]
+            new_quad: super::quad::Quad,
+            #[doc = "Object of interface [`Quad`](super::quad::Quad)."]
+            old_quad: Option<super::quad::Quad>,
+        },
     }
     impl Event {
         #[doc = "Get the opcode number of this message"]
@@ -874,8 +1226,16 @@ pub mod test_global {
                 Event::CycleQuad { .. } => 2u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::ManyArgsEvt { .. } => 1u32,
+                Event::AckSecondary { .. } => 1u32,
+                Event::CycleQuad { .. } => 1u32,
+            }
+        }
     }
-    #[doc = "test_global\n\nSee also the [Event] enum for this interface."]
+    #[doc = "test_global\n\nSee also the [Event] enum for this interface.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | [`many_args`](Request::ManyArgs) | [`many_args_evt`](Event::ManyArgsEvt), [`ack_secondary`](Event::AckSecondary), [`cycle_quad`](Event::CycleQuad) |\n| 2 | [`get_secondary`](Request::GetSecondary) | - |\n| 3 | [`get_tertiary`](Request::GetTertiary), [`link`](Request::Link) | - |\n| 4 | [`destroy`](Request::Destroy) | - |\n| 5 | [`reverse_link`](Request::ReverseLink), [`newid_and_allow_null`](Request::NewidAndAllowNull) | - |"]
     #[derive(Debug, Clone)]
     pub struct TestGlobal {
         id: ObjectId,
@@ -1225,6 +1585,7 @@ pub mod test_global {
                 None,
             );
         }
+        #[doc = "Only available since version 2 of the interface ([`REQ_GET_SECONDARY_SINCE`]), see [`try_get_secondary()`](Self::try_get_secondary) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn get_secondary<
             U: Send + Sync + 'static,
@@ -1240,6 +1601,25 @@ pub mod test_global {
             )
             .unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
         }
+        #[doc = "Version-checked variant of [`get_secondary()`](Self::get_secondary)\n\nReturns an error without sending the request if the version of this object is lower than 2."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_get_secondary<
+            U: Send + Sync + 'static,
+            D: Dispatch<super::secondary::Secondary, U> + 'static,
+        >(
+            &self,
+            qh: &QueueHandle<D>,
+            udata: U,
+        ) -> Result<super::secondary::Secondary, super::wayland_client::VersionTooLow> {
+            if self.version < REQ_GET_SECONDARY_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_GET_SECONDARY_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.get_secondary(qh, udata))
+        }
+        #[doc = "Only available since version 3 of the interface ([`REQ_GET_TERTIARY_SINCE`]), see [`try_get_tertiary()`](Self::try_get_tertiary) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn get_tertiary<
             U: Send + Sync + 'static,
@@ -1255,7 +1635,28 @@ pub mod test_global {
             )
             .unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
         }
+        #[doc = "Version-checked variant of [`get_tertiary()`](Self::get_tertiary)\n\nReturns an error without sending the request if the version of this object is lower than 3."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_get_tertiary<
+            U: Send + Sync + 'static,
+            D: Dispatch<super::tertiary::Tertiary, U> + 'static,
+        >(
+            &self,
+            qh: &QueueHandle<D>,
+            udata: U,
+        ) -> Result<super::tertiary::Tertiary, super::wayland_client::VersionTooLow> {
+            if self.version < REQ_GET_TERTIARY_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_GET_TERTIARY_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.get_tertiary(qh, udata))
+        }
         #[doc = "link a secondary and a tertiary"]
+        #[doc = ""]
+        #[doc = "Only available since version 3 of the interface ([`REQ_LINK_SINCE`]), see [`try_link()`](Self::try_link) for a version-checked variant."]
+        #[deprecated(note = "Deprecated since version 5 of the interface")]
         #[allow(clippy::too_many_arguments)]
         pub fn link(
             &self,
@@ -1274,6 +1675,26 @@ pub mod test_global {
                 None,
             );
         }
+        #[doc = "Version-checked variant of [`link()`](Self::link)\n\nReturns an error without sending the request if the version of this object is lower than 3."]
+        #[deprecated(note = "Deprecated since version 5 of the interface")]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_link(
+            &self,
+            sec: &super::secondary::Secondary,
+            ter: Option<&super::tertiary::Tertiary>,
+            time: u32,
+        ) -> Result<(), super::wayland_client::VersionTooLow> {
+            if self.version < REQ_LINK_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_LINK_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.link(sec, ter, time))
+        }
+        #[doc = "This is a destructor, once sent this object cannot be used any longer."]
+        #[doc = ""]
+        #[doc = "Only available since version 4 of the interface ([`REQ_DESTROY_SINCE`]), see [`try_destroy()`](Self::try_destroy) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn destroy(&self) {
             let backend = match self.backend.upgrade() {
@@ -1283,7 +1704,20 @@ pub mod test_global {
             let conn = Connection::from_backend(backend);
             let _ = conn.send_request(self, Request::Destroy {}, None);
         }
+        #[doc = "Version-checked variant of [`destroy()`](Self::destroy)\n\nReturns an error without sending the request if the version of this object is lower than 4."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_destroy(&self) -> Result<(), super::wayland_client::VersionTooLow> {
+            if self.version < REQ_DESTROY_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_DESTROY_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.destroy())
+        }
         #[doc = "reverse link a secondary and a tertiary"]
+        #[doc = ""]
+        #[doc = "Only available since version 5 of the interface ([`REQ_REVERSE_LINK_SINCE`]), see [`try_reverse_link()`](Self::try_reverse_link) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn reverse_link(
             &self,
@@ -1301,7 +1735,24 @@ pub mod test_global {
                 None,
             );
         }
+        #[doc = "Version-checked variant of [`reverse_link()`](Self::reverse_link)\n\nReturns an error without sending the request if the version of this object is lower than 5."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_reverse_link(
+            &self,
+            sec: Option<&super::secondary::Secondary>,
+            ter: &super::tertiary::Tertiary,
+        ) -> Result<(), super::wayland_client::VersionTooLow> {
+            if self.version < REQ_REVERSE_LINK_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_REVERSE_LINK_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.reverse_link(sec, ter))
+        }
         #[doc = "a newid request that also takes allow null arg"]
+        #[doc = ""]
+        #[doc = "Only available since version 5 of the interface ([`REQ_NEWID_AND_ALLOW_NULL_SINCE`]), see [`try_newid_and_allow_null()`](Self::try_newid_and_allow_null) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn newid_and_allow_null<
             U: Send + Sync + 'static,
@@ -1319,7 +1770,243 @@ pub mod test_global {
             )
             .unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
         }
+        #[doc = "Version-checked variant of [`newid_and_allow_null()`](Self::newid_and_allow_null)\n\nReturns an error without sending the request if the version of this object is lower than 5."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_newid_and_allow_null<
+            U: Send + Sync + 'static,
+            D: Dispatch<super::quad::Quad, U> + 'static,
+        >(
+            &self,
+            sec: Option<&super::secondary::Secondary>,
+            ter: &super::tertiary::Tertiary,
+            qh: &QueueHandle<D>,
+            udata: U,
+        ) -> Result<super::quad::Quad, super::wayland_client::VersionTooLow> {
+            if self.version < REQ_NEWID_AND_ALLOW_NULL_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_NEWID_AND_ALLOW_NULL_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.newid_and_allow_null(sec, ter, qh, udata))
+        }
     }
+    #[doc = "Handler for the events of [TestGlobal], with one method per event\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [TestGlobal], using `delegate_dispatch!(State: [TestGlobal: UserData] => TestGlobal);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "an event with every possible non-object arg"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn many_args_evt(
+            &mut self,
+            proxy: &TestGlobal,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            unsigned_int: u32,
+            signed_int: i32,
+            fixed_point: f64,
+            number_array: Vec<u8>,
+            some_text: String,
+            file_descriptor: OwnedFd,
+        ) {
+        }
+        #[doc = "acking the creation of a secondary"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn ack_secondary(
+            &mut self,
+            proxy: &TestGlobal,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            sec: super::secondary::Secondary,
+        ) {
+        }
+        #[doc = "create a new quad optionally replacing a previous one"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn cycle_quad(
+            &mut self,
+            proxy: &TestGlobal,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<Self>,
+            new_quad: super::quad::Quad,
+            old_quad: Option<super::quad::Quad>,
+        ) {
+        }
+        #[doc = r" Initialize the user data of objects created by events"]
+        #[doc = r""]
+        #[doc = r" See [`Dispatch::event_created_child()`]."]
+        fn event_created_child(opcode: u16, _qhandle: &QueueHandle<Self>) -> Arc<dyn ObjectData> {
+            panic!(
+                "Missing event_created_child specialization for event opcode {} of {}",
+                opcode,
+                <TestGlobal as Proxy>::interface().name
+            );
+        }
+    }
+    impl<U, D> Dispatch<TestGlobal, U, D> for TestGlobal
+    where
+        D: Handler<U> + Dispatch<TestGlobal, U, D>,
+    {
+        fn event(
+            state: &mut D,
+            proxy: &TestGlobal,
+            event: Event,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<D>,
+        ) {
+            match event {
+                Event::ManyArgsEvt {
+                    unsigned_int,
+                    signed_int,
+                    fixed_point,
+                    number_array,
+                    some_text,
+                    file_descriptor,
+                } => <D as Handler<U>>::many_args_evt(
+                    state,
+                    proxy,
+                    data,
+                    conn,
+                    qhandle,
+                    unsigned_int,
+                    signed_int,
+                    fixed_point,
+                    number_array,
+                    some_text,
+                    file_descriptor,
+                ),
+                Event::AckSecondary { sec } => {
+                    <D as Handler<U>>::ack_secondary(state, proxy, data, conn, qhandle, sec)
+                }
+                Event::CycleQuad { new_quad, old_quad } => <D as Handler<U>>::cycle_quad(
+                    state, proxy, data, conn, qhandle, new_quad, old_quad,
+                ),
+            }
+        }
+        fn event_created_child(opcode: u16, qhandle: &QueueHandle<D>) -> Arc<dyn ObjectData> {
+            <D as Handler<U>>::event_created_child(opcode, qhandle)
+        }
+    }
+    const _: () = {
+        use super::wayland_client::backend::serde;
+        impl serde::Serialize for TestGlobal {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl<'a> serde::Serialize for Request<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Request::ManyArgs {
+                        unsigned_int,
+                        signed_int,
+                        fixed_point,
+                        number_array,
+                        some_text,
+                        ..
+                    } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Request", 0u32, "many_args", 6)?;
+                        state.serialize_field("unsigned_int", unsigned_int)?;
+                        state.serialize_field("signed_int", signed_int)?;
+                        state.serialize_field("fixed_point", fixed_point)?;
+                        state.serialize_field("number_array", number_array)?;
+                        state.serialize_field("some_text", some_text)?;
+                        state.serialize_field("file_descriptor", "<fd>")?;
+                        state.end()
+                    }
+                    Request::GetSecondary { .. } => {
+                        serializer.serialize_unit_variant("Request", 1u32, "get_secondary")
+                    }
+                    Request::GetTertiary { .. } => {
+                        serializer.serialize_unit_variant("Request", 2u32, "get_tertiary")
+                    }
+                    Request::Link { sec, ter, time } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Request", 3u32, "link", 3)?;
+                        state.serialize_field("sec", sec)?;
+                        state.serialize_field("ter", ter)?;
+                        state.serialize_field("time", time)?;
+                        state.end()
+                    }
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 4u32, "destroy")
+                    }
+                    Request::ReverseLink { sec, ter } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Request",
+                            5u32,
+                            "reverse_link",
+                            2,
+                        )?;
+                        state.serialize_field("sec", sec)?;
+                        state.serialize_field("ter", ter)?;
+                        state.end()
+                    }
+                    Request::NewidAndAllowNull { sec, ter, .. } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Request",
+                            6u32,
+                            "newid_and_allow_null",
+                            2,
+                        )?;
+                        state.serialize_field("sec", sec)?;
+                        state.serialize_field("ter", ter)?;
+                        state.end()
+                    }
+                    Request::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+        impl serde::Serialize for Event {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Event::ManyArgsEvt {
+                        unsigned_int,
+                        signed_int,
+                        fixed_point,
+                        number_array,
+                        some_text,
+                        ..
+                    } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Event",
+                            0u32,
+                            "many_args_evt",
+                            6,
+                        )?;
+                        state.serialize_field("unsigned_int", unsigned_int)?;
+                        state.serialize_field("signed_int", signed_int)?;
+                        state.serialize_field("fixed_point", fixed_point)?;
+                        state.serialize_field("number_array", number_array)?;
+                        state.serialize_field("some_text", some_text)?;
+                        state.serialize_field("file_descriptor", "<fd>")?;
+                        state.end()
+                    }
+                    Event::AckSecondary { sec } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Event",
+                            1u32,
+                            "ack_secondary",
+                            1,
+                        )?;
+                        state.serialize_field("sec", sec)?;
+                        state.end()
+                    }
+                    Event::CycleQuad { new_quad, old_quad } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Event", 2u32, "cycle_quad", 2)?;
+                        state.serialize_field("new_quad", new_quad)?;
+                        state.serialize_field("old_quad", old_quad)?;
+                        state.end()
+                    }
+                }
+            }
+        }
+    };
 }
 pub mod secondary {
     use super::wayland_client::{
@@ -1354,6 +2041,13 @@ pub mod secondary {
                 Request::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Destroy => 2u32,
+                Request::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -1363,8 +2057,12 @@ pub mod secondary {
         pub fn opcode(&self) -> u16 {
             match *self {}
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {}
+        }
     }
-    #[doc = "secondary\n\nThis interface has no events."]
+    #[doc = "secondary\n\nThis interface has no events.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | - |\n| 2 | [`destroy`](Request::Destroy) | - |"]
     #[derive(Debug, Clone)]
     pub struct Secondary {
         id: ObjectId,
@@ -1480,6 +2178,9 @@ pub mod secondary {
         }
     }
     impl Secondary {
+        #[doc = "This is a destructor, once sent this object cannot be used any longer."]
+        #[doc = ""]
+        #[doc = "Only available since version 2 of the interface ([`REQ_DESTROY_SINCE`]), see [`try_destroy()`](Self::try_destroy) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn destroy(&self) {
             let backend = match self.backend.upgrade() {
@@ -1489,7 +2190,58 @@ pub mod secondary {
             let conn = Connection::from_backend(backend);
             let _ = conn.send_request(self, Request::Destroy {}, None);
         }
+        #[doc = "Version-checked variant of [`destroy()`](Self::destroy)\n\nReturns an error without sending the request if the version of this object is lower than 2."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_destroy(&self) -> Result<(), super::wayland_client::VersionTooLow> {
+            if self.version < REQ_DESTROY_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_DESTROY_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.destroy())
+        }
+    }
+    #[doc = "Handler for the events of [Secondary], with one method per event\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [Secondary], using `delegate_dispatch!(State: [Secondary: UserData] => Secondary);`."]
+    pub trait Handler<U>: Sized {}
+    impl<U, D> Dispatch<Secondary, U, D> for Secondary
+    where
+        D: Handler<U> + Dispatch<Secondary, U, D>,
+    {
+        fn event(
+            state: &mut D,
+            proxy: &Secondary,
+            event: Event,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<D>,
+        ) {
+            match event {}
+        }
     }
+    const _: () = {
+        use super::wayland_client::backend::serde;
+        impl serde::Serialize for Secondary {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl<'a> serde::Serialize for Request<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 0u32, "destroy")
+                    }
+                    Request::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+        impl serde::Serialize for Event {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match *self {}
+            }
+        }
+    };
 }
 pub mod tertiary {
     use super::wayland_client::{
@@ -1524,6 +2276,13 @@ pub mod tertiary {
                 Request::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Destroy => 3u32,
+                Request::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -1533,8 +2292,12 @@ pub mod tertiary {
         pub fn opcode(&self) -> u16 {
             match *self {}
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {}
+        }
     }
-    #[doc = "tertiary\n\nThis interface has no events."]
+    #[doc = "tertiary\n\nThis interface has no events.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | - |\n| 3 | [`destroy`](Request::Destroy) | - |"]
     #[derive(Debug, Clone)]
     pub struct Tertiary {
         id: ObjectId,
@@ -1650,6 +2413,9 @@ pub mod tertiary {
         }
     }
     impl Tertiary {
+        #[doc = "This is a destructor, once sent this object cannot be used any longer."]
+        #[doc = ""]
+        #[doc = "Only available since version 3 of the interface ([`REQ_DESTROY_SINCE`]), see [`try_destroy()`](Self::try_destroy) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn destroy(&self) {
             let backend = match self.backend.upgrade() {
@@ -1659,7 +2425,58 @@ pub mod tertiary {
             let conn = Connection::from_backend(backend);
             let _ = conn.send_request(self, Request::Destroy {}, None);
         }
+        #[doc = "Version-checked variant of [`destroy()`](Self::destroy)\n\nReturns an error without sending the request if the version of this object is lower than 3."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_destroy(&self) -> Result<(), super::wayland_client::VersionTooLow> {
+            if self.version < REQ_DESTROY_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_DESTROY_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.destroy())
+        }
+    }
+    #[doc = "Handler for the events of [Tertiary], with one method per event\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [Tertiary], using `delegate_dispatch!(State: [Tertiary: UserData] => Tertiary);`."]
+    pub trait Handler<U>: Sized {}
+    impl<U, D> Dispatch<Tertiary, U, D> for Tertiary
+    where
+        D: Handler<U> + Dispatch<Tertiary, U, D>,
+    {
+        fn event(
+            state: &mut D,
+            proxy: &Tertiary,
+            event: Event,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<D>,
+        ) {
+            match event {}
+        }
     }
+    const _: () = {
+        use super::wayland_client::backend::serde;
+        impl serde::Serialize for Tertiary {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl<'a> serde::Serialize for Request<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 0u32, "destroy")
+                    }
+                    Request::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+        impl serde::Serialize for Event {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match *self {}
+            }
+        }
+    };
 }
 pub mod quad {
     use super::wayland_client::{
@@ -1694,6 +2511,13 @@ pub mod quad {
                 Request::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Destroy => 3u32,
+                Request::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -1703,8 +2527,12 @@ pub mod quad {
         pub fn opcode(&self) -> u16 {
             match *self {}
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {}
+        }
     }
-    #[doc = "quad\n\nThis interface has no events."]
+    #[doc = "quad\n\nThis interface has no events.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | - |\n| 3 | [`destroy`](Request::Destroy) | - |"]
     #[derive(Debug, Clone)]
     pub struct Quad {
         id: ObjectId,
@@ -1820,6 +2648,9 @@ pub mod quad {
         }
     }
     impl Quad {
+        #[doc = "This is a destructor, once sent this object cannot be used any longer."]
+        #[doc = ""]
+        #[doc = "Only available since version 3 of the interface ([`REQ_DESTROY_SINCE`]), see [`try_destroy()`](Self::try_destroy) for a version-checked variant."]
         #[allow(clippy::too_many_arguments)]
         pub fn destroy(&self) {
             let backend = match self.backend.upgrade() {
@@ -1829,5 +2660,56 @@ pub mod quad {
             let conn = Connection::from_backend(backend);
             let _ = conn.send_request(self, Request::Destroy {}, None);
         }
+        #[doc = "Version-checked variant of [`destroy()`](Self::destroy)\n\nReturns an error without sending the request if the version of this object is lower than 3."]
+        #[allow(clippy::too_many_arguments)]
+        pub fn try_destroy(&self) -> Result<(), super::wayland_client::VersionTooLow> {
+            if self.version < REQ_DESTROY_SINCE {
+                return Err(super::wayland_client::VersionTooLow {
+                    required: REQ_DESTROY_SINCE,
+                    bound: self.version,
+                });
+            }
+            Ok(self.destroy())
+        }
+    }
+    #[doc = "Handler for the events of [Quad], with one method per event\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [Quad], using `delegate_dispatch!(State: [Quad: UserData] => Quad);`."]
+    pub trait Handler<U>: Sized {}
+    impl<U, D> Dispatch<Quad, U, D> for Quad
+    where
+        D: Handler<U> + Dispatch<Quad, U, D>,
+    {
+        fn event(
+            state: &mut D,
+            proxy: &Quad,
+            event: Event,
+            data: &U,
+            conn: &Connection,
+            qhandle: &QueueHandle<D>,
+        ) {
+            match event {}
+        }
     }
+    const _: () = {
+        use super::wayland_client::backend::serde;
+        impl serde::Serialize for Quad {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl<'a> serde::Serialize for Request<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 0u32, "destroy")
+                    }
+                    Request::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+        impl serde::Serialize for Event {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match *self {}
+            }
+        }
+    };
 }
@@ -8,27 +8,27 @@ pub static WL_DISPLAY_INTERFACE: wayland_backend::protocol::Interface =
         name: "wl_display",
         version: 1u32,
         requests: &[
-            wayland_backend::protocol::MessageDesc {
-                name: "sync",
-                signature: &[wayland_backend::protocol::ArgumentType::NewId],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: Some(&WL_CALLBACK_INTERFACE),
-                arg_interfaces: &[],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "get_registry",
-                signature: &[wayland_backend::protocol::ArgumentType::NewId],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: Some(&WL_REGISTRY_INTERFACE),
-                arg_interfaces: &[],
-            },
+            wayland_backend::protocol::MessageDesc::new(
+                "sync",
+                &[wayland_backend::protocol::ArgumentType::NewId],
+                1u32,
+                false,
+                Some(&WL_CALLBACK_INTERFACE),
+                &[],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "get_registry",
+                &[wayland_backend::protocol::ArgumentType::NewId],
+                1u32,
+                false,
+                Some(&WL_REGISTRY_INTERFACE),
+                &[],
+            ),
         ],
         events: &[
-            wayland_backend::protocol::MessageDesc {
-                name: "error",
-                signature: &[
+            wayland_backend::protocol::MessageDesc::new(
+                "error",
+                &[
                     wayland_backend::protocol::ArgumentType::Object(
                         wayland_backend::protocol::AllowNull::No,
                     ),
@@ -37,19 +37,19 @@ pub static WL_DISPLAY_INTERFACE: wayland_backend::protocol::Interface =
                         wayland_backend::protocol::AllowNull::No,
                     ),
                 ],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[&wayland_backend::protocol::ANONYMOUS_INTERFACE],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "delete_id",
-                signature: &[wayland_backend::protocol::ArgumentType::Uint],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[],
-            },
+                1u32,
+                false,
+                None,
+                &[&wayland_backend::protocol::ANONYMOUS_INTERFACE],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "delete_id",
+                &[wayland_backend::protocol::ArgumentType::Uint],
+                1u32,
+                false,
+                None,
+                &[],
+            ),
         ],
         c_ptr: Some(unsafe { &wl_display_interface }),
     };
@@ -97,9 +97,9 @@ pub static WL_REGISTRY_INTERFACE: wayland_backend::protocol::Interface =
     wayland_backend::protocol::Interface {
         name: "wl_registry",
         version: 1u32,
-        requests: &[wayland_backend::protocol::MessageDesc {
-            name: "bind",
-            signature: &[
+        requests: &[wayland_backend::protocol::MessageDesc::new(
+            "bind",
+            &[
                 wayland_backend::protocol::ArgumentType::Uint,
                 wayland_backend::protocol::ArgumentType::Str(
                     wayland_backend::protocol::AllowNull::No,
@@ -107,34 +107,34 @@ pub static WL_REGISTRY_INTERFACE: wayland_backend::protocol::Interface =
                 wayland_backend::protocol::ArgumentType::Uint,
                 wayland_backend::protocol::ArgumentType::NewId,
             ],
-            since: 1u32,
-            is_destructor: false,
-            child_interface: None,
-            arg_interfaces: &[],
-        }],
+            1u32,
+            false,
+            None,
+            &[],
+        )],
         events: &[
-            wayland_backend::protocol::MessageDesc {
-                name: "global",
-                signature: &[
+            wayland_backend::protocol::MessageDesc::new(
+                "global",
+                &[
                     wayland_backend::protocol::ArgumentType::Uint,
                     wayland_backend::protocol::ArgumentType::Str(
                         wayland_backend::protocol::AllowNull::No,
                     ),
                     wayland_backend::protocol::ArgumentType::Uint,
                 ],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "global_remove",
-                signature: &[wayland_backend::protocol::ArgumentType::Uint],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[],
-            },
+                1u32,
+                false,
+                None,
+                &[],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "global_remove",
+                &[wayland_backend::protocol::ArgumentType::Uint],
+                1u32,
+                false,
+                None,
+                &[],
+            ),
         ],
         c_ptr: Some(unsafe { &wl_registry_interface }),
     };
@@ -170,14 +170,14 @@ pub static WL_CALLBACK_INTERFACE: wayland_backend::protocol::Interface =
         name: "wl_callback",
         version: 1u32,
         requests: &[],
-        events: &[wayland_backend::protocol::MessageDesc {
-            name: "done",
-            signature: &[wayland_backend::protocol::ArgumentType::Uint],
-            since: 1u32,
-            is_destructor: true,
-            child_interface: None,
-            arg_interfaces: &[],
-        }],
+        events: &[wayland_backend::protocol::MessageDesc::new(
+            "done",
+            &[wayland_backend::protocol::ArgumentType::Uint],
+            1u32,
+            true,
+            None,
+            &[],
+        )],
         c_ptr: Some(unsafe { &wl_callback_interface }),
     };
 static wl_callback_events: SyncWrapper<[wayland_backend::protocol::wl_message; 1]> =
@@ -200,9 +200,9 @@ pub static TEST_GLOBAL_INTERFACE: wayland_backend::protocol::Interface =
         name: "test_global",
         version: 5u32,
         requests: &[
-            wayland_backend::protocol::MessageDesc {
-                name: "many_args",
-                signature: &[
+            wayland_backend::protocol::MessageDesc::new(
+                "many_args",
+                &[
                     wayland_backend::protocol::ArgumentType::Uint,
                     wayland_backend::protocol::ArgumentType::Int,
                     wayland_backend::protocol::ArgumentType::Fixed,
@@ -212,30 +212,30 @@ pub static TEST_GLOBAL_INTERFACE: wayland_backend::protocol::Interface =
                     ),
                     wayland_backend::protocol::ArgumentType::Fd,
                 ],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "get_secondary",
-                signature: &[wayland_backend::protocol::ArgumentType::NewId],
-                since: 2u32,
-                is_destructor: false,
-                child_interface: Some(&SECONDARY_INTERFACE),
-                arg_interfaces: &[],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "get_tertiary",
-                signature: &[wayland_backend::protocol::ArgumentType::NewId],
-                since: 3u32,
-                is_destructor: false,
-                child_interface: Some(&TERTIARY_INTERFACE),
-                arg_interfaces: &[],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "link",
-                signature: &[
+                1u32,
+                false,
+                None,
+                &[],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "get_secondary",
+                &[wayland_backend::protocol::ArgumentType::NewId],
+                2u32,
+                false,
+                Some(&SECONDARY_INTERFACE),
+                &[],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "get_tertiary",
+                &[wayland_backend::protocol::ArgumentType::NewId],
+                3u32,
+                false,
+                Some(&TERTIARY_INTERFACE),
+                &[],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "link",
+                &[
                     wayland_backend::protocol::ArgumentType::Object(
                         wayland_backend::protocol::AllowNull::No,
                     ),
@@ -244,22 +244,16 @@ pub static TEST_GLOBAL_INTERFACE: wayland_backend::protocol::Interface =
                     ),
                     wayland_backend::protocol::ArgumentType::Uint,
                 ],
-                since: 3u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[&SECONDARY_INTERFACE, &TERTIARY_INTERFACE],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "destroy",
-                signature: &[],
-                since: 4u32,
-                is_destructor: true,
-                child_interface: None,
-                arg_interfaces: &[],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "reverse_link",
-                signature: &[
+                3u32,
+                false,
+                None,
+                &[&SECONDARY_INTERFACE, &TERTIARY_INTERFACE],
+            )
+            .deprecated_since(5u32),
+            wayland_backend::protocol::MessageDesc::new("destroy", &[], 4u32, true, None, &[]),
+            wayland_backend::protocol::MessageDesc::new(
+                "reverse_link",
+                &[
                     wayland_backend::protocol::ArgumentType::Object(
                         wayland_backend::protocol::AllowNull::Yes,
                     ),
@@ -267,14 +261,14 @@ pub static TEST_GLOBAL_INTERFACE: wayland_backend::protocol::Interface =
                         wayland_backend::protocol::AllowNull::No,
                     ),
                 ],
-                since: 5u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[&SECONDARY_INTERFACE, &TERTIARY_INTERFACE],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "newid_and_allow_null",
-                signature: &[
+                5u32,
+                false,
+                None,
+                &[&SECONDARY_INTERFACE, &TERTIARY_INTERFACE],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "newid_and_allow_null",
+                &[
                     wayland_backend::protocol::ArgumentType::NewId,
                     wayland_backend::protocol::ArgumentType::Object(
                         wayland_backend::protocol::AllowNull::Yes,
@@ -283,16 +277,16 @@ pub static TEST_GLOBAL_INTERFACE: wayland_backend::protocol::Interface =
                         wayland_backend::protocol::AllowNull::No,
                     ),
                 ],
-                since: 5u32,
-                is_destructor: false,
-                child_interface: Some(&QUAD_INTERFACE),
-                arg_interfaces: &[&SECONDARY_INTERFACE, &TERTIARY_INTERFACE],
-            },
+                5u32,
+                false,
+                Some(&QUAD_INTERFACE),
+                &[&SECONDARY_INTERFACE, &TERTIARY_INTERFACE],
+            ),
         ],
         events: &[
-            wayland_backend::protocol::MessageDesc {
-                name: "many_args_evt",
-                signature: &[
+            wayland_backend::protocol::MessageDesc::new(
+                "many_args_evt",
+                &[
                     wayland_backend::protocol::ArgumentType::Uint,
                     wayland_backend::protocol::ArgumentType::Int,
                     wayland_backend::protocol::ArgumentType::Fixed,
@@ -302,34 +296,34 @@ pub static TEST_GLOBAL_INTERFACE: wayland_backend::protocol::Interface =
                     ),
                     wayland_backend::protocol::ArgumentType::Fd,
                 ],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "ack_secondary",
-                signature: &[wayland_backend::protocol::ArgumentType::Object(
+                1u32,
+                false,
+                None,
+                &[],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "ack_secondary",
+                &[wayland_backend::protocol::ArgumentType::Object(
                     wayland_backend::protocol::AllowNull::No,
                 )],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: None,
-                arg_interfaces: &[&SECONDARY_INTERFACE],
-            },
-            wayland_backend::protocol::MessageDesc {
-                name: "cycle_quad",
-                signature: &[
+                1u32,
+                false,
+                None,
+                &[&SECONDARY_INTERFACE],
+            ),
+            wayland_backend::protocol::MessageDesc::new(
+                "cycle_quad",
+                &[
                     wayland_backend::protocol::ArgumentType::NewId,
                     wayland_backend::protocol::ArgumentType::Object(
                         wayland_backend::protocol::AllowNull::Yes,
                     ),
                 ],
-                since: 1u32,
-                is_destructor: false,
-                child_interface: Some(&QUAD_INTERFACE),
-                arg_interfaces: &[&QUAD_INTERFACE],
-            },
+                1u32,
+                false,
+                Some(&QUAD_INTERFACE),
+                &[&QUAD_INTERFACE],
+            ),
         ],
         c_ptr: Some(unsafe { &test_global_interface }),
     };
@@ -436,14 +430,14 @@ pub static SECONDARY_INTERFACE: wayland_backend::protocol::Interface =
     wayland_backend::protocol::Interface {
         name: "secondary",
         version: 5u32,
-        requests: &[wayland_backend::protocol::MessageDesc {
-            name: "destroy",
-            signature: &[],
-            since: 2u32,
-            is_destructor: true,
-            child_interface: None,
-            arg_interfaces: &[],
-        }],
+        requests: &[wayland_backend::protocol::MessageDesc::new(
+            "destroy",
+            &[],
+            2u32,
+            true,
+            None,
+            &[],
+        )],
         events: &[],
         c_ptr: Some(unsafe { &secondary_interface }),
     };
@@ -466,14 +460,14 @@ pub static TERTIARY_INTERFACE: wayland_backend::protocol::Interface =
     wayland_backend::protocol::Interface {
         name: "tertiary",
         version: 5u32,
-        requests: &[wayland_backend::protocol::MessageDesc {
-            name: "destroy",
-            signature: &[],
-            since: 3u32,
-            is_destructor: true,
-            child_interface: None,
-            arg_interfaces: &[],
-        }],
+        requests: &[wayland_backend::protocol::MessageDesc::new(
+            "destroy",
+            &[],
+            3u32,
+            true,
+            None,
+            &[],
+        )],
         events: &[],
         c_ptr: Some(unsafe { &tertiary_interface }),
     };
@@ -496,14 +490,14 @@ pub static QUAD_INTERFACE: wayland_backend::protocol::Interface =
     wayland_backend::protocol::Interface {
         name: "quad",
         version: 5u32,
-        requests: &[wayland_backend::protocol::MessageDesc {
-            name: "destroy",
-            signature: &[],
-            since: 3u32,
-            is_destructor: true,
-            child_interface: None,
-            arg_interfaces: &[],
-        }],
+        requests: &[wayland_backend::protocol::MessageDesc::new(
+            "destroy",
+            &[],
+            3u32,
+            true,
+            None,
+            &[],
+        )],
         events: &[],
         c_ptr: Some(unsafe { &quad_interface }),
     };
@@ -21,6 +21,10 @@ pub mod wl_callback {
         pub fn opcode(&self) -> u16 {
             match *self {}
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {}
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -44,8 +48,15 @@ pub mod wl_callback {
                 Event::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::Done { .. } => 1u32,
+                Event::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
-    #[doc = "callback object\n\nClients can handle the 'done' event to get notified when\nthe related request is done.\n\nThis interface has no requests."]
+    #[doc = "callback object\n\nClients can handle the 'done' event to get notified when\nthe related request is done.\n\nThis interface has no requests.\n\nThis interface is frozen: no new versions of it will be released.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | [`done`](Event::Done) |"]
     #[derive(Debug, Clone)]
     pub struct WlCallback {
         id: ObjectId,
@@ -156,13 +167,77 @@ pub mod wl_callback {
         }
     }
     impl WlCallback {
-        #[doc = "done event\n\nNotify the client when the related request is done."]
+        #[doc = "done event\n\nNotify the client when the related request is done.\n\nThis is a destructor, once sent this object cannot be used any longer."]
         #[allow(clippy::too_many_arguments)]
         pub fn done(&self, callback_data: u32) {
             let _ = self.send_event(Event::Done { callback_data });
         }
     }
+    #[doc = "Handler for the requests of [WlCallback], with one method per request\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [WlCallback], using `delegate_dispatch!(State: [WlCallback: UserData] => WlCallback);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = r" Called when the object has been destroyed, see [`Dispatch::destroyed()`]"]
+        #[allow(unused_variables)]
+        fn destroyed(
+            &mut self,
+            client: super::wayland_server::backend::ClientId,
+            resource: &WlCallback,
+            data: &U,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<WlCallback, U, D> for WlCallback
+    where
+        D: Handler<U>,
+    {
+        fn request(
+            state: &mut D,
+            client: &super::wayland_server::Client,
+            resource: &WlCallback,
+            request: Request,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, D>,
+        ) {
+            match request {}
+        }
+        fn destroyed(
+            state: &mut D,
+            client: super::wayland_server::backend::ClientId,
+            resource: &WlCallback,
+            data: &U,
+        ) {
+            <D as Handler<U>>::destroyed(state, client, resource, data)
+        }
+    }
+    const _: () = {
+        use super::wayland_server::backend::serde;
+        impl serde::Serialize for WlCallback {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl serde::Serialize for Request {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match *self {}
+            }
+        }
+        impl<'a> serde::Serialize for Event<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Event::Done { callback_data } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Event", 0u32, "done", 1)?;
+                        state.serialize_field("callback_data", callback_data)?;
+                        state.end()
+                    }
+                    Event::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+    };
 }
+#[allow(deprecated)]
 pub mod test_global {
     use super::wayland_server::{
         backend::{
@@ -233,24 +308,39 @@ pub mod test_global {
         },
         #[doc = "Only available since version 2 of the interface"]
         GetSecondary {
-            #[doc = "create a secondary"]
+            #[doc = "create a secondary\n\nCreates a new [`Secondary`](super::secondary::Secondary) object, see the [`secondary`](super::secondary) module."]
             sec: New<super::secondary::Secondary>,
         },
         #[doc = "Only available since version 3 of the interface"]
         GetTertiary {
-            #[doc = "create a tertiary"]
+            #[doc = "create a tertiary\n\nCreates a new [`Tertiary`](super::tertiary::Tertiary) object, see the [`tertiary`](super::tertiary) module."]
             ter: New<super::tertiary::Tertiary>,
         },
         #[doc = "link a secondary and a tertiary\n\n\n\nOnly available since version 3 of the interface"]
-        Link { sec: super::secondary::Secondary, ter: Option<super::tertiary::Tertiary>, time: u32 },
+        #[deprecated(note = "Deprecated since version 5 of the interface")]
+        Link {
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
+            sec: super::secondary::Secondary,
+            #[doc = "Object of interface [`Tertiary`](super::tertiary::Tertiary)."]
+            ter: Option<super::tertiary::Tertiary>,
+            time: u32,
+        },
         #[doc = "This is a destructor, once received this object cannot be used any longer.\nOnly available since version 4 of the interface"]
         Destroy,
         #[doc = "reverse link a secondary and a tertiary\n\n\n\nOnly available since version 5 of the interface"]
-        ReverseLink { sec: Option<super::secondary::Secondary>, ter: super::tertiary::Tertiary },
+        ReverseLink {
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
+            sec: Option<super::secondary::Secondary>,
+            #[doc = "Object of interface [`Tertiary`](super::tertiary::Tertiary)."]
+            ter: super::tertiary::Tertiary,
+        },
         #[doc = "a newid request that also takes allow null arg\n\n\n\nOnly available since version 5 of the interface"]
         NewidAndAllowNull {
+            #[doc = "Creates a new [`Quad`](super::quad::Quad) object, see the [`quad`](super::quad) module."]
             quad: New<super::quad::Quad>,
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
             sec: Option<super::secondary::Secondary>,
+            #[doc = "Object of interface [`Tertiary`](super::tertiary::Tertiary)."]
             ter: super::tertiary::Tertiary,
         },
     }
@@ -267,6 +357,18 @@ pub mod test_global {
                 Request::NewidAndAllowNull { .. } => 6u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::ManyArgs { .. } => 1u32,
+                Request::GetSecondary { .. } => 2u32,
+                Request::GetTertiary { .. } => 3u32,
+                Request::Link { .. } => 3u32,
+                Request::Destroy => 4u32,
+                Request::ReverseLink { .. } => 5u32,
+                Request::NewidAndAllowNull { .. } => 5u32,
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -287,9 +389,17 @@ pub mod test_global {
             file_descriptor: std::os::unix::io::BorrowedFd<'a>,
         },
         #[doc = "acking the creation of a secondary"]
-        AckSecondary { sec: super::secondary::Secondary },
+        AckSecondary {
+            #[doc = "Object of interface [`Secondary`](super::secondary::Secondary)."]
+            sec: super::secondary::Secondary,
+        },
         #[doc = "create a new quad optionally replacing a previous one"]
-        CycleQuad { new_quad: super::quad::Quad, old_quad: Option<super::quad::Quad> },
+        CycleQuad {
+            #[doc = "Creates a new [`Quad`](super::quad::Quad) object, see the [`quad`](super::quad) module."]
+            new_quad: super::quad::Quad,
+            #[doc = "Object of interface [`Quad`](super::quad::Quad)."]
+            old_quad: Option<super::quad::Quad>,
+        },
         #[doc(hidden)]
         __phantom_lifetime {
             phantom: std::marker::PhantomData<&'a ()>,
@@ -306,8 +416,17 @@ pub mod test_global {
                 Event::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::ManyArgsEvt { .. } => 1u32,
+                Event::AckSecondary { .. } => 1u32,
+                Event::CycleQuad { .. } => 1u32,
+                Event::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
-    #[doc = "test_global\n\nSee also the [Request] enum for this interface."]
+    #[doc = "test_global\n\nSee also the [Request] enum for this interface.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | [`many_args`](Request::ManyArgs) | [`many_args_evt`](Event::ManyArgsEvt), [`ack_secondary`](Event::AckSecondary), [`cycle_quad`](Event::CycleQuad) |\n| 2 | [`get_secondary`](Request::GetSecondary) | - |\n| 3 | [`get_tertiary`](Request::GetTertiary), [`link`](Request::Link) | - |\n| 4 | [`destroy`](Request::Destroy) | - |\n| 5 | [`reverse_link`](Request::ReverseLink), [`newid_and_allow_null`](Request::NewidAndAllowNull) | - |"]
     #[derive(Debug, Clone)]
     pub struct TestGlobal {
         id: ObjectId,
@@ -767,6 +886,312 @@ pub mod test_global {
             });
         }
     }
+    #[doc = "Handler for the requests of [TestGlobal], with one method per request\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [TestGlobal], using `delegate_dispatch!(State: [TestGlobal: UserData] => TestGlobal);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "a request with every possible non-object arg"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn many_args(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+            unsigned_int: u32,
+            signed_int: i32,
+            fixed_point: f64,
+            number_array: Vec<u8>,
+            some_text: String,
+            file_descriptor: OwnedFd,
+        ) {
+        }
+        #[doc = "get_secondary"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn get_secondary(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+            sec: New<super::secondary::Secondary>,
+        ) {
+        }
+        #[doc = "get_tertiary"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn get_tertiary(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+            ter: New<super::tertiary::Tertiary>,
+        ) {
+        }
+        #[doc = "link a secondary and a tertiary"]
+        #[deprecated(note = "Deprecated since version 5 of the interface")]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn link(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+            sec: super::secondary::Secondary,
+            ter: Option<super::tertiary::Tertiary>,
+            time: u32,
+        ) {
+        }
+        #[doc = "destroy"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn destroy(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+        ) {
+        }
+        #[doc = "reverse link a secondary and a tertiary"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn reverse_link(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+            sec: Option<super::secondary::Secondary>,
+            ter: super::tertiary::Tertiary,
+        ) {
+        }
+        #[doc = "a newid request that also takes allow null arg"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn newid_and_allow_null(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+            quad: New<super::quad::Quad>,
+            sec: Option<super::secondary::Secondary>,
+            ter: super::tertiary::Tertiary,
+        ) {
+        }
+        #[doc = r" Called when the object has been destroyed, see [`Dispatch::destroyed()`]"]
+        #[allow(unused_variables)]
+        fn destroyed(
+            &mut self,
+            client: super::wayland_server::backend::ClientId,
+            resource: &TestGlobal,
+            data: &U,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<TestGlobal, U, D> for TestGlobal
+    where
+        D: Handler<U>,
+    {
+        fn request(
+            state: &mut D,
+            client: &super::wayland_server::Client,
+            resource: &TestGlobal,
+            request: Request,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, D>,
+        ) {
+            match request {
+                Request::ManyArgs {
+                    unsigned_int,
+                    signed_int,
+                    fixed_point,
+                    number_array,
+                    some_text,
+                    file_descriptor,
+                } => <D as Handler<U>>::many_args(
+                    state,
+                    client,
+                    resource,
+                    data,
+                    dhandle,
+                    data_init,
+                    unsigned_int,
+                    signed_int,
+                    fixed_point,
+                    number_array,
+                    some_text,
+                    file_descriptor,
+                ),
+                Request::GetSecondary { sec } => <D as Handler<U>>::get_secondary(
+                    state, client, resource, data, dhandle, data_init, sec,
+                ),
+                Request::GetTertiary { ter } => <D as Handler<U>>::get_tertiary(
+                    state, client, resource, data, dhandle, data_init, ter,
+                ),
+                Request::Link { sec, ter, time } => <D as Handler<U>>::link(
+                    state, client, resource, data, dhandle, data_init, sec, ter, time,
+                ),
+                Request::Destroy => {
+                    <D as Handler<U>>::destroy(state, client, resource, data, dhandle, data_init)
+                }
+                Request::ReverseLink { sec, ter } => <D as Handler<U>>::reverse_link(
+                    state, client, resource, data, dhandle, data_init, sec, ter,
+                ),
+                Request::NewidAndAllowNull { quad, sec, ter } => {
+                    <D as Handler<U>>::newid_and_allow_null(
+                        state, client, resource, data, dhandle, data_init, quad, sec, ter,
+                    )
+                }
+            }
+        }
+        fn destroyed(
+            state: &mut D,
+            client: super::wayland_server::backend::ClientId,
+            resource: &TestGlobal,
+            data: &U,
+        ) {
+            <D as Handler<U>>::destroyed(state, client, resource, data)
+        }
+    }
+    const _: () = {
+        use super::wayland_server::backend::serde;
+        impl serde::Serialize for TestGlobal {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl serde::Serialize for Request {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Request::ManyArgs {
+                        unsigned_int,
+                        signed_int,
+                        fixed_point,
+                        number_array,
+                        some_text,
+                        ..
+                    } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Request", 0u32, "many_args", 6)?;
+                        state.serialize_field("unsigned_int", unsigned_int)?;
+                        state.serialize_field("signed_int", signed_int)?;
+                        state.serialize_field("fixed_point", fixed_point)?;
+                        state.serialize_field("number_array", number_array)?;
+                        state.serialize_field("some_text", some_text)?;
+                        state.serialize_field("file_descriptor", "<fd>")?;
+                        state.end()
+                    }
+                    Request::GetSecondary { sec } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Request",
+                            1u32,
+                            "get_secondary",
+                            1,
+                        )?;
+                        state.serialize_field("sec", sec)?;
+                        state.end()
+                    }
+                    Request::GetTertiary { ter } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Request",
+                            2u32,
+                            "get_tertiary",
+                            1,
+                        )?;
+                        state.serialize_field("ter", ter)?;
+                        state.end()
+                    }
+                    Request::Link { sec, ter, time } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Request", 3u32, "link", 3)?;
+                        state.serialize_field("sec", sec)?;
+                        state.serialize_field("ter", ter)?;
+                        state.serialize_field("time", time)?;
+                        state.end()
+                    }
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 4u32, "destroy")
+                    }
+                    Request::ReverseLink { sec, ter } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Request",
+                            5u32,
+                            "reverse_link",
+                            2,
+                        )?;
+                        state.serialize_field("sec", sec)?;
+                        state.serialize_field("ter", ter)?;
+                        state.end()
+                    }
+                    Request::NewidAndAllowNull { quad, sec, ter } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Request",
+                            6u32,
+                            "newid_and_allow_null",
+                            3,
+                        )?;
+                        state.serialize_field("quad", quad)?;
+                        state.serialize_field("sec", sec)?;
+                        state.serialize_field("ter", ter)?;
+                        state.end()
+                    }
+                }
+            }
+        }
+        impl<'a> serde::Serialize for Event<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStructVariant;
+                match self {
+                    Event::ManyArgsEvt {
+                        unsigned_int,
+                        signed_int,
+                        fixed_point,
+                        number_array,
+                        some_text,
+                        ..
+                    } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Event",
+                            0u32,
+                            "many_args_evt",
+                            6,
+                        )?;
+                        state.serialize_field("unsigned_int", unsigned_int)?;
+                        state.serialize_field("signed_int", signed_int)?;
+                        state.serialize_field("fixed_point", fixed_point)?;
+                        state.serialize_field("number_array", number_array)?;
+                        state.serialize_field("some_text", some_text)?;
+                        state.serialize_field("file_descriptor", "<fd>")?;
+                        state.end()
+                    }
+                    Event::AckSecondary { sec } => {
+                        let mut state = serializer.serialize_struct_variant(
+                            "Event",
+                            1u32,
+                            "ack_secondary",
+                            1,
+                        )?;
+                        state.serialize_field("sec", sec)?;
+                        state.end()
+                    }
+                    Event::CycleQuad { new_quad, old_quad } => {
+                        let mut state =
+                            serializer.serialize_struct_variant("Event", 2u32, "cycle_quad", 2)?;
+                        state.serialize_field("new_quad", new_quad)?;
+                        state.serialize_field("old_quad", old_quad)?;
+                        state.end()
+                    }
+                    Event::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+    };
 }
 pub mod secondary {
     use super::wayland_server::{
@@ -795,6 +1220,12 @@ pub mod secondary {
                 Request::Destroy => 0u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Destroy => 2u32,
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -812,8 +1243,14 @@ pub mod secondary {
                 Event::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
-    #[doc = "secondary\n\nSee also the [Request] enum for this interface."]
+    #[doc = "secondary\n\nSee also the [Request] enum for this interface.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | - |\n| 2 | [`destroy`](Request::Destroy) | - |"]
     #[derive(Debug, Clone)]
     pub struct Secondary {
         id: ObjectId,
@@ -926,6 +1363,81 @@ pub mod secondary {
         }
     }
     impl Secondary {}
+    #[doc = "Handler for the requests of [Secondary], with one method per request\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [Secondary], using `delegate_dispatch!(State: [Secondary: UserData] => Secondary);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "destroy"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn destroy(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &Secondary,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+        ) {
+        }
+        #[doc = r" Called when the object has been destroyed, see [`Dispatch::destroyed()`]"]
+        #[allow(unused_variables)]
+        fn destroyed(
+            &mut self,
+            client: super::wayland_server::backend::ClientId,
+            resource: &Secondary,
+            data: &U,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<Secondary, U, D> for Secondary
+    where
+        D: Handler<U>,
+    {
+        fn request(
+            state: &mut D,
+            client: &super::wayland_server::Client,
+            resource: &Secondary,
+            request: Request,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, D>,
+        ) {
+            match request {
+                Request::Destroy => {
+                    <D as Handler<U>>::destroy(state, client, resource, data, dhandle, data_init)
+                }
+            }
+        }
+        fn destroyed(
+            state: &mut D,
+            client: super::wayland_server::backend::ClientId,
+            resource: &Secondary,
+            data: &U,
+        ) {
+            <D as Handler<U>>::destroyed(state, client, resource, data)
+        }
+    }
+    const _: () = {
+        use super::wayland_server::backend::serde;
+        impl serde::Serialize for Secondary {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl serde::Serialize for Request {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 0u32, "destroy")
+                    }
+                }
+            }
+        }
+        impl<'a> serde::Serialize for Event<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Event::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+    };
 }
 pub mod tertiary {
     use super::wayland_server::{
@@ -954,6 +1466,12 @@ pub mod tertiary {
                 Request::Destroy => 0u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Destroy => 3u32,
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -971,8 +1489,14 @@ pub mod tertiary {
                 Event::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
-    #[doc = "tertiary\n\nSee also the [Request] enum for this interface."]
+    #[doc = "tertiary\n\nSee also the [Request] enum for this interface.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | - |\n| 3 | [`destroy`](Request::Destroy) | - |"]
     #[derive(Debug, Clone)]
     pub struct Tertiary {
         id: ObjectId,
@@ -1085,6 +1609,81 @@ pub mod tertiary {
         }
     }
     impl Tertiary {}
+    #[doc = "Handler for the requests of [Tertiary], with one method per request\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [Tertiary], using `delegate_dispatch!(State: [Tertiary: UserData] => Tertiary);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "destroy"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn destroy(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &Tertiary,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+        ) {
+        }
+        #[doc = r" Called when the object has been destroyed, see [`Dispatch::destroyed()`]"]
+        #[allow(unused_variables)]
+        fn destroyed(
+            &mut self,
+            client: super::wayland_server::backend::ClientId,
+            resource: &Tertiary,
+            data: &U,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<Tertiary, U, D> for Tertiary
+    where
+        D: Handler<U>,
+    {
+        fn request(
+            state: &mut D,
+            client: &super::wayland_server::Client,
+            resource: &Tertiary,
+            request: Request,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, D>,
+        ) {
+            match request {
+                Request::Destroy => {
+                    <D as Handler<U>>::destroy(state, client, resource, data, dhandle, data_init)
+                }
+            }
+        }
+        fn destroyed(
+            state: &mut D,
+            client: super::wayland_server::backend::ClientId,
+            resource: &Tertiary,
+            data: &U,
+        ) {
+            <D as Handler<U>>::destroyed(state, client, resource, data)
+        }
+    }
+    const _: () = {
+        use super::wayland_server::backend::serde;
+        impl serde::Serialize for Tertiary {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl serde::Serialize for Request {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 0u32, "destroy")
+                    }
+                }
+            }
+        }
+        impl<'a> serde::Serialize for Event<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Event::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+    };
 }
 pub mod quad {
     use super::wayland_server::{
@@ -1113,6 +1712,12 @@ pub mod quad {
                 Request::Destroy => 0u16,
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Request::Destroy => 3u32,
+            }
+        }
     }
     #[derive(Debug)]
     #[non_exhaustive]
@@ -1130,8 +1735,14 @@ pub mod quad {
                 Event::__phantom_lifetime { never, .. } => match never {},
             }
         }
+        #[doc = "Get the minimal version of the interface supporting this message"]
+        pub fn since(&self) -> u32 {
+            match *self {
+                Event::__phantom_lifetime { never, .. } => match never {},
+            }
+        }
     }
-    #[doc = "quad\n\nSee also the [Request] enum for this interface."]
+    #[doc = "quad\n\nSee also the [Request] enum for this interface.\n\n# Versions\n\n| Version | Requests | Events |\n|---|---|---|\n| 1 | - | - |\n| 3 | [`destroy`](Request::Destroy) | - |"]
     #[derive(Debug, Clone)]
     pub struct Quad {
         id: ObjectId,
@@ -1244,4 +1855,368 @@ pub mod quad {
         }
     }
     impl Quad {}
+    #[doc = "Handler for the requests of [Quad], with one method per request\n\nAll methods do nothing by default. To use it, implement it for your state and delegate the\ndispatching of this interface to [Quad], using `delegate_dispatch!(State: [Quad: UserData] => Quad);`."]
+    pub trait Handler<U>: Sized {
+        #[doc = "destroy"]
+        #[allow(clippy::too_many_arguments, unused_variables)]
+        fn destroy(
+            &mut self,
+            client: &super::wayland_server::Client,
+            resource: &Quad,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, Self>,
+        ) {
+        }
+        #[doc = r" Called when the object has been destroyed, see [`Dispatch::destroyed()`]"]
+        #[allow(unused_variables)]
+        fn destroyed(
+            &mut self,
+            client: super::wayland_server::backend::ClientId,
+            resource: &Quad,
+            data: &U,
+        ) {
+        }
+    }
+    impl<U, D> Dispatch<Quad, U, D> for Quad
+    where
+        D: Handler<U>,
+    {
+        fn request(
+            state: &mut D,
+            client: &super::wayland_server::Client,
+            resource: &Quad,
+            request: Request,
+            data: &U,
+            dhandle: &DisplayHandle,
+            data_init: &mut super::wayland_server::DataInit<'_, D>,
+        ) {
+            match request {
+                Request::Destroy => {
+                    <D as Handler<U>>::destroy(state, client, resource, data, dhandle, data_init)
+                }
+            }
+        }
+        fn destroyed(
+            state: &mut D,
+            client: super::wayland_server::backend::ClientId,
+            resource: &Quad,
+            data: &U,
+        ) {
+            <D as Handler<U>>::destroyed(state, client, resource, data)
+        }
+    }
+    const _: () = {
+        use super::wayland_server::backend::serde;
+        impl serde::Serialize for Quad {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.id, serializer)
+            }
+        }
+        impl serde::Serialize for Request {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Request::Destroy => {
+                        serializer.serialize_unit_variant("Request", 0u32, "destroy")
+                    }
+                }
+            }
+        }
+        impl<'a> serde::Serialize for Event<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    Event::__phantom_lifetime { never, .. } => match *never {},
+                }
+            }
+        }
+    };
+}
+#[doc = r" Recording implementations of the interfaces of this protocol, for tests"]
+#[doc = r""]
+#[doc = r" [`MockState`] can be used as the state of a `Display` to implement a test server without"]
+#[doc = r" writing the `Dispatch` and `GlobalDispatch` implementations of the interfaces it uses: it"]
+#[doc = r" initializes all the objects it receives with a `()` user data, and records their requests."]
+#[doc = r" Responses can be scripted with [`MockState::on_request()`]."]
+#[allow(deprecated)]
+pub mod mock {
+    use super::wayland_server::{
+        backend::{ClientId, ObjectId},
+        Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+    };
+    #[doc = r" A request received by a [`MockState`]"]
+    #[derive(Debug)]
+    #[non_exhaustive]
+    pub enum MockRequest {
+        #[doc = "A request of [`WlCallback`](super::wl_callback::WlCallback)"]
+        WlCallback(super::wl_callback::Request),
+        #[doc = "A request of [`TestGlobal`](super::test_global::TestGlobal)"]
+        TestGlobal(super::test_global::Request),
+        #[doc = "A request of [`Secondary`](super::secondary::Secondary)"]
+        Secondary(super::secondary::Request),
+        #[doc = "A request of [`Tertiary`](super::tertiary::Tertiary)"]
+        Tertiary(super::tertiary::Request),
+        #[doc = "A request of [`Quad`](super::quad::Quad)"]
+        Quad(super::quad::Request),
+    }
+    #[doc = r" A request recorded by a [`MockState`]"]
+    #[derive(Debug)]
+    pub struct Recorded {
+        #[doc = r" The client which sent the request"]
+        pub client: ClientId,
+        #[doc = r" The object which received the request"]
+        pub resource: ObjectId,
+        #[doc = r" The request"]
+        #[doc = r""]
+        #[doc = r" The objects it creates have already been initialized, they are also listed in"]
+        #[doc = r" [`created`](Recorded::created)."]
+        pub request: MockRequest,
+        #[doc = r" The objects created by the request"]
+        pub created: Vec<ObjectId>,
+    }
+    type Responder = Box<dyn FnMut(&DisplayHandle, &Recorded)>;
+    #[doc = r" A server state recording the requests it receives"]
+    #[doc = r""]
+    #[doc = r" Create the globals of the protocol with it as the state, like"]
+    #[doc = r" `display.handle().create_global::<MockState, WlFoo, ()>(1, ())`."]
+    #[derive(Default)]
+    pub struct MockState {
+        #[doc = r" The recorded requests, in the order they were received"]
+        pub log: Vec<Recorded>,
+        #[doc = r" The objects created by binding globals, in the order they were created"]
+        pub bound: Vec<ObjectId>,
+        responders: Vec<Responder>,
+    }
+    impl MockState {
+        #[doc = r" Create a new mock state with no recorded requests"]
+        pub fn new() -> MockState {
+            MockState::default()
+        }
+        #[doc = r" Script the responses to requests"]
+        #[doc = r""]
+        #[doc = r" The responder is called for every request, once its new objects have been initialized"]
+        #[doc = r" and before it is added to the log. It can send events to the objects of the request,"]
+        #[doc = r" which can be retrieved using `Resource::from_id()`."]
+        pub fn on_request(&mut self, responder: impl FnMut(&DisplayHandle, &Recorded) + 'static) {
+            self.responders.push(Box::new(responder));
+        }
+        #[doc = r" Take the recorded requests, leaving the log empty"]
+        pub fn take_log(&mut self) -> Vec<Recorded> {
+            std::mem::take(&mut self.log)
+        }
+        fn record(&mut self, dhandle: &DisplayHandle, recorded: Recorded) {
+            for responder in &mut self.responders {
+                responder(dhandle, &recorded);
+            }
+            self.log.push(recorded);
+        }
+    }
+    impl std::fmt::Debug for MockState {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("MockState")
+                .field("log", &self.log)
+                .field("bound", &self.bound)
+                .finish_non_exhaustive()
+        }
+    }
+    impl GlobalDispatch<super::wl_callback::WlCallback, ()> for MockState {
+        fn bind(
+            state: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            resource: New<super::wl_callback::WlCallback>,
+            _: &(),
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let resource = data_init.init(resource, ());
+            state.bound.push(Resource::id(&resource));
+        }
+    }
+    impl Dispatch<super::wl_callback::WlCallback, ()> for MockState {
+        #[allow(unused_variables, unused_mut)]
+        fn request(
+            state: &mut Self,
+            client: &Client,
+            resource: &super::wl_callback::WlCallback,
+            request: super::wl_callback::Request,
+            _: &(),
+            dhandle: &DisplayHandle,
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            match request {}
+        }
+    }
+    impl GlobalDispatch<super::test_global::TestGlobal, ()> for MockState {
+        fn bind(
+            state: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            resource: New<super::test_global::TestGlobal>,
+            _: &(),
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let resource = data_init.init(resource, ());
+            state.bound.push(Resource::id(&resource));
+        }
+    }
+    impl Dispatch<super::test_global::TestGlobal, ()> for MockState {
+        #[allow(unused_variables, unused_mut)]
+        fn request(
+            state: &mut Self,
+            client: &Client,
+            resource: &super::test_global::TestGlobal,
+            request: super::test_global::Request,
+            _: &(),
+            dhandle: &DisplayHandle,
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let mut created = Vec::new();
+            let request = match request {
+                super::test_global::Request::GetSecondary { sec } => {
+                    let sec = data_init.init(sec, ());
+                    created.push(Resource::id(&sec));
+                    let sec = New::wrap(sec);
+                    super::test_global::Request::GetSecondary { sec }
+                }
+                super::test_global::Request::GetTertiary { ter } => {
+                    let ter = data_init.init(ter, ());
+                    created.push(Resource::id(&ter));
+                    let ter = New::wrap(ter);
+                    super::test_global::Request::GetTertiary { ter }
+                }
+                super::test_global::Request::NewidAndAllowNull { quad, sec, ter } => {
+                    let quad = data_init.init(quad, ());
+                    created.push(Resource::id(&quad));
+                    let quad = New::wrap(quad);
+                    super::test_global::Request::NewidAndAllowNull { quad, sec, ter }
+                }
+                request => request,
+            };
+            state.record(
+                dhandle,
+                Recorded {
+                    client: client.id(),
+                    resource: Resource::id(resource),
+                    request: MockRequest::TestGlobal(request),
+                    created,
+                },
+            );
+        }
+    }
+    impl GlobalDispatch<super::secondary::Secondary, ()> for MockState {
+        fn bind(
+            state: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            resource: New<super::secondary::Secondary>,
+            _: &(),
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let resource = data_init.init(resource, ());
+            state.bound.push(Resource::id(&resource));
+        }
+    }
+    impl Dispatch<super::secondary::Secondary, ()> for MockState {
+        #[allow(unused_variables, unused_mut)]
+        fn request(
+            state: &mut Self,
+            client: &Client,
+            resource: &super::secondary::Secondary,
+            request: super::secondary::Request,
+            _: &(),
+            dhandle: &DisplayHandle,
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let mut created = Vec::new();
+            let request = match request {
+                request => request,
+            };
+            state.record(
+                dhandle,
+                Recorded {
+                    client: client.id(),
+                    resource: Resource::id(resource),
+                    request: MockRequest::Secondary(request),
+                    created,
+                },
+            );
+        }
+    }
+    impl GlobalDispatch<super::tertiary::Tertiary, ()> for MockState {
+        fn bind(
+            state: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            resource: New<super::tertiary::Tertiary>,
+            _: &(),
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let resource = data_init.init(resource, ());
+            state.bound.push(Resource::id(&resource));
+        }
+    }
+    impl Dispatch<super::tertiary::Tertiary, ()> for MockState {
+        #[allow(unused_variables, unused_mut)]
+        fn request(
+            state: &mut Self,
+            client: &Client,
+            resource: &super::tertiary::Tertiary,
+            request: super::tertiary::Request,
+            _: &(),
+            dhandle: &DisplayHandle,
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let mut created = Vec::new();
+            let request = match request {
+                request => request,
+            };
+            state.record(
+                dhandle,
+                Recorded {
+                    client: client.id(),
+                    resource: Resource::id(resource),
+                    request: MockRequest::Tertiary(request),
+                    created,
+                },
+            );
+        }
+    }
+    impl GlobalDispatch<super::quad::Quad, ()> for MockState {
+        fn bind(
+            state: &mut Self,
+            _: &DisplayHandle,
+            _: &Client,
+            resource: New<super::quad::Quad>,
+            _: &(),
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let resource = data_init.init(resource, ());
+            state.bound.push(Resource::id(&resource));
+        }
+    }
+    impl Dispatch<super::quad::Quad, ()> for MockState {
+        #[allow(unused_variables, unused_mut)]
+        fn request(
+            state: &mut Self,
+            client: &Client,
+            resource: &super::quad::Quad,
+            request: super::quad::Request,
+            _: &(),
+            dhandle: &DisplayHandle,
+            data_init: &mut DataInit<'_, Self>,
+        ) {
+            let mut created = Vec::new();
+            let request = match request {
+                request => request,
+            };
+            state.record(
+                dhandle,
+                Recorded {
+                    client: client.id(),
+                    resource: Resource::id(resource),
+                    request: MockRequest::Quad(request),
+                    created,
+                },
+            );
+        }
+    }
 }
@@ -0,0 +1,223 @@
+//! Animation of cursors driven by frame callbacks
+
+use std::os::unix::io::OwnedFd;
+use std::sync::{Arc, Mutex};
+
+use wayland_client::backend::protocol::{Argument, Message};
+use wayland_client::backend::{Backend, ObjectData, ObjectId};
+use wayland_client::protocol::wl_compositor::{self, WlCompositor};
+use wayland_client::protocol::wl_pointer::WlPointer;
+use wayland_client::protocol::wl_surface::{self, WlSurface};
+use wayland_client::{Connection, Proxy};
+
+use crate::{Cursor, CursorError, IgnoreObjectData, ScaledCursor};
+
+/// Displays a cursor on a surface, animating it if it has several images
+///
+/// The animator owns the cursor surface. Call [`enter()`](CursorAnimator::enter) when receiving a
+/// `wl_pointer.enter` event to display the cursor, and [`stop()`](CursorAnimator::stop) when the pointer
+/// leaves your surfaces or is hidden, so that the animation does not wake up the process needlessly.
+///
+/// The frames are advanced from the `wl_surface.frame` callbacks of the cursor surface, which are
+/// processed while dispatching the events of its [`Connection`], like the other events of your app.
+#[derive(Debug)]
+pub struct CursorAnimator {
+    conn: Connection,
+    surface: WlSurface,
+    state: Arc<Mutex<AnimationState>>,
+}
+
+#[derive(Debug)]
+struct AnimationState {
+    cursor: Option<Cursor>,
+    /// The scale of the images of the cursor, see [`ScaledCursor::scale()`]
+    scale: f64,
+    running: bool,
+    /// Incremented to invalidate the pending frame callback
+    generation: u64,
+    /// Timestamp of the first frame callback of the animation
+    start: Option<u32>,
+    frame: usize,
+}
+
+impl CursorAnimator {
+    /// Create a new animator, with its cursor surface
+    pub fn new(conn: &Connection, compositor: &WlCompositor) -> Result<Self, CursorError> {
+        let surface_id = conn.send_request(
+            compositor,
+            wl_compositor::Request::CreateSurface {},
+            Some(Arc::new(IgnoreObjectData)),
+        )?;
+        let surface = WlSurface::from_id(conn, surface_id)?;
+        let state = AnimationState {
+            cursor: None,
+            scale: 1.,
+            running: false,
+            generation: 0,
+            start: None,
+            frame: 0,
+        };
+        Ok(Self { conn: conn.clone(), surface, state: Arc::new(Mutex::new(state)) })
+    }
+
+    /// The cursor surface
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+
+    /// Set the cursor to display
+    ///
+    /// This is either a [`Cursor`] displayed at the size of its images, or a [`ScaledCursor`] returned by
+    /// [`CursorTheme::get_cursor_for_scale()`](crate::CursorTheme::get_cursor_for_scale), displayed with its
+    /// [`buffer_scale()`](ScaledCursor::buffer_scale), which requires version 3 of `wl_compositor`. If it
+    /// has no buffer scale, set the destination of a `wp_viewport` of the [`surface()`](Self::surface) to
+    /// its [`logical_size()`](ScaledCursor::logical_size) instead.
+    ///
+    /// If the animation is running, it restarts from the first image of the new cursor. Otherwise the
+    /// cursor is displayed on the next call to [`enter()`](CursorAnimator::enter) or
+    /// [`start()`](CursorAnimator::start).
+    pub fn set_cursor<'a>(&self, cursor: impl Into<ScaledCursor<'a>>) {
+        let cursor = cursor.into();
+        let mut state = self.state.lock().unwrap();
+        state.cursor = Some(cursor.cursor().clone());
+        state.scale = cursor.scale();
+        if state.running {
+            self.restart(&mut state);
+        }
+    }
+
+    /// Display the cursor for a pointer, and start its animation
+    ///
+    /// `serial` is the serial of the `wl_pointer.enter` event. This does nothing if no cursor was set.
+    pub fn enter(&self, pointer: &WlPointer, serial: u32) {
+        let mut state = self.state.lock().unwrap();
+        let (x, y) = match state.cursor {
+            Some(ref cursor) => ScaledCursor { cursor, scale: state.scale }.logical_hotspot(0),
+            None => return,
+        };
+        pointer.set_cursor(serial, Some(&self.surface), x, y);
+        state.running = true;
+        self.restart(&mut state);
+    }
+
+    /// Start or resume the animation of the cursor
+    pub fn start(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !state.running {
+            state.running = true;
+            self.restart(&mut state);
+        }
+    }
+
+    /// Stop the animation of the cursor
+    ///
+    /// The current image stays displayed, but no frame callbacks are requested anymore.
+    pub fn stop(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.running = false;
+        state.generation += 1;
+    }
+
+    /// Whether the animation is running
+    pub fn is_running(&self) -> bool {
+        self.state.lock().unwrap().running
+    }
+
+    /// Display the first image of the cursor, and request a frame callback if it is animated
+    fn restart(&self, state: &mut AnimationState) {
+        state.generation += 1;
+        state.start = None;
+        state.frame = 0;
+        let cursor = match state.cursor {
+            Some(ref cursor) => cursor,
+            None => return,
+        };
+        if self.surface.version() >= 3 {
+            let buffer_scale = ScaledCursor { cursor, scale: state.scale }.buffer_scale(0);
+            self.surface.set_buffer_scale(buffer_scale.unwrap_or(1));
+        }
+        cursor[0].attach_to(&self.surface);
+        self.surface.damage(0, 0, i32::MAX, i32::MAX);
+        if cursor.image_count() > 1 {
+            request_frame(&self.conn, &self.surface, &self.state, state.generation);
+        }
+        self.surface.commit();
+    }
+}
+
+impl Drop for CursorAnimator {
+    fn drop(&mut self) {
+        self.stop();
+        self.surface.destroy();
+    }
+}
+
+fn request_frame(
+    conn: &Connection,
+    surface: &WlSurface,
+    state: &Arc<Mutex<AnimationState>>,
+    generation: u64,
+) {
+    let data = FrameCallbackData { surface: surface.clone(), state: state.clone(), generation };
+    // an error means the connection is dead, the animation stops by itself
+    let _ = conn.send_request(surface, wl_surface::Request::Frame {}, Some(Arc::new(data)));
+}
+
+struct FrameCallbackData {
+    surface: WlSurface,
+    state: Arc<Mutex<AnimationState>>,
+    generation: u64,
+}
+
+impl FrameCallbackData {
+    fn frame_done(&self, conn: &Connection, time: u32) {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        if !state.running || state.generation != self.generation {
+            return;
+        }
+        let cursor = match state.cursor {
+            Some(ref cursor) => cursor,
+            None => return,
+        };
+
+        let start = *state.start.get_or_insert(time);
+        let frame = cursor.frame_and_duration(time.wrapping_sub(start)).frame_index;
+        if frame != state.frame {
+            // keep the hotspot in place if it moves between the images
+            let scaled = ScaledCursor { cursor, scale: state.scale };
+            let (old_x, old_y) = scaled.logical_hotspot(state.frame);
+            let (new_x, new_y) = scaled.logical_hotspot(frame);
+            let (dx, dy) = (old_x - new_x, old_y - new_y);
+            cursor[frame].attach_to(&self.surface);
+            if (dx, dy) != (0, 0) {
+                if self.surface.version() >= 5 {
+                    self.surface.offset(dx, dy);
+                } else {
+                    // replaces the pending attachment with the offset
+                    self.surface.attach(Some(&cursor[frame]), dx, dy);
+                }
+            }
+            self.surface.damage(0, 0, i32::MAX, i32::MAX);
+            state.frame = frame;
+        }
+        request_frame(conn, &self.surface, &self.state, self.generation);
+        self.surface.commit();
+    }
+}
+
+impl ObjectData for FrameCallbackData {
+    fn event(
+        self: Arc<Self>,
+        backend: &Backend,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData>> {
+        // wl_callback.done is the only event of wl_callback
+        if let [Argument::Uint(time)] = msg.args[..] {
+            self.frame_done(&Connection::from_backend(backend.clone()), time);
+        }
+        None
+    }
+
+    fn destroyed(&self, _: ObjectId) {}
+}
@@ -1,5 +1,6 @@
 #![warn(missing_docs, missing_debug_implementations)]
 #![forbid(improper_ctypes, unsafe_op_in_unsafe_fn)]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 //! Wayland cursor utilities
 //!
@@ -12,75 +13,254 @@
 //! From this theme, using the [`get_cursor()`](CursorTheme::get_cursor) method, you can load a
 //! specific [`Cursor`], which can contain several images if the cursor is animated. It also provides
 //! you with the means of querying which frame of the animation should be displayed at what time, as
-//! well as handles to the buffers containing these frames, to attach them to a wayland surface.
+//! well as handles to the buffers containing these frames, to attach them to a wayland surface with
+//! [`CursorImageBuffer::attach_to()`].
+//!
+//! Custom cursors can be created from RGBA images with [`cursor_from_rgba()`](CursorTheme::cursor_from_rgba),
+//! or loaded from XCursor and Windows cursor files with [`cursor_from_file()`](CursorTheme::cursor_from_file).
+//!
+//! The theme, cursor size, search paths and the memory used by the cached cursors can be configured
+//! with a [`CursorThemeConfig`].
+//!
+//! On HiDPI outputs, use [`get_cursor_for_scale()`](CursorTheme::get_cursor_for_scale) instead, which
+//! loads larger images and tells you how to scale them down to the size of the theme.
+//!
+//! The [`CursorAnimator`] displays a cursor on its own surface and drives its animation from the frame
+//! callbacks of this surface. If you prefer to drive the animation yourself, use
+//! [`Cursor::frame_and_duration()`] to know which image to display at a given time.
 //!
 //! # Example
 //!
 //! ```
-//! use wayland_cursor::CursorTheme;
-//! # use std::ops::Deref;
-//! # use std::thread::sleep;
-//! # use std::time::{Instant, Duration};
-//! # fn test(connection: &wayland_client::Connection, cursor_surface: &wayland_client::protocol::wl_surface::WlSurface, shm: wayland_client::protocol::wl_shm::WlShm) {
+//! use wayland_cursor::{CursorAnimator, CursorTheme};
+//! # fn test(
+//! #     connection: &wayland_client::Connection,
+//! #     compositor: &wayland_client::protocol::wl_compositor::WlCompositor,
+//! #     shm: wayland_client::protocol::wl_shm::WlShm,
+//! #     pointer: &wayland_client::protocol::wl_pointer::WlPointer,
+//! #     serial: u32,
+//! # ) {
 //! // Load the default cursor theme.
 //! let mut cursor_theme = CursorTheme::load(&connection, shm, 32)
 //!     .expect("Could not load cursor theme");
 //! let cursor = cursor_theme.get_cursor("wait")
-//!     .expect("Cursor not provided by theme");
+//!     .expect("Could not load cursor");
 //!
-//! let start_time = Instant::now();
-//! loop {
-//!     // Obtain which frame we should show, and for how long.
-//!     let millis = start_time.elapsed().as_millis();
-//!     let fr_info = cursor.frame_and_duration(millis as u32);
+//! // Create the cursor surface and display the cursor on it.
+//! let animator = CursorAnimator::new(&connection, compositor)
+//!     .expect("Could not create the cursor surface");
+//! animator.set_cursor(cursor);
 //!
-//!     // Here, we obtain the right cursor frame...
-//!     let buffer = &cursor[fr_info.frame_index];
-//!     // and attach it to a wl_surface.
-//!     cursor_surface.attach(Some(&buffer), 0, 0);
-//!     cursor_surface.commit();
+//! // When receiving a wl_pointer.enter event, use the cursor surface for the pointer and start the
+//! // animation, which is then driven while dispatching the events of the connection.
+//! animator.enter(pointer, serial);
 //!
-//!     sleep(Duration::from_millis(fr_info.frame_duration as u64));
-//! }
+//! // When receiving a wl_pointer.leave event, stop the animation.
+//! animator.stop();
 //! # }
 //! ```
+//!
+//! # Cursor shape protocol
+//!
+//! With the `cursor-shape` cargo feature, the `CursorManager` sets the cursors of pointers and tablet
+//! tools using the `wp_cursor_shape_v1` protocol when the compositor supports it, and falls back to a
+//! [`CursorTheme`] otherwise.
 
 use std::env;
+use std::fmt;
 use std::fs::File;
-use std::io::{Error as IoError, Read, Result as IoResult, Seek, SeekFrom, Write};
+use std::io::{BufReader, Error as IoError, ErrorKind};
 use std::ops::{Deref, Index};
-use std::os::unix::io::{AsFd, OwnedFd};
+use std::os::unix::io::OwnedFd;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-use rustix::fs::Mode;
-#[cfg(any(target_os = "linux", target_os = "android"))]
-use rustix::fs::{memfd_create, MemfdFlags};
-use rustix::io::Errno;
-use rustix::shm::{shm_open, shm_unlink, ShmOFlags};
-#[cfg(any(target_os = "linux", target_os = "android"))]
-use std::ffi::CStr;
-
-use wayland_client::backend::{InvalidId, ObjectData, WeakBackend};
+use wayland_client::backend::{InvalidId, ObjectData};
 use wayland_client::protocol::wl_buffer::WlBuffer;
-use wayland_client::protocol::wl_shm::{self, Format, WlShm};
-use wayland_client::protocol::wl_shm_pool::{self, WlShmPool};
-use wayland_client::{Connection, Proxy, WEnum};
+use wayland_client::protocol::wl_shm::{Format, WlShm};
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::shm::{Buffer, Shm, ShmError, ShmPool};
+use wayland_client::Connection;
 
 use xcursor::parser as xparser;
 use xcursor::CursorTheme as XCursorTheme;
 use xparser::Image as XCursorImage;
 
+mod animator;
+pub use animator::CursorAnimator;
+
+mod search;
+mod windows;
+
+#[cfg(feature = "cursor-shape")]
+mod manager;
+#[cfg(feature = "cursor-shape")]
+pub use manager::{CursorDevice, CursorManager};
+
+/// An error that occurred while loading a cursor theme or a cursor
+#[derive(Debug)]
+pub enum CursorError {
+    /// The shared memory holding the cursor images could not be allocated or resized
+    Shm(IoError),
+    /// An I/O error occurred while reading a cursor file or writing the cursor images
+    Io(IoError),
+    /// The connection to the Wayland server is dead, or the `wl_shm` object is invalid
+    InvalidId(InvalidId),
+    /// The provided image or cursor file is invalid or uses an unsupported format
+    InvalidImage(String),
+    /// The cursor is provided neither by the theme nor by the themes it inherits from
+    ///
+    /// This is also the case for all cursors if the theme is not installed.
+    NotFound {
+        /// The name of the theme
+        theme: String,
+        /// The name of the cursor
+        cursor: String,
+    },
+}
+
+impl std::error::Error for CursorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CursorError::Shm(source) | CursorError::Io(source) => Some(source),
+            CursorError::InvalidId(source) => Some(source),
+            CursorError::InvalidImage(_) | CursorError::NotFound { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CursorError::Shm(source) => write!(f, "Failed to allocate the cursor shm: {}", source),
+            CursorError::Io(source) => write!(f, "I/O error while loading a cursor: {}", source),
+            CursorError::InvalidId(_) => f.write_str("The Wayland connection is dead"),
+            CursorError::InvalidImage(reason) => write!(f, "Invalid cursor image: {}", reason),
+            CursorError::NotFound { theme, cursor } => {
+                write!(f, "Cursor `{}` not found in theme `{}`", cursor, theme)
+            }
+        }
+    }
+}
+
+impl From<InvalidId> for CursorError {
+    fn from(source: InvalidId) -> Self {
+        CursorError::InvalidId(source)
+    }
+}
+
+fn shm_error(error: ShmError) -> CursorError {
+    match error {
+        ShmError::Io(source) => CursorError::Shm(source),
+        ShmError::InvalidId(source) => CursorError::InvalidId(source),
+        // the images use the Argb8888 format supported by all servers, so the pool is too large
+        error => CursorError::Shm(IoError::new(ErrorKind::OutOfMemory, error.to_string())),
+    }
+}
+
+/// The configuration of a [`CursorTheme`], used with [`CursorTheme::load_with_config()`].
+///
+/// By default, the `default` theme is loaded with a size of 24, the `XCURSOR_THEME` and `XCURSOR_SIZE`
+/// environment variables override them, the themes are looked up in the default search paths and the
+/// loaded cursors are cached without limit.
+///
+/// ```no_run
+/// use wayland_cursor::{CursorTheme, CursorThemeConfig};
+/// # fn example(
+/// #     conn: &wayland_client::Connection,
+/// #     shm: wayland_client::protocol::wl_shm::WlShm,
+/// # ) -> Result<CursorTheme, wayland_cursor::CursorError> {
+/// let config = CursorThemeConfig::new()
+///     .name("Adwaita")
+///     .size(32)
+///     .use_env(false)
+///     .search_paths(["/usr/share/icons"])
+///     .cache_limit(4 * 1024 * 1024);
+/// CursorTheme::load_with_config(conn, shm, &config)
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CursorThemeConfig {
+    name: String,
+    size: u32,
+    use_env: bool,
+    search_paths: Option<Vec<PathBuf>>,
+    cache_limit: Option<usize>,
+}
+
+impl CursorThemeConfig {
+    /// Create the default configuration
+    pub fn new() -> Self {
+        Self {
+            name: String::from("default"),
+            size: 24,
+            use_env: true,
+            search_paths: None,
+            cache_limit: None,
+        }
+    }
+
+    /// Set the name of the theme
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = String::from(name);
+        self
+    }
+
+    /// Set the nominal size of the cursors, in pixels
+    pub fn size(mut self, size: u32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set whether the `XCURSOR_THEME` and `XCURSOR_SIZE` environment variables override the name and
+    /// size of the theme
+    ///
+    /// Invalid values of these variables are ignored.
+    pub fn use_env(mut self, use_env: bool) -> Self {
+        self.use_env = use_env;
+        self
+    }
+
+    /// Set the directories in which the themes are looked up, in order of priority
+    ///
+    /// By default, the themes are looked up in the directories listed by the `XCURSOR_PATH` environment
+    /// variable if it is set, and in the standard icon directories of the XDG specification otherwise.
+    /// Explicit search paths replace all of them.
+    pub fn search_paths<P: Into<PathBuf>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.search_paths = Some(paths.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Limit the memory used by the cached cursor images, in bytes
+    ///
+    /// When loading a cursor would exceed this limit, the least recently used cursors are evicted from
+    /// the cache, and the space of their images in the shared memory pool is reused for the new one. The
+    /// cursors you still hold a clone of, and the ones with an image attached with
+    /// [`CursorImageBuffer::attach_to()`] and not yet released by the server, are never evicted, so the
+    /// limit can be exceeded if they are enough to reach it.
+    pub fn cache_limit(mut self, bytes: usize) -> Self {
+        self.cache_limit = Some(bytes);
+        self
+    }
+}
+
+impl Default for CursorThemeConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Represents a cursor theme loaded from the system.
 #[derive(Debug)]
 pub struct CursorTheme {
     name: String,
+    /// The loaded cursors, from the least to the most recently used
     cursors: Vec<Cursor>,
     size: u32,
-    pool: WlShmPool,
-    pool_size: i32,
-    file: File,
-    backend: WeakBackend,
+    search_paths: Option<Vec<PathBuf>>,
+    cache_limit: Option<usize>,
+    /// The number of bytes used by the images of the loaded cursors
+    cached_bytes: usize,
+    pool: ShmPool,
 }
 
 impl CursorTheme {
@@ -90,11 +270,12 @@ impl CursorTheme {
     /// ```
     /// # use wayland_cursor::CursorTheme;
     /// # use wayland_client::{Connection, backend::InvalidId, protocol::wl_shm};
-    /// # fn example(conn: &Connection, shm: wl_shm::WlShm, size: u32) -> Result<CursorTheme, InvalidId> {
+    /// # use wayland_cursor::CursorError;
+    /// # fn example(conn: &Connection, shm: wl_shm::WlShm, size: u32) -> Result<CursorTheme, CursorError> {
     /// CursorTheme::load_or(conn, shm, "default", size)
     /// # }
     /// ```
-    pub fn load(conn: &Connection, shm: WlShm, size: u32) -> Result<Self, InvalidId> {
+    pub fn load(conn: &Connection, shm: WlShm, size: u32) -> Result<Self, CursorError> {
         Self::load_or(conn, shm, "default", size)
     }
 
@@ -107,110 +288,321 @@ impl CursorTheme {
         conn: &Connection,
         shm: WlShm,
         name: &str,
-        mut size: u32,
-    ) -> Result<Self, InvalidId> {
-        let name_string = String::from(name);
-        let name = &env::var("XCURSOR_THEME").unwrap_or(name_string);
-
-        if let Ok(var) = env::var("XCURSOR_SIZE") {
-            if let Ok(int) = var.parse() {
-                size = int;
-            }
-        }
-
-        Self::load_from_name(conn, shm, name, size)
+        size: u32,
+    ) -> Result<Self, CursorError> {
+        Self::load_with_config(conn, shm, &CursorThemeConfig::new().name(name).size(size))
     }
 
     /// Create a new cursor theme, ignoring the system defaults.
+    ///
+    /// The theme directories are only looked up when loading cursors, so this does not fail if the
+    /// theme is not installed, but [`get_cursor()`](CursorTheme::get_cursor) will.
     pub fn load_from_name(
         conn: &Connection,
         shm: WlShm,
         name: &str,
         size: u32,
-    ) -> Result<Self, InvalidId> {
+    ) -> Result<Self, CursorError> {
+        let config = CursorThemeConfig::new().name(name).size(size).use_env(false);
+        Self::load_with_config(conn, shm, &config)
+    }
+
+    /// Load a cursor theme with an explicit configuration.
+    ///
+    /// Like [`load_from_name()`](CursorTheme::load_from_name), this does not fail if the theme is not
+    /// installed.
+    pub fn load_with_config(
+        _conn: &Connection,
+        shm: WlShm,
+        config: &CursorThemeConfig,
+    ) -> Result<Self, CursorError> {
         // Set some minimal cursor size to hold it. We're not using `size` argument for that,
         // because the actual size that we'll use depends on theme sizes available on a system.
         // The minimal size covers most common minimal theme size, which is 16.
-        const INITIAL_POOL_SIZE: i32 = 16 * 16 * 4;
-
-        //  Create shm.
-        let mem_fd = create_shm_fd().expect("Shm fd allocation failed");
-        let mut file = File::from(mem_fd);
-        file.set_len(INITIAL_POOL_SIZE as u64).expect("Failed to set buffer length");
+        const INITIAL_POOL_SIZE: usize = 16 * 16 * 4;
 
-        // Ensure that we have the same we requested.
-        file.write_all(&[0; INITIAL_POOL_SIZE as usize]).expect("Write to shm fd failed");
-        // Flush to ensure the compositor has access to the buffer when it tries to map it.
-        file.flush().expect("Flush on shm fd failed");
+        let pool = ShmPool::new(&Shm::from_wl_shm(shm), INITIAL_POOL_SIZE).map_err(shm_error)?;
 
-        let pool_id = conn.send_request(
-            &shm,
-            wl_shm::Request::CreatePool { fd: file.as_fd(), size: INITIAL_POOL_SIZE },
-            Some(Arc::new(IgnoreObjectData)),
-        )?;
-        let pool = WlShmPool::from_id(conn, pool_id)?;
-
-        let name = String::from(name);
+        let mut name = config.name.clone();
+        let mut size = config.size;
+        if config.use_env {
+            if let Ok(var) = env::var("XCURSOR_THEME") {
+                if !var.is_empty() {
+                    name = var;
+                }
+            }
+            if let Ok(var) = env::var("XCURSOR_SIZE") {
+                if let Ok(int) = var.parse() {
+                    size = int;
+                }
+            }
+        }
 
         Ok(Self {
             name,
-            file,
             size,
+            search_paths: config.search_paths.clone(),
+            cache_limit: config.cache_limit,
+            cached_bytes: 0,
             pool,
-            pool_size: INITIAL_POOL_SIZE,
             cursors: Vec::new(),
-            backend: conn.backend().downgrade(),
         })
     }
 
     /// Retrieve a cursor from the theme.
     ///
-    /// This method returns [`None`] if this cursor is not provided either by the theme, or by one of its parents.
-    pub fn get_cursor(&mut self, name: &str) -> Option<&Cursor> {
-        match self.cursors.iter().position(|cursor| cursor.name == name) {
-            Some(i) => Some(&self.cursors[i]),
-            None => {
-                let cursor = self.load_cursor(name, self.size)?;
-                self.cursors.push(cursor);
-                self.cursors.iter().last()
-            }
-        }
+    /// This method returns [`CursorError::NotFound`] if this cursor is not provided either by the theme, or
+    /// by one of its parents.
+    pub fn get_cursor(&mut self, name: &str) -> Result<&Cursor, CursorError> {
+        self.get_cursor_with_size(name, self.size)
+    }
+
+    /// Retrieve a cursor from the theme, for a surface displayed with the given scale.
+    ///
+    /// The images are loaded for the size of the theme multiplied by `scale`, which can be the integer
+    /// scale of an output or the fractional scale of a surface given by the `wp_fractional_scale_v1`
+    /// protocol. The theme may not provide images of this size, in which case the nearest ones are used.
+    /// The returned [`ScaledCursor`] gives the buffer scale or viewport destination to use to display
+    /// them with the size of the theme, and their hotspot in surface coordinates.
+    ///
+    /// The cursors loaded for each size are cached separately. Scales which are not strictly positive
+    /// are treated as 1.
+    pub fn get_cursor_for_scale(
+        &mut self,
+        name: &str,
+        scale: f64,
+    ) -> Result<ScaledCursor<'_>, CursorError> {
+        let scale = if scale > 0. && scale.is_finite() { scale } else { 1. };
+        let size = (self.size as f64 * scale).round().max(1.) as u32;
+        let theme_size = self.size.max(1);
+        let cursor = self.get_cursor_with_size(name, size)?;
+        let scale = cursor.nominal_size.max(1) as f64 / theme_size as f64;
+        Ok(ScaledCursor { cursor, scale })
+    }
+
+    fn get_cursor_with_size(&mut self, name: &str, size: u32) -> Result<&Cursor, CursorError> {
+        let cursor = match self
+            .cursors
+            .iter()
+            .position(|c| c.name.as_deref() == Some(name) && c.size == size)
+        {
+            Some(i) => self.cursors.remove(i),
+            None => self.load_cursor(name, size)?,
+        };
+        // keep the cursors ordered by last use
+        self.cursors.push(cursor);
+        Ok(self.cursors.last().unwrap())
     }
 
     /// This function loads a cursor, parses it and pushes the images onto the shm pool.
     ///
     /// Keep in mind that if the cursor is already loaded, the function will make a duplicate.
-    fn load_cursor(&mut self, name: &str, size: u32) -> Option<Cursor> {
-        let conn = Connection::from_backend(self.backend.upgrade()?);
-        let icon_path = XCursorTheme::load(&self.name).load_icon(name)?;
-        let mut icon_file = File::open(icon_path).ok()?;
-
-        let mut buf = Vec::new();
-        let images = {
-            icon_file.read_to_end(&mut buf).ok()?;
-            xparser::parse_xcursor(&buf)?
+    fn load_cursor(&mut self, name: &str, size: u32) -> Result<Cursor, CursorError> {
+        let icon_path = match self.search_paths {
+            Some(ref search_paths) => search::find_cursor(&self.name, name, search_paths),
+            None => XCursorTheme::load(&self.name).load_icon(name),
+        }
+        .ok_or_else(|| CursorError::NotFound { theme: self.name.clone(), cursor: name.into() })?;
+        let icon_file = File::open(icon_path).map_err(CursorError::Io)?;
+
+        let images = xparser::parse_xcursor_stream(&mut BufReader::new(icon_file))
+            .map_err(CursorError::Io)?;
+        if images.is_empty() {
+            return Err(CursorError::Io(IoError::new(
+                ErrorKind::InvalidData,
+                "cursor file contains no image",
+            )));
+        }
+
+        let images = nearest_images(size, images.into_iter().map(Image::from).collect());
+        self.make_room(images.iter().map(|image| image.pixels.len()).sum());
+        let cursor = Cursor::new(Some(name), self, &images, size)?;
+        self.cached_bytes += cursor.len();
+        Ok(cursor)
+    }
+
+    /// Create a cursor from RGBA images.
+    ///
+    /// Each frame is a pair of the pixels of an image and of the time in milliseconds for which it is
+    /// displayed. The pixels are in RGBA order with 8 bits per channel and straight (non-premultiplied)
+    /// alpha, row by row without padding, as decoded by most image libraries. All the frames have the
+    /// given dimensions and hotspot.
+    ///
+    /// The cursor is not part of the theme, so it cannot be retrieved with
+    /// [`get_cursor()`](CursorTheme::get_cursor). The space of its images in the shared memory pool is
+    /// reused for the next cursors once it and all its clones have been dropped.
+    pub fn cursor_from_rgba(
+        &mut self,
+        width: u32,
+        height: u32,
+        hotspot: (u32, u32),
+        frames: &[(&[u8], u32)],
+    ) -> Result<Cursor, CursorError> {
+        let invalid = |reason: &str| CursorError::InvalidImage(String::from(reason));
+        if width == 0 || height == 0 || width > i32::MAX as u32 / 4 / height {
+            return Err(invalid("invalid image dimensions"));
+        }
+        if hotspot.0 >= width || hotspot.1 >= height {
+            return Err(invalid("hotspot outside of the image"));
+        }
+        if frames.is_empty() {
+            return Err(invalid("cursor without frames"));
+        }
+
+        let images = frames
+            .iter()
+            .map(|&(pixels, delay)| {
+                if pixels.len() != (width * height * 4) as usize {
+                    return Err(invalid("image size does not match its dimensions"));
+                }
+                let pixels = pixels
+                    .chunks_exact(4)
+                    .flat_map(|rgba| premultiply([rgba[2], rgba[1], rgba[0], rgba[3]]))
+                    .collect();
+                Ok(Image {
+                    size: width.max(height),
+                    width,
+                    height,
+                    xhot: hotspot.0,
+                    yhot: hotspot.1,
+                    delay,
+                    pixels,
+                })
+            })
+            .collect::<Result<Vec<_>, CursorError>>()?;
+        self.add_custom_cursor(images)
+    }
+
+    /// Load a cursor from a file.
+    ///
+    /// Like [`cursor_from_bytes()`](CursorTheme::cursor_from_bytes), with the content of the file.
+    pub fn cursor_from_file(&mut self, path: impl AsRef<Path>) -> Result<Cursor, CursorError> {
+        let data = std::fs::read(path).map_err(CursorError::Io)?;
+        self.cursor_from_bytes(&data)
+    }
+
+    /// Load a cursor from the content of a cursor file.
+    ///
+    /// XCursor files, as found in cursor themes, and Windows `.cur`, `.ico` and `.ani` files are
+    /// supported, and recognized by their content. When the file provides several sizes, the images nearest
+    /// to the size of the theme are used. Windows cursors whose images are compressed as PNG, and animated
+    /// cursors of more than 256 steps, are not supported.
+    ///
+    /// Like for [`cursor_from_rgba()`](CursorTheme::cursor_from_rgba), the cursor is not part of the theme
+    /// and its space in the shared memory pool is reused once it has been dropped.
+    pub fn cursor_from_bytes(&mut self, data: &[u8]) -> Result<Cursor, CursorError> {
+        let images = if data.starts_with(b"Xcur") {
+            let images = xparser::parse_xcursor(data)
+                .filter(|images| !images.is_empty())
+                .ok_or_else(|| CursorError::InvalidImage(String::from("invalid XCursor file")))?;
+            nearest_images(self.size, images.into_iter().map(Image::from).collect())
+        } else if windows::is_ani(data) {
+            windows::parse_ani(data, self.size)?
+        } else if windows::is_cur(data) {
+            vec![windows::parse_cur(data, self.size)?]
+        } else {
+            return Err(CursorError::InvalidImage(String::from("unknown cursor file format")));
         };
+        self.add_custom_cursor(images)
+    }
 
-        Some(Cursor::new(&conn, name, self, &images, size))
+    fn add_custom_cursor(&mut self, images: Vec<Image>) -> Result<Cursor, CursorError> {
+        self.make_room(images.iter().map(|image| image.pixels.len()).sum());
+        let cursor = Cursor::new(None, self, &images, images[0].size)?;
+        self.cached_bytes += cursor.len();
+        self.cursors.push(cursor.clone());
+        Ok(cursor)
     }
 
-    /// Grow the wl_shm_pool this theme is stored on.
+    /// Evict the least recently used cursors until `bytes` more can be cached within the cache limit.
     ///
-    /// This method does nothing if the provided size is smaller or equal to the pool's current size.
-    fn grow(&mut self, size: i32) {
-        if size > self.pool_size {
-            self.file.set_len(size as u64).expect("Failed to set new buffer length");
-            self.pool.resize(size);
-            self.pool_size = size;
+    /// The cursors which are still shared with the user or displayed are kept.
+    fn make_room(&mut self, bytes: usize) {
+        // the custom cursors cannot be retrieved anymore once they have been dropped
+        let mut i = 0;
+        while i < self.cursors.len() {
+            let cursor = &self.cursors[i];
+            if cursor.name.is_none() && !cursor.in_use() {
+                self.evict(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        let limit = match self.cache_limit {
+            Some(limit) => limit,
+            None => return,
+        };
+        let mut i = 0;
+        while self.cached_bytes + bytes > limit && i < self.cursors.len() {
+            if self.cursors[i].in_use() {
+                i += 1;
+                continue;
+            }
+            self.evict(i);
         }
     }
+
+    /// Remove a cursor from the cache, destroying its buffers and releasing their space in the pool.
+    fn evict(&mut self, index: usize) {
+        let cursor = self.cursors.remove(index);
+        self.cached_bytes -= cursor.len();
+    }
+}
+
+/// A cursor image, with premultiplied ARGB8888 pixels in the byte order of `wl_shm`
+struct Image {
+    /// The nominal size of the image
+    size: u32,
+    width: u32,
+    height: u32,
+    xhot: u32,
+    yhot: u32,
+    delay: u32,
+    pixels: Vec<u8>,
 }
 
-/// A cursor from a theme. Can contain several images if animated.
+impl From<XCursorImage> for Image {
+    fn from(image: XCursorImage) -> Self {
+        Self {
+            size: image.size,
+            width: image.width,
+            height: image.height,
+            xhot: image.xhot,
+            yhot: image.yhot,
+            delay: image.delay,
+            // XCursor files store premultiplied ARGB in little-endian, despite the name of the field
+            pixels: image.pixels_rgba,
+        }
+    }
+}
+
+/// Premultiply the color channels of a pixel by its alpha channel, which is the last one
+fn premultiply([c1, c2, c3, a]: [u8; 4]) -> [u8; 4] {
+    let mul = |c: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+    [mul(c1), mul(c2), mul(c3), a]
+}
+
+/// Select the images whose dimensions are the ones of the image with the nominal size nearest to `size`
+///
+/// The images are not empty.
+fn nearest_images(size: u32, images: Vec<Image>) -> Vec<Image> {
+    let nearest_image =
+        images.iter().min_by_key(|image| (size as i64 - image.size as i64).abs()).unwrap();
+    let (width, height) = (nearest_image.width, nearest_image.height);
+    images.into_iter().filter(|image| image.width == width && image.height == height).collect()
+}
+
+/// A cursor from a theme, or a custom cursor. Can contain several images if animated.
 #[derive(Debug, Clone)]
 pub struct Cursor {
-    name: String,
-    images: Vec<CursorImageBuffer>,
+    /// The name of the cursor in the theme, `None` for custom cursors
+    name: Option<String>,
+    size: u32,
+    /// The nominal size of the images, which differs from `size` if no images of this size exist
+    nominal_size: u32,
+    /// Shared with the clones of the cursor, so that the theme does not evict it while they exist
+    images: Arc<[CursorImageBuffer]>,
     total_duration: u32,
 }
 
@@ -220,33 +612,24 @@ impl Cursor {
     /// Each of the provided images will be written into `theme`.
     /// This will also grow `theme.pool` if necessary.
     fn new(
-        conn: &Connection,
-        name: &str,
+        name: Option<&str>,
         theme: &mut CursorTheme,
-        images: &[XCursorImage],
+        images: &[Image],
         size: u32,
-    ) -> Self {
+    ) -> Result<Self, CursorError> {
         let mut total_duration = 0;
-        let images: Vec<CursorImageBuffer> = Self::nearest_images(size, images)
+        let nominal_size = images[0].size;
+        let images = images
+            .iter()
             .map(|image| {
-                let buffer = CursorImageBuffer::new(conn, theme, image);
+                let buffer = CursorImageBuffer::new(theme, image)?;
                 total_duration += buffer.delay;
 
-                buffer
+                Ok(buffer)
             })
-            .collect();
-
-        Self { total_duration, name: String::from(name), images }
-    }
+            .collect::<Result<Arc<[_]>, CursorError>>()?;
 
-    fn nearest_images(size: u32, images: &[XCursorImage]) -> impl Iterator<Item = &XCursorImage> {
-        // Follow the nominal size of the cursor to choose the nearest
-        let nearest_image =
-            images.iter().min_by_key(|image| (size as i32 - image.size as i32).abs()).unwrap();
-
-        images.iter().filter(move |image| {
-            image.width == nearest_image.width && image.height == nearest_image.height
-        })
+        Ok(Self { total_duration, name: name.map(String::from), size, nominal_size, images })
     }
 
     /// Given a time, calculate which frame to show, and how much time remains until the next frame.
@@ -254,6 +637,10 @@ impl Cursor {
     /// Time will wrap, so if for instance the cursor has an animation lasting 100ms,
     /// then calling this function with 5ms and 105ms as input gives the same output.
     pub fn frame_and_duration(&self, mut millis: u32) -> FrameAndDuration {
+        // static cursors can have no delay
+        if self.total_duration == 0 {
+            return FrameAndDuration { frame_index: 0, frame_duration: 0 };
+        }
         millis %= self.total_duration;
 
         let mut res = 0;
@@ -272,6 +659,17 @@ impl Cursor {
     pub fn image_count(&self) -> usize {
         self.images.len()
     }
+
+    /// Whether the cursor is shared with the user, or one of its images is in use by the server
+    fn in_use(&self) -> bool {
+        Arc::strong_count(&self.images) > 1
+            || self.images.iter().any(|image| image.buffer.is_busy())
+    }
+
+    /// The size of the images of the cursor in the pool, in bytes
+    fn len(&self) -> usize {
+        self.images.iter().map(CursorImageBuffer::len).sum()
+    }
 }
 
 impl Index<usize> for Cursor {
@@ -282,15 +680,83 @@ impl Index<usize> for Cursor {
     }
 }
 
+/// A cursor loaded for a given scale, returned by [`CursorTheme::get_cursor_for_scale()`].
+///
+/// To display an image of this cursor, attach it to the cursor surface and either set the
+/// [`buffer_scale()`](ScaledCursor::buffer_scale) of the surface if there is one, or set the destination
+/// of a `wp_viewport` of the surface to the [`logical_size()`](ScaledCursor::logical_size) of the image.
+/// The hotspot given to `wl_pointer.set_cursor` is the [`logical_hotspot()`](ScaledCursor::logical_hotspot)
+/// of the image.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaledCursor<'a> {
+    cursor: &'a Cursor,
+    scale: f64,
+}
+
+impl<'a> ScaledCursor<'a> {
+    /// The cursor, whose images have been loaded for this scale
+    pub fn cursor(&self) -> &'a Cursor {
+        self.cursor
+    }
+
+    /// The scale of the images of this cursor relative to the size of the theme
+    ///
+    /// This is the scale the cursor was requested for, unless the theme does not provide images of this
+    /// size and the nearest ones are used.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The buffer scale to set on the cursor surface to display an image
+    ///
+    /// This is `None` if the [`scale()`](ScaledCursor::scale) is fractional, or if the dimensions of the
+    /// image are not a multiple of it, in which case a viewport must be used instead.
+    pub fn buffer_scale(&self, index: usize) -> Option<i32> {
+        let scale = self.scale as u32;
+        let (width, height) = self.cursor[index].dimensions();
+        (scale >= 1 && scale as f64 == self.scale && width % scale == 0 && height % scale == 0)
+            .then_some(scale as i32)
+    }
+
+    /// The size of an image in surface coordinates, to be used as the destination of a viewport
+    pub fn logical_size(&self, index: usize) -> (i32, i32) {
+        let (width, height) = self.cursor[index].dimensions();
+        ((width as f64 / self.scale).round() as i32, (height as f64 / self.scale).round() as i32)
+    }
+
+    /// The hotspot of an image in surface coordinates
+    pub fn logical_hotspot(&self, index: usize) -> (i32, i32) {
+        let (x, y) = self.cursor[index].hotspot();
+        ((x as f64 / self.scale).round() as i32, (y as f64 / self.scale).round() as i32)
+    }
+}
+
+/// A cursor displayed at the size of its images
+impl<'a> From<&'a Cursor> for ScaledCursor<'a> {
+    fn from(cursor: &'a Cursor) -> Self {
+        Self { cursor, scale: 1. }
+    }
+}
+
+impl Deref for ScaledCursor<'_> {
+    type Target = Cursor;
+
+    fn deref(&self) -> &Cursor {
+        self.cursor
+    }
+}
+
 /// A buffer containing a cursor image.
 ///
 /// You can access the `WlBuffer` via `Deref`.
 ///
 /// Note that this buffer is internally managed by wayland-cursor, as such you should
-/// not try to act on it beyond assigning it to `wl_surface`s.
+/// not try to act on it beyond assigning it to `wl_surface`s, preferably with
+/// [`attach_to()`](CursorImageBuffer::attach_to).
 #[derive(Debug, Clone)]
 pub struct CursorImageBuffer {
-    buffer: WlBuffer,
+    /// Shared with the clones of the image, its space in the pool is reused once they are all dropped
+    buffer: Arc<Buffer>,
     delay: u32,
     xhot: u32,
     yhot: u32,
@@ -301,42 +767,29 @@ pub struct CursorImageBuffer {
 impl CursorImageBuffer {
     /// Construct a new CursorImageBuffer
     ///
-    /// This function appends the pixels of the image to the provided file,
+    /// This function writes the pixels of the image to the pool of the provided theme,
     /// and constructs a wl_buffer on that data.
-    fn new(conn: &Connection, theme: &mut CursorTheme, image: &XCursorImage) -> Self {
-        let buf = &image.pixels_rgba;
-        let offset = theme.file.seek(SeekFrom::End(0)).unwrap();
-
-        // Resize memory before writing to it to handle shm correctly.
-        let new_size = offset + buf.len() as u64;
-        theme.grow(new_size as i32);
-
-        theme.file.write_all(buf).unwrap();
-
-        let buffer_id = conn
-            .send_request(
-                &theme.pool,
-                wl_shm_pool::Request::CreateBuffer {
-                    offset: offset as i32,
-                    width: image.width as i32,
-                    height: image.height as i32,
-                    stride: (image.width * 4) as i32,
-                    format: WEnum::Value(Format::Argb8888),
-                },
-                Some(Arc::new(IgnoreObjectData)),
+    fn new(theme: &mut CursorTheme, image: &Image) -> Result<Self, CursorError> {
+        let buffer = theme
+            .pool
+            .create_buffer(
+                image.width as i32,
+                image.height as i32,
+                (image.width * 4) as i32,
+                Format::Argb8888,
             )
-            .unwrap();
-
-        let buffer = WlBuffer::from_id(conn, buffer_id).unwrap();
+            .map_err(shm_error)?;
+        // a new buffer is not in use by the server yet
+        theme.pool.canvas(&buffer).unwrap().copy_from_slice(&image.pixels);
 
-        Self {
-            buffer,
+        Ok(Self {
+            buffer: Arc::new(buffer),
             delay: image.delay,
             xhot: image.xhot,
             yhot: image.yhot,
             width: image.width,
             height: image.height,
-        }
+        })
     }
 
     /// Dimensions of this image
@@ -353,13 +806,26 @@ impl CursorImageBuffer {
     pub fn delay(&self) -> u32 {
         self.delay
     }
+
+    /// Attach the image to a surface
+    ///
+    /// The image is then in use until the server releases it, and the theme does not evict its cursor in
+    /// the meantime. Attaching the image with `wl_surface.attach` directly does not track this.
+    pub fn attach_to(&self, surface: &WlSurface) {
+        self.buffer.attach_to(surface);
+    }
+
+    /// The size of the image in the pool, in bytes
+    fn len(&self) -> usize {
+        (self.width * self.height * 4) as usize
+    }
 }
 
 impl Deref for CursorImageBuffer {
     type Target = WlBuffer;
 
     fn deref(&self) -> &WlBuffer {
-        &self.buffer
+        self.buffer.wl_buffer()
     }
 }
 
@@ -374,52 +840,6 @@ pub struct FrameAndDuration {
     pub frame_duration: u32,
 }
 
-/// Create a shared file descriptor in memory.
-fn create_shm_fd() -> IoResult<OwnedFd> {
-    // Only try memfd on systems that provide it, (like Linux, Android)
-    #[cfg(any(target_os = "linux", target_os = "android"))]
-    loop {
-        match memfd_create(
-            CStr::from_bytes_with_nul(b"wayland-cursor-rs\0").unwrap(),
-            MemfdFlags::CLOEXEC,
-        ) {
-            Ok(fd) => return Ok(fd),
-            Err(Errno::INTR) => continue,
-            Err(Errno::NOSYS) => break,
-            Err(errno) => return Err(errno.into()),
-        }
-    }
-
-    // Fallback to using shm_open.
-    let sys_time = SystemTime::now();
-    let mut mem_file_handle = format!(
-        "/wayland-cursor-rs-{}",
-        sys_time.duration_since(UNIX_EPOCH).unwrap().subsec_nanos()
-    );
-    loop {
-        match shm_open(
-            mem_file_handle.as_str(),
-            ShmOFlags::CREATE | ShmOFlags::EXCL | ShmOFlags::RDWR,
-            Mode::RUSR | Mode::WUSR,
-        ) {
-            Ok(fd) => match shm_unlink(mem_file_handle.as_str()) {
-                Ok(_) => return Ok(fd),
-                Err(errno) => return Err(IoError::from(errno)),
-            },
-            Err(Errno::EXIST) => {
-                // If a file with that handle exists then change the handle
-                mem_file_handle = format!(
-                    "/wayland-cursor-rs-{}",
-                    sys_time.duration_since(UNIX_EPOCH).unwrap().subsec_nanos()
-                );
-                continue;
-            }
-            Err(Errno::INTR) => continue,
-            Err(errno) => return Err(IoError::from(errno)),
-        }
-    }
-}
-
 struct IgnoreObjectData;
 
 impl ObjectData for IgnoreObjectData {
@@ -0,0 +1,291 @@
+//! Setting cursors using the cursor shape protocol, with a fallback on cursor themes
+
+use std::sync::Arc;
+
+use wayland_client::protocol::wl_compositor::{self, WlCompositor};
+use wayland_client::protocol::wl_pointer::WlPointer;
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::{Connection, Proxy};
+
+use wayland_protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::{
+    Shape, WpCursorShapeDeviceV1,
+};
+use wayland_protocols::wp::cursor_shape::v1::client::wp_cursor_shape_manager_v1::{
+    self, WpCursorShapeManagerV1,
+};
+use wayland_protocols::wp::tablet::zv2::client::zwp_tablet_tool_v2::ZwpTabletToolV2;
+
+use crate::{Cursor, CursorError, CursorTheme, IgnoreObjectData};
+
+/// The cursor names of the CSS specification, with their cursor shape and their traditional X11 names
+///
+/// Cursor themes usually provide cursors under both names, but older themes only have the X11 ones.
+const CURSOR_NAMES: &[(&str, Shape, &[&str])] = &[
+    ("default", Shape::Default, &["left_ptr", "arrow", "top_left_arrow", "left_arrow"]),
+    ("context-menu", Shape::ContextMenu, &[]),
+    ("help", Shape::Help, &["question_arrow", "whats_this", "left_ptr_help"]),
+    ("pointer", Shape::Pointer, &["hand2", "hand1", "hand", "pointing_hand"]),
+    ("progress", Shape::Progress, &["left_ptr_watch", "half-busy"]),
+    ("wait", Shape::Wait, &["watch", "clock"]),
+    ("cell", Shape::Cell, &["plus"]),
+    ("crosshair", Shape::Crosshair, &["cross", "tcross"]),
+    ("text", Shape::Text, &["xterm", "ibeam"]),
+    ("vertical-text", Shape::VerticalText, &[]),
+    ("alias", Shape::Alias, &["dnd-link", "link"]),
+    ("copy", Shape::Copy, &["dnd-copy"]),
+    ("move", Shape::Move, &["dnd-move"]),
+    ("no-drop", Shape::NoDrop, &["dnd-no-drop"]),
+    ("not-allowed", Shape::NotAllowed, &["crossed_circle", "forbidden", "circle"]),
+    ("grab", Shape::Grab, &["openhand"]),
+    ("grabbing", Shape::Grabbing, &["closedhand"]),
+    ("e-resize", Shape::EResize, &["right_side"]),
+    ("n-resize", Shape::NResize, &["top_side"]),
+    ("ne-resize", Shape::NeResize, &["top_right_corner"]),
+    ("nw-resize", Shape::NwResize, &["top_left_corner"]),
+    ("s-resize", Shape::SResize, &["bottom_side"]),
+    ("se-resize", Shape::SeResize, &["bottom_right_corner"]),
+    ("sw-resize", Shape::SwResize, &["bottom_left_corner"]),
+    ("w-resize", Shape::WResize, &["left_side"]),
+    ("ew-resize", Shape::EwResize, &["sb_h_double_arrow", "h_double_arrow"]),
+    ("ns-resize", Shape::NsResize, &["sb_v_double_arrow", "v_double_arrow"]),
+    ("nesw-resize", Shape::NeswResize, &["fd_double_arrow", "size_bdiag"]),
+    ("nwse-resize", Shape::NwseResize, &["bd_double_arrow", "size_fdiag"]),
+    ("col-resize", Shape::ColResize, &["split_h"]),
+    ("row-resize", Shape::RowResize, &["split_v"]),
+    ("all-scroll", Shape::AllScroll, &["fleur", "size_all"]),
+    ("zoom-in", Shape::ZoomIn, &["zoom_in"]),
+    ("zoom-out", Shape::ZoomOut, &["zoom_out"]),
+];
+
+/// Find the entry of a cursor in [`CURSOR_NAMES`], from its CSS name or one of its X11 names
+fn find_cursor(name: &str) -> Option<&'static (&'static str, Shape, &'static [&'static str])> {
+    CURSOR_NAMES
+        .iter()
+        .find(|(css_name, _, x11_names)| *css_name == name || x11_names.contains(&name))
+}
+
+/// An input device whose cursor can be set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorDevice {
+    /// A pointer
+    Pointer(WlPointer),
+    /// A tablet tool
+    TabletTool(ZwpTabletToolV2),
+}
+
+impl CursorDevice {
+    fn is_alive(&self) -> bool {
+        match self {
+            CursorDevice::Pointer(pointer) => pointer.is_alive(),
+            CursorDevice::TabletTool(tool) => tool.is_alive(),
+        }
+    }
+
+    fn set_cursor(&self, serial: u32, surface: Option<&WlSurface>, x: i32, y: i32) {
+        match self {
+            CursorDevice::Pointer(pointer) => pointer.set_cursor(serial, surface, x, y),
+            CursorDevice::TabletTool(tool) => tool.set_cursor(serial, surface, x, y),
+        }
+    }
+}
+
+impl From<WlPointer> for CursorDevice {
+    fn from(pointer: WlPointer) -> Self {
+        CursorDevice::Pointer(pointer)
+    }
+}
+
+impl From<ZwpTabletToolV2> for CursorDevice {
+    fn from(tool: ZwpTabletToolV2) -> Self {
+        CursorDevice::TabletTool(tool)
+    }
+}
+
+/// Sets the cursors of input devices by name
+///
+/// If the compositor supports the `wp_cursor_shape_v1` protocol, the cursors are set using
+/// `wp_cursor_shape_device_v1.set_shape`, and drawn by the compositor. Otherwise the cursor is loaded from
+/// the [`CursorTheme`], and attached to a cursor surface owned by the manager.
+///
+/// The cursors are named after the [CSS specification](https://www.w3.org/TR/css-ui-3/#cursor), like
+/// `pointer` or `text`. Their traditional X11 names, like `hand2` or `xterm`, are also accepted, and the
+/// alternative names of a cursor are tried if the theme does not provide it. Cursors which are not
+/// part of the CSS specification are always loaded from the theme.
+///
+/// Only the first image of animated cursors is displayed when falling back to the theme.
+///
+/// ```no_run
+/// use wayland_cursor::{CursorManager, CursorTheme};
+/// # fn example(
+/// #     conn: &wayland_client::Connection,
+/// #     shm: wayland_client::protocol::wl_shm::WlShm,
+/// #     compositor: &wayland_client::protocol::wl_compositor::WlCompositor,
+/// #     shape_manager: Option<wayland_protocols::wp::cursor_shape::v1::client::wp_cursor_shape_manager_v1::WpCursorShapeManagerV1>,
+/// #     pointer: wayland_client::protocol::wl_pointer::WlPointer,
+/// #     serial: u32,
+/// # ) -> Result<(), wayland_cursor::CursorError> {
+/// let theme = CursorTheme::load(conn, shm, 24)?;
+/// let mut manager = CursorManager::new(conn, compositor, shape_manager, theme)?;
+/// // on wl_pointer.enter
+/// manager.set_cursor(pointer, serial, "pointer")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CursorManager {
+    conn: Connection,
+    shape_manager: Option<WpCursorShapeManagerV1>,
+    shape_devices: Vec<(CursorDevice, WpCursorShapeDeviceV1)>,
+    theme: CursorTheme,
+    surface: WlSurface,
+    /// The cursor attached to the surface, kept so that the theme does not evict it
+    current: Option<Cursor>,
+}
+
+impl CursorManager {
+    /// Create a new cursor manager
+    ///
+    /// `shape_manager` is the bound `wp_cursor_shape_manager_v1` global if the compositor advertises it,
+    /// the cursors are loaded from `theme` otherwise.
+    pub fn new(
+        conn: &Connection,
+        compositor: &WlCompositor,
+        shape_manager: Option<WpCursorShapeManagerV1>,
+        theme: CursorTheme,
+    ) -> Result<Self, CursorError> {
+        let surface_id = conn.send_request(
+            compositor,
+            wl_compositor::Request::CreateSurface {},
+            Some(Arc::new(IgnoreObjectData)),
+        )?;
+        let surface = WlSurface::from_id(conn, surface_id)?;
+
+        Ok(Self {
+            conn: conn.clone(),
+            shape_manager,
+            shape_devices: Vec::new(),
+            theme,
+            surface,
+            current: None,
+        })
+    }
+
+    /// Set the cursor of a device
+    ///
+    /// `serial` is the serial of the `enter` event of the device. This method returns
+    /// [`CursorError::NotFound`] if the cursor shape protocol is not available and the cursor is
+    /// provided neither by the theme nor under one of its alternative names.
+    pub fn set_cursor(
+        &mut self,
+        device: impl Into<CursorDevice>,
+        serial: u32,
+        name: &str,
+    ) -> Result<(), CursorError> {
+        let device = device.into();
+        let entry = find_cursor(name);
+
+        if let Some((_, shape, _)) = entry {
+            if let Some(shape_device) = self.shape_device(&device)? {
+                shape_device.set_shape(serial, *shape);
+                return Ok(());
+            }
+        }
+
+        let mut names = vec![name];
+        if let Some((css_name, _, x11_names)) = entry {
+            names.push(css_name);
+            names.extend(x11_names.iter());
+        }
+        let mut error = None;
+        for candidate in names {
+            match self.theme.get_cursor(candidate) {
+                Ok(cursor) => {
+                    let image = &cursor[0];
+                    let (x, y) = image.hotspot();
+                    image.attach_to(&self.surface);
+                    self.surface.damage(0, 0, i32::MAX, i32::MAX);
+                    self.surface.commit();
+                    device.set_cursor(serial, Some(&self.surface), x as i32, y as i32);
+                    self.current = Some(cursor.clone());
+                    return Ok(());
+                }
+                Err(err @ CursorError::NotFound { .. }) => {
+                    error.get_or_insert(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(error.unwrap())
+    }
+
+    /// Hide the cursor of a device
+    ///
+    /// `serial` is the serial of the `enter` event of the device.
+    pub fn hide_cursor(&self, device: impl Into<CursorDevice>, serial: u32) {
+        device.into().set_cursor(serial, None, 0, 0);
+    }
+
+    /// Whether the cursors are set using the cursor shape protocol
+    pub fn uses_cursor_shape(&self) -> bool {
+        self.shape_manager.is_some()
+    }
+
+    /// Access the cursor theme used when the cursor shape protocol is not available
+    pub fn theme_mut(&mut self) -> &mut CursorTheme {
+        &mut self.theme
+    }
+
+    /// Get the shape device of an input device, creating it if needed
+    fn shape_device(
+        &mut self,
+        device: &CursorDevice,
+    ) -> Result<Option<&WpCursorShapeDeviceV1>, CursorError> {
+        let shape_manager = match self.shape_manager {
+            Some(ref shape_manager) => shape_manager,
+            None => return Ok(None),
+        };
+
+        // forget the shape devices of the input devices which were released
+        self.shape_devices.retain(|(input_device, shape_device)| {
+            let alive = input_device.is_alive();
+            if !alive {
+                shape_device.destroy();
+            }
+            alive
+        });
+
+        let index = match self.shape_devices.iter().position(|(known, _)| known == device) {
+            Some(index) => index,
+            None => {
+                let request = match device {
+                    CursorDevice::Pointer(pointer) => {
+                        wp_cursor_shape_manager_v1::Request::GetPointer { pointer: pointer.clone() }
+                    }
+                    CursorDevice::TabletTool(tool) => {
+                        wp_cursor_shape_manager_v1::Request::GetTabletToolV2 {
+                            tablet_tool: tool.clone(),
+                        }
+                    }
+                };
+                let id = self.conn.send_request(
+                    shape_manager,
+                    request,
+                    Some(Arc::new(IgnoreObjectData)),
+                )?;
+                let shape_device = WpCursorShapeDeviceV1::from_id(&self.conn, id)?;
+                self.shape_devices.push((device.clone(), shape_device));
+                self.shape_devices.len() - 1
+            }
+        };
+        Ok(Some(&self.shape_devices[index].1))
+    }
+}
+
+impl Drop for CursorManager {
+    fn drop(&mut self) {
+        for (_, shape_device) in self.shape_devices.drain(..) {
+            shape_device.destroy();
+        }
+        self.surface.destroy();
+    }
+}
@@ -0,0 +1,61 @@
+//! Lookup of cursor files in explicit theme search paths
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Find the file of a cursor in a theme or in the themes it inherits from
+///
+/// This follows the same rules as the `xcursor` crate, which can only use the default search paths: the
+/// themes are directories of the search paths, their cursors are in a `cursors` subdirectory, and the
+/// themes they inherit from are given by the `Inherits` key of their `index.theme` file. Themes without
+/// this key inherit from the `default` theme.
+pub(crate) fn find_cursor(theme: &str, name: &str, search_paths: &[PathBuf]) -> Option<PathBuf> {
+    find_in_theme(theme, name, search_paths, &mut HashSet::new())
+}
+
+fn find_in_theme(
+    theme: &str,
+    name: &str,
+    search_paths: &[PathBuf],
+    walked_themes: &mut HashSet<String>,
+) -> Option<PathBuf> {
+    if !walked_themes.insert(theme.to_owned()) {
+        return None;
+    }
+
+    let theme_dirs = search_paths
+        .iter()
+        .map(|path| path.join(theme))
+        .filter(|dir| dir.is_dir())
+        .collect::<Vec<_>>();
+
+    for dir in &theme_dirs {
+        let path = dir.join("cursors").join(name);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+
+    for dir in &theme_dirs {
+        let inherits = match theme_inherits(&dir.join("index.theme")) {
+            Some(inherits) => inherits,
+            None if theme != "default" => String::from("default"),
+            None => continue,
+        };
+        if let Some(path) = find_in_theme(&inherits, name, search_paths, walked_themes) {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+/// Read the first theme of the `Inherits` key of an `index.theme` file
+fn theme_inherits(path: &Path) -> Option<String> {
+    let is_separator = |c: char| c.is_whitespace() || c == ';' || c == ',';
+
+    std::fs::read_to_string(path).ok()?.lines().find_map(|line| {
+        let value = line.strip_prefix("Inherits")?.trim_start().strip_prefix('=')?;
+        value.split(is_separator).find(|theme| !theme.is_empty()).map(String::from)
+    })
+}
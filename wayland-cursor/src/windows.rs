@@ -0,0 +1,460 @@
+//! Parsing of Windows cursor files
+//!
+//! `.cur` files (and `.ico` files, whose hotspot is the top-left corner) contain images of several sizes
+//! stored as device-independent bitmaps, of which the one nearest to the size of the theme is used.
+//! Images compressed as PNG are not supported.
+//!
+//! `.ani` files are RIFF containers of `.cur` or `.ico` frames, with a sequence of steps and their
+//! durations in jiffies (1/60 s).
+
+use crate::{CursorError, Image};
+
+fn invalid(reason: &str) -> CursorError {
+    CursorError::InvalidImage(String::from(reason))
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16, CursorError> {
+    match data.get(offset..offset + 2) {
+        Some(bytes) => Ok(u16::from_le_bytes([bytes[0], bytes[1]])),
+        None => Err(invalid("truncated cursor file")),
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32, CursorError> {
+    match data.get(offset..offset + 4) {
+        Some(bytes) => Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(invalid("truncated cursor file")),
+    }
+}
+
+/// Whether the data is a `.cur` or `.ico` file
+pub(crate) fn is_cur(data: &[u8]) -> bool {
+    data.starts_with(&[0, 0, 2, 0]) || data.starts_with(&[0, 0, 1, 0])
+}
+
+/// Whether the data is an `.ani` file
+pub(crate) fn is_ani(data: &[u8]) -> bool {
+    data.starts_with(b"RIFF") && data.get(8..12) == Some(b"ACON")
+}
+
+/// Parse a `.cur` or `.ico` file, returning its image nearest to `size`
+pub(crate) fn parse_cur(data: &[u8], size: u32) -> Result<Image, CursorError> {
+    let is_cursor = u16_at(data, 2)? == 2;
+    let count = u16_at(data, 4)? as usize;
+
+    let mut images = Vec::with_capacity(count);
+    for i in 0..count {
+        let entry = 6 + 16 * i;
+        // the hotspot replaces the color planes and bit count of icons
+        let (xhot, yhot) =
+            if is_cursor { (u16_at(data, entry + 4)?, u16_at(data, entry + 6)?) } else { (0, 0) };
+        let len = u32_at(data, entry + 8)? as usize;
+        let offset = u32_at(data, entry + 12)? as usize;
+        let bitmap = offset
+            .checked_add(len)
+            .and_then(|end| data.get(offset..end))
+            .ok_or_else(|| invalid("truncated cursor file"))?;
+        if bitmap.starts_with(b"\x89PNG") {
+            continue;
+        }
+        let (width, height, pixels) = decode_bitmap(bitmap)?;
+        images.push(Image {
+            size: width.max(height),
+            width,
+            height,
+            xhot: (xhot as u32).min(width - 1),
+            yhot: (yhot as u32).min(height - 1),
+            delay: 0,
+            pixels,
+        });
+    }
+
+    images.into_iter().min_by_key(|image| (size as i64 - image.size as i64).abs()).ok_or_else(
+        || invalid("no supported image in the cursor file (PNG images are not supported)"),
+    )
+}
+
+/// Decode a device-independent bitmap and its transparency mask into premultiplied ARGB8888 pixels
+fn decode_bitmap(data: &[u8]) -> Result<(u32, u32, Vec<u8>), CursorError> {
+    let header_size = u32_at(data, 0)? as u64;
+    let width = u32_at(data, 4)? as i32;
+    // the height covers both the color bitmap and the mask
+    let height = u32_at(data, 8)? as i32 / 2;
+    let bit_count = u16_at(data, 14)? as u64;
+    let compression = u32_at(data, 16)?;
+    let colors_used = u32_at(data, 32)? as u64;
+
+    if width <= 0 || height <= 0 {
+        return Err(invalid("invalid bitmap dimensions"));
+    }
+    if compression != 0 || ![1, 4, 8, 24, 32].contains(&bit_count) {
+        return Err(invalid("unsupported bitmap format"));
+    }
+    let (width, height) = (width as u64, height as u64);
+
+    let palette_len = match bit_count {
+        1 | 4 | 8 if colors_used != 0 => colors_used,
+        1 | 4 | 8 => 1 << bit_count,
+        _ => 0,
+    };
+    let color_start = header_size + 4 * palette_len;
+    let color_stride = (width * bit_count + 31) / 32 * 4;
+    let mask_start = color_start + color_stride * height;
+    let mask_stride = (width + 31) / 32 * 4;
+    let mask_end = mask_start + mask_stride * height;
+    if (data.len() as u64) < mask_start {
+        return Err(invalid("truncated bitmap"));
+    }
+    // 32-bit bitmaps have an alpha channel, the mask is only used if it is empty
+    let has_alpha = bit_count == 32
+        && data[color_start as usize..mask_start as usize].chunks_exact(4).any(|p| p[3] != 0);
+    if !has_alpha && (data.len() as u64) < mask_end {
+        return Err(invalid("truncated bitmap mask"));
+    }
+
+    let (palette_start, color_start, color_stride) =
+        (header_size as usize, color_start as usize, color_stride as usize);
+    let (mask_start, mask_stride) = (mask_start as usize, mask_stride as usize);
+    let (width, height) = (width as usize, height as usize);
+    let bit_count = bit_count as usize;
+
+    let mut pixels = vec![0; width * height * 4];
+    for y in 0..height {
+        // the rows are stored bottom-up
+        let row = height - 1 - y;
+        let color_row = &data[color_start + row * color_stride..][..color_stride];
+        for x in 0..width {
+            let [b, g, r, a] = match bit_count {
+                32 => [
+                    color_row[4 * x],
+                    color_row[4 * x + 1],
+                    color_row[4 * x + 2],
+                    color_row[4 * x + 3],
+                ],
+                24 => [color_row[3 * x], color_row[3 * x + 1], color_row[3 * x + 2], 0],
+                _ => {
+                    let bit = x * bit_count;
+                    let index = (color_row[bit / 8] >> (8 - bit_count - bit % 8)) as usize
+                        & ((1 << bit_count) - 1);
+                    if index as u64 >= palette_len {
+                        return Err(invalid("bitmap color index out of the palette"));
+                    }
+                    let color = &data[palette_start + 4 * index..][..4];
+                    [color[0], color[1], color[2], 0]
+                }
+            };
+            let a = if has_alpha {
+                a
+            } else {
+                // pixels whose mask bit is set are transparent, inverted pixels are not supported
+                let mask = data[mask_start + row * mask_stride + x / 8] >> (7 - x % 8) & 1;
+                if mask == 1 {
+                    0
+                } else {
+                    255
+                }
+            };
+            let pixel = &mut pixels[4 * (y * width + x)..][..4];
+            pixel.copy_from_slice(&crate::premultiply([b, g, r, a]));
+        }
+    }
+
+    Ok((width as u32, height as u32, pixels))
+}
+
+/// The id and the data of a RIFF chunk
+type Chunk<'a> = (&'a [u8], &'a [u8]);
+
+/// Split the content of a RIFF chunk list into its chunks
+fn riff_chunks(data: &[u8]) -> Result<Vec<Chunk<'_>>, CursorError> {
+    let mut data = data;
+    let mut chunks = Vec::new();
+    while data.len() >= 8 {
+        let len = u32_at(data, 4)? as usize;
+        let end = len.saturating_add(8);
+        let chunk = data.get(8..end).ok_or_else(|| invalid("truncated animated cursor file"))?;
+        chunks.push((&data[..4], chunk));
+        // chunks are padded to an even length
+        data = data.get(end + (len & 1)..).unwrap_or(&[]);
+    }
+    Ok(chunks)
+}
+
+fn u32_list(data: &[u8]) -> Vec<u32> {
+    data.chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// The maximal number of steps of an animated cursor
+///
+/// Each step is a copy of the image of its frame, this bounds the memory used by a small file repeating
+/// the same frames.
+const MAX_STEPS: usize = 256;
+
+/// Parse an `.ani` file into the images of its steps, using for each frame its image nearest to `size`
+pub(crate) fn parse_ani(data: &[u8], size: u32) -> Result<Vec<Image>, CursorError> {
+    let mut header = None;
+    let mut rates = None;
+    let mut sequence = None;
+    let mut frames = Vec::new();
+
+    for (id, chunk) in riff_chunks(data.get(12..).unwrap_or(&[]))? {
+        match id {
+            b"anih" => header = Some(chunk),
+            b"rate" => rates = Some(u32_list(chunk)),
+            b"seq " => sequence = Some(u32_list(chunk)),
+            b"LIST" if chunk.starts_with(b"fram") => {
+                for (id, icon) in riff_chunks(&chunk[4..])? {
+                    if id == b"icon" {
+                        frames.push(parse_cur(icon, size)?);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or_else(|| invalid("missing animated cursor header"))?;
+    let step_count = u32_at(header, 8)? as usize;
+    let default_rate = u32_at(header, 28)?;
+    let flags = u32_at(header, 32)?;
+    if flags & 1 == 0 {
+        return Err(invalid("animated cursors with raw bitmap frames are not supported"));
+    }
+    if frames.is_empty() {
+        return Err(invalid("animated cursor without frames"));
+    }
+    let step_count = if step_count == 0 { frames.len() } else { step_count };
+    if step_count > MAX_STEPS {
+        return Err(invalid("too many steps in the animated cursor"));
+    }
+
+    (0..step_count)
+        .map(|step| {
+            let frame = match sequence {
+                Some(ref sequence) => sequence.get(step).map(|&frame| frame as usize),
+                None => Some(step),
+            };
+            let image = frame
+                .and_then(|frame| frames.get(frame))
+                .ok_or_else(|| invalid("animated cursor step without frame"))?;
+            let jiffies =
+                rates.as_ref().and_then(|rates| rates.get(step)).copied().unwrap_or(default_rate);
+            Ok(Image {
+                delay: jiffies.saturating_mul(1000) / 60,
+                pixels: image.pixels.clone(),
+                ..*image
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A bitmap with the given palette, color rows and mask rows, in file order (bottom-up)
+    fn bitmap(
+        width: u32,
+        height: u32,
+        bit_count: u16,
+        palette: &[[u8; 4]],
+        rows: &[&[u8]],
+        mask: &[&[u8]],
+    ) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in [40, width, 2 * height] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&bit_count.to_le_bytes());
+        for value in [0, 0, 0, 0, palette.len() as u32, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for color in palette {
+            data.extend_from_slice(color);
+        }
+        // rows are padded to 4 bytes
+        for row in rows.iter().chain(mask) {
+            data.extend_from_slice(row);
+            data.resize(data.len() + (4 - row.len() % 4) % 4, 0);
+        }
+        data
+    }
+
+    /// A `.cur` file (`kind` 2) or `.ico` file (`kind` 1) with the given hotspots and bitmaps
+    fn cur(kind: u16, images: &[((u16, u16), Vec<u8>)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for value in [0, kind, images.len() as u16] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        let mut offset = 6 + 16 * images.len();
+        for ((xhot, yhot), bitmap) in images {
+            data.extend_from_slice(&[0, 0, 0, 0]);
+            data.extend_from_slice(&xhot.to_le_bytes());
+            data.extend_from_slice(&yhot.to_le_bytes());
+            data.extend_from_slice(&(bitmap.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += bitmap.len();
+        }
+        for (_, bitmap) in images {
+            data.extend_from_slice(bitmap);
+        }
+        data
+    }
+
+    fn chunk(id: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut data = id.to_vec();
+        data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        data.extend_from_slice(content);
+        data.resize(data.len() + content.len() % 2, 0);
+        data
+    }
+
+    fn u32_bytes(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|value| value.to_le_bytes()).collect()
+    }
+
+    /// An `.ani` file with the given frames, and optional sequence and rates
+    fn ani(
+        frames: &[Vec<u8>],
+        steps: u32,
+        sequence: Option<&[u32]>,
+        rates: Option<&[u32]>,
+    ) -> Vec<u8> {
+        let mut content = b"ACON".to_vec();
+        content.extend(chunk(
+            b"anih",
+            &u32_bytes(&[36, frames.len() as u32, steps, 0, 0, 0, 0, 10, 1]),
+        ));
+        if let Some(sequence) = sequence {
+            content.extend(chunk(b"seq ", &u32_bytes(sequence)));
+        }
+        if let Some(rates) = rates {
+            content.extend(chunk(b"rate", &u32_bytes(rates)));
+        }
+        let mut list = b"fram".to_vec();
+        for frame in frames {
+            list.extend(chunk(b"icon", frame));
+        }
+        content.extend(chunk(b"LIST", &list));
+        chunk(b"RIFF", &content)
+    }
+
+    fn assert_invalid<T>(result: Result<T, CursorError>) {
+        match result {
+            Err(CursorError::InvalidImage(_)) => {}
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("the cursor data should be invalid"),
+        }
+    }
+
+    #[test]
+    fn bitmap_32_bits_alpha() {
+        // the bottom row is stored first
+        let rows: [&[u8]; 2] = [&[0, 0, 255, 255, 0, 255, 0, 128], &[255, 0, 0, 255, 0, 0, 0, 0]];
+        let data = bitmap(2, 2, 32, &[], &rows, &[]);
+        let (width, height, pixels) = decode_bitmap(&data).unwrap();
+        assert_eq!((width, height), (2, 2));
+        assert_eq!(pixels, [255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 255, 255, 0, 128, 0, 128]);
+    }
+
+    #[test]
+    fn bitmap_32_bits_mask() {
+        // without alpha channel, the pixels whose mask bit is set are transparent
+        let rows: [&[u8]; 2] = [&[10, 20, 30, 0, 10, 20, 30, 0], &[10, 20, 30, 0, 10, 20, 30, 0]];
+        let data = bitmap(2, 2, 32, &[], &rows, &[&[0b0100_0000], &[0]]);
+        let (_, _, pixels) = decode_bitmap(&data).unwrap();
+        assert_eq!(pixels, [10, 20, 30, 255, 10, 20, 30, 255, 10, 20, 30, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn bitmap_24_bits() {
+        let rows: [&[u8]; 2] = [&[1, 2, 3, 4, 5, 6], &[7, 8, 9, 10, 11, 12]];
+        let data = bitmap(2, 2, 24, &[], &rows, &[&[0], &[0b1000_0000]]);
+        let (_, _, pixels) = decode_bitmap(&data).unwrap();
+        assert_eq!(pixels, [0, 0, 0, 0, 10, 11, 12, 255, 1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn bitmap_palette() {
+        let palette = [[255, 0, 0, 0], [0, 255, 0, 0], [0, 0, 255, 0]];
+        let (blue, green, red) = ([255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]);
+        let fixtures: [(u16, [&[u8]; 2]); 3] = [
+            (8, [&[0, 1], &[2, 0]]),
+            (4, [&[0x01], &[0x20]]),
+            (1, [&[0b0100_0000], &[0b1000_0000]]),
+        ];
+        for (bit_count, rows) in fixtures {
+            // the 1-bit bitmap only uses the first two colors
+            let palette = if bit_count == 1 { &palette[..2] } else { &palette[..] };
+            let data = bitmap(2, 2, bit_count, palette, &rows, &[&[0], &[0]]);
+            let (_, _, pixels) = decode_bitmap(&data).unwrap();
+            let top = if bit_count == 1 { [green, blue] } else { [red, blue] };
+            let expected = [top[0], top[1], blue, green].concat();
+            assert_eq!(pixels, expected, "{}-bit bitmap", bit_count);
+        }
+    }
+
+    #[test]
+    fn bitmap_invalid() {
+        // color index out of the palette
+        let data = bitmap(2, 2, 8, &[[0; 4]; 2], &[&[0, 5], &[0, 0]], &[&[0], &[0]]);
+        assert_invalid(decode_bitmap(&data));
+        // truncated color rows or mask
+        let data = bitmap(2, 2, 24, &[], &[&[0; 6], &[0; 6]], &[&[0], &[0]]);
+        assert_invalid(decode_bitmap(&data[..data.len() - 12]));
+        assert_invalid(decode_bitmap(&data[..data.len() - 1]));
+        assert_invalid(decode_bitmap(&data[..20]));
+        // unsupported bit count
+        let data = bitmap(2, 2, 16, &[], &[&[0; 4], &[0; 4]], &[&[0], &[0]]);
+        assert_invalid(decode_bitmap(&data));
+    }
+
+    #[test]
+    fn cur_hotspot_and_size() {
+        let small = bitmap(2, 2, 32, &[], &[&[255; 8], &[255; 8]], &[]);
+        let large = bitmap(4, 4, 32, &[], &[&[255; 16][..]; 4], &[]);
+        let data = cur(2, &[((1, 0), small.clone()), ((3, 2), large.clone())]);
+        assert!(is_cur(&data));
+
+        let image = parse_cur(&data, 2).unwrap();
+        assert_eq!((image.width, image.height, image.xhot, image.yhot), (2, 2, 1, 0));
+        let image = parse_cur(&data, 5).unwrap();
+        assert_eq!((image.width, image.height, image.xhot, image.yhot), (4, 4, 3, 2));
+
+        // the hotspot of icons is their top-left corner
+        let data = cur(1, &[((1, 1), small)]);
+        let image = parse_cur(&data, 2).unwrap();
+        assert_eq!((image.xhot, image.yhot), (0, 0));
+
+        assert_invalid(parse_cur(&data[..data.len() - 1], 2));
+        assert_invalid(parse_cur(&data[..10], 2));
+    }
+
+    #[test]
+    fn ani_steps() {
+        // opaque frames whose blue channel identifies them
+        let frame = |blue| cur(2, &[((0, 0), bitmap(1, 1, 32, &[], &[&[blue, 0, 0, 255]], &[]))]);
+        let frames = [frame(1), frame(2)];
+        let data = ani(&frames, 3, Some(&[1, 0, 1]), Some(&[6, 12, 6]));
+        assert!(is_ani(&data));
+
+        let images = parse_ani(&data, 1).unwrap();
+        let steps = images.iter().map(|image| (image.pixels[0], image.delay)).collect::<Vec<_>>();
+        assert_eq!(steps, [(2, 100), (1, 200), (2, 100)]);
+
+        // without sequence nor rates, each frame is a step with the default rate
+        let images = parse_ani(&ani(&frames, 0, None, None), 1).unwrap();
+        let steps = images.iter().map(|image| (image.pixels[0], image.delay)).collect::<Vec<_>>();
+        assert_eq!(steps, [(1, 166), (2, 166)]);
+
+        // a step refers to a missing frame
+        assert_invalid(parse_ani(&ani(&frames, 2, Some(&[0, 2]), None), 1));
+        // too many steps
+        let sequence = vec![0; MAX_STEPS + 1];
+        assert_invalid(parse_ani(&ani(&frames, sequence.len() as u32, Some(&sequence), None), 1));
+        assert_invalid(parse_ani(&data[..data.len() - 2], 1));
+    }
+}
@@ -1,5 +1,6 @@
 #![warn(missing_docs, missing_debug_implementations)]
 #![forbid(improper_ctypes, unsafe_op_in_unsafe_fn)]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 //! EGL utilities
 //!
@@ -8,19 +9,47 @@
 //! This library is used to interface with the OpenGL stack, and creating
 //! EGL surfaces from a wayland surface.
 //!
-//! See [`WlEglSurface`] documentation for details.
+//! See `WlEglSurface` documentation for details.
+//!
+//! ## Backends
+//!
+//! `libwayland-egl` needs the `wl_surface` pointer of `libwayland-client`, which only exists with the
+//! system backend of `wayland-backend`. It is enabled by the `client_system` cargo feature of this crate,
+//! which is enabled by default. Without it, `WlEglSurface` is not available and `libwayland-egl` is
+//! neither linked nor loaded: the objects of the rust backend cannot be shared with `libwayland-client`,
+//! not even through a second connection to the same socket, as the two connections would have their
+//! own objects.
+//!
+//! [`MockEglSurface`] tracks the sizes and resize offsets of a surface in pure rust, which allows testing
+//! the code driving an EGL surface through the [`EglWindow`] trait without a GPU, with either backend.
 
-use std::{fmt, os::raw::c_void};
+use std::fmt;
+#[cfg(feature = "client_system")]
+use std::os::raw::c_void;
 
+#[cfg(feature = "client_system")]
 use wayland_backend::client::ObjectId;
+#[cfg(feature = "client_system")]
 use wayland_sys::{client::wl_proxy, egl::*, ffi_dispatch};
 
+mod mock;
+
+pub use mock::MockEglSurface;
+
 /// Checks if the wayland-egl lib is available and can be used
 ///
-/// Trying to create an [`WlEglSurface`] while this function returns
-/// `false` will result in a panic.
+/// Trying to create an `WlEglSurface` while this function returns
+/// `false` will result in a panic. This always returns `false` without
+/// the `client_system` cargo feature.
 pub fn is_available() -> bool {
-    is_lib_available()
+    #[cfg(feature = "client_system")]
+    {
+        is_lib_available()
+    }
+    #[cfg(not(feature = "client_system"))]
+    {
+        false
+    }
 }
 
 /// EGL surface
@@ -30,11 +59,13 @@ pub fn is_available() -> bool {
 /// is created to get the window pointer your OpenGL library is needing to initialize
 /// the EGL context (you'll most likely need the display ptr as well, that you can
 /// get via the [`ObjectId::as_ptr()`] method on of the `wl_display` ID).
+#[cfg(feature = "client_system")]
 #[derive(Debug)]
 pub struct WlEglSurface {
     ptr: *mut wl_egl_window,
 }
 
+#[cfg(feature = "client_system")]
 impl WlEglSurface {
     /// Create an EGL surface from a wayland surface
     ///
@@ -124,9 +155,11 @@ impl WlEglSurface {
 }
 
 // SAFETY: We own the pointer to the wl_egl_window and can therefore be transferred to another thread.
+#[cfg(feature = "client_system")]
 unsafe impl Send for WlEglSurface {}
 // Note that WlEglSurface is !Sync. This is because the pointer performs no internal synchronization.
 
+#[cfg(feature = "client_system")]
 impl Drop for WlEglSurface {
     fn drop(&mut self) {
         unsafe {
@@ -135,6 +168,29 @@ impl Drop for WlEglSurface {
     }
 }
 
+#[cfg(feature = "client_system")]
+impl EglWindow for WlEglSurface {
+    fn get_size(&self) -> (i32, i32) {
+        WlEglSurface::get_size(self)
+    }
+
+    fn resize(&self, width: i32, height: i32, dx: i32, dy: i32) {
+        WlEglSurface::resize(self, width, height, dx, dy)
+    }
+}
+
+/// The size management of an EGL surface
+///
+/// This trait is implemented by `WlEglSurface` and by [`MockEglSurface`], to test the code managing
+/// the size of a surface without `libwayland-egl`.
+pub trait EglWindow {
+    /// Fetch the size of the last buffer attached to the surface
+    fn get_size(&self) -> (i32, i32);
+
+    /// Resize the surface, moving its top-left corner by `(dx, dy)`
+    fn resize(&self, width: i32, height: i32, dx: i32, dy: i32);
+}
+
 /// EGL surface creation error.
 #[derive(Debug)]
 pub enum Error {
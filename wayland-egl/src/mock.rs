@@ -0,0 +1,203 @@
+use std::cell::Cell;
+
+use wayland_backend::client::ObjectId;
+
+use crate::{EglWindow, Error};
+
+/// EGL surface bookkeeping without `libwayland-egl`
+///
+/// This object tracks the sizes and resize offsets of a surface the same way `libwayland-egl` does
+/// for a `wl_egl_window`, without loading any library or requiring a GPU. It works with both the
+/// rust and the system backends, and is meant to test the code driving a `WlEglSurface` through the
+/// [`EglWindow`] trait.
+///
+/// As with a `wl_egl_window`, the size reported by [`get_size()`](EglWindow::get_size) is the size
+/// of the last attached buffer, which is `(0, 0)` until the first call to
+/// [`swap_buffers()`](MockEglSurface::swap_buffers) (which is where the EGL implementation attaches a
+/// new buffer to the surface).
+#[derive(Debug)]
+pub struct MockEglSurface {
+    surface: ObjectId,
+    size: Cell<(i32, i32)>,
+    offset: Cell<(i32, i32)>,
+    attached_size: Cell<(i32, i32)>,
+}
+
+impl MockEglSurface {
+    /// Create a mock EGL surface for a wayland surface
+    ///
+    /// This method checks that the provided `ObjectId` is from the correct interface (`wl_surface`)
+    /// and that the size is positive, like `WlEglSurface::new()`.
+    pub fn new(surface: ObjectId, width: i32, height: i32) -> Result<Self, Error> {
+        if surface.interface().name != "wl_surface" {
+            return Err(Error::InvalidId);
+        }
+        if width <= 0 || height <= 0 {
+            return Err(Error::InvalidSize);
+        }
+        Ok(Self {
+            surface,
+            size: Cell::new((width, height)),
+            offset: Cell::new((0, 0)),
+            attached_size: Cell::new((0, 0)),
+        })
+    }
+
+    /// The wayland surface of this EGL surface
+    pub fn surface(&self) -> &ObjectId {
+        &self.surface
+    }
+
+    /// The size requested by the last resize, which the next attached buffer will have
+    pub fn size(&self) -> (i32, i32) {
+        self.size.get()
+    }
+
+    /// The displacement of the top-left corner of the surface requested by the last resize
+    ///
+    /// It is reset when the next buffer is attached.
+    pub fn pending_offset(&self) -> (i32, i32) {
+        self.offset.get()
+    }
+
+    /// Simulate the swap of the buffers by the EGL implementation
+    ///
+    /// This attaches a buffer of the current size, and returns the displacement the EGL implementation
+    /// would send with it to the compositor.
+    pub fn swap_buffers(&self) -> (i32, i32) {
+        self.attached_size.set(self.size.get());
+        self.offset.replace((0, 0))
+    }
+}
+
+impl EglWindow for MockEglSurface {
+    fn get_size(&self) -> (i32, i32) {
+        self.attached_size.get()
+    }
+
+    fn resize(&self, width: i32, height: i32, dx: i32, dy: i32) {
+        self.size.set((width, height));
+        self.offset.set((dx, dy));
+    }
+}
+
+// the objects of the system backend cannot be created without a compositor
+#[cfg(all(test, not(feature = "client_system")))]
+mod tests {
+    use std::ffi::CString;
+    use std::os::unix::net::UnixStream;
+    use std::sync::Arc;
+
+    use wayland_backend::client::{Backend, ObjectData, ObjectId};
+    use wayland_backend::protocol::{Argument, Interface, Message};
+
+    use super::MockEglSurface;
+    use crate::{EglWindow, Error};
+
+    static WL_SURFACE_INTERFACE: Interface =
+        Interface { name: "wl_surface", version: 1, requests: &[], events: &[], c_ptr: None };
+
+    struct DoNothingData;
+
+    impl ObjectData for DoNothingData {
+        fn event(
+            self: Arc<Self>,
+            _: &Backend,
+            _: Message<ObjectId, std::os::unix::io::OwnedFd>,
+        ) -> Option<Arc<dyn ObjectData>> {
+            None
+        }
+
+        fn destroyed(&self, _: ObjectId) {}
+    }
+
+    /// Create a `wl_surface` and a `wl_registry`, the requests are never read by a server
+    fn objects() -> (Backend, UnixStream, ObjectId, ObjectId) {
+        let (client, server) = UnixStream::pair().unwrap();
+        let backend = Backend::connect(client).unwrap();
+        let registry = backend
+            .send_request(
+                Message {
+                    sender_id: backend.display_id(),
+                    opcode: 1,
+                    args: [Argument::NewId(ObjectId::null())].into_iter().collect(),
+                },
+                Some(Arc::new(DoNothingData)),
+                None,
+            )
+            .unwrap();
+        let surface = backend
+            .send_request(
+                Message {
+                    sender_id: registry.clone(),
+                    opcode: 0,
+                    args: [
+                        Argument::Uint(1),
+                        Argument::Str(Some(Box::new(CString::new("wl_surface").unwrap()))),
+                        Argument::Uint(1),
+                        Argument::NewId(ObjectId::null()),
+                    ]
+                    .into_iter()
+                    .collect(),
+                },
+                Some(Arc::new(DoNothingData)),
+                Some((&WL_SURFACE_INTERFACE, 1)),
+            )
+            .unwrap();
+        (backend, server, surface, registry)
+    }
+
+    #[test]
+    fn new_validation() {
+        let (_backend, _server, surface, registry) = objects();
+        assert!(matches!(MockEglSurface::new(ObjectId::null(), 10, 10), Err(Error::InvalidId)));
+        assert!(matches!(MockEglSurface::new(registry, 10, 10), Err(Error::InvalidId)));
+        assert!(matches!(MockEglSurface::new(surface.clone(), 0, 10), Err(Error::InvalidSize)));
+        assert!(matches!(MockEglSurface::new(surface.clone(), 10, -1), Err(Error::InvalidSize)));
+
+        let egl_surface = MockEglSurface::new(surface.clone(), 10, 20).unwrap();
+        assert_eq!(egl_surface.surface(), &surface);
+        assert_eq!(egl_surface.size(), (10, 20));
+        assert_eq!(egl_surface.pending_offset(), (0, 0));
+    }
+
+    #[test]
+    fn attached_size() {
+        let (_backend, _server, surface, _) = objects();
+        let egl_surface = MockEglSurface::new(surface, 10, 20).unwrap();
+        // no buffer is attached before the first swap
+        assert_eq!(egl_surface.get_size(), (0, 0));
+        egl_surface.swap_buffers();
+        assert_eq!(egl_surface.get_size(), (10, 20));
+    }
+
+    #[test]
+    fn resize() {
+        let (_backend, _server, surface, _) = objects();
+        let egl_surface = MockEglSurface::new(surface, 10, 20).unwrap();
+        egl_surface.swap_buffers();
+
+        egl_surface.resize(30, 40, 5, -5);
+        assert_eq!(egl_surface.size(), (30, 40));
+        assert_eq!(egl_surface.pending_offset(), (5, -5));
+        // the attached buffer keeps its size until the next swap
+        assert_eq!(egl_surface.get_size(), (10, 20));
+
+        // only the last resize before the swap is applied
+        egl_surface.resize(50, 60, 1, 2);
+        assert_eq!(egl_surface.swap_buffers(), (1, 2));
+        assert_eq!(egl_surface.get_size(), (50, 60));
+    }
+
+    #[test]
+    fn swap_buffers_resets_offset() {
+        let (_backend, _server, surface, _) = objects();
+        let egl_surface = MockEglSurface::new(surface, 10, 20).unwrap();
+        egl_surface.resize(10, 20, 3, 4);
+        assert_eq!(egl_surface.swap_buffers(), (3, 4));
+        assert_eq!(egl_surface.pending_offset(), (0, 0));
+        // the offset is sent with a single buffer
+        assert_eq!(egl_surface.swap_buffers(), (0, 0));
+        assert_eq!(egl_surface.get_size(), (10, 20));
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+
+use wayland_codegen::protocol::{Enum, Interface, Message, Protocol};
+
+use crate::summary::{arg_type, message_signature};
+
+/// A change between two versions of a protocol
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// Whether the change breaks backward compatibility
+    pub breaking: bool,
+    /// Path of the changed element, like `interface[wl_surface] > request[attach]`
+    pub path: Vec<String>,
+    /// Description of the change
+    pub message: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.breaking { "breaking" } else { "compatible" };
+        write!(f, "{}: in `{}`: {}", kind, self.path.join(" > "), self.message)
+    }
+}
+
+struct Differ {
+    path: Vec<String>,
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn enter(&mut self, tag: &str, name: &str) {
+        self.path.push(format!("{}[{}]", tag, name));
+    }
+
+    fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    fn report(&mut self, breaking: bool, message: impl Into<String>) {
+        self.changes.push(Change { breaking, path: self.path.clone(), message: message.into() });
+    }
+
+    fn breaking(&mut self, message: impl Into<String>) {
+        self.report(true, message)
+    }
+
+    fn compatible(&mut self, message: impl Into<String>) {
+        self.report(false, message)
+    }
+
+    fn diff_interface(&mut self, old: &Interface, new: &Interface) {
+        if new.version < old.version {
+            self.breaking(format!("version decreased from {} to {}", old.version, new.version));
+        } else if new.version > old.version {
+            if old.frozen {
+                self.breaking(format!(
+                    "version of frozen interface increased from {} to {}",
+                    old.version, new.version
+                ));
+            } else {
+                self.compatible(format!(
+                    "version increased from {} to {}",
+                    old.version, new.version
+                ));
+            }
+        }
+
+        self.diff_messages("request", &old.requests, &new.requests, old.version);
+        self.diff_messages("event", &old.events, &new.events, old.version);
+
+        for old_enum in &old.enums {
+            self.enter("enum", &old_enum.name);
+            match new.enums.iter().find(|e| e.name == old_enum.name) {
+                Some(new_enum) => self.diff_enum(old_enum, new_enum, old.version),
+                None => self.breaking("removed"),
+            }
+            self.leave();
+        }
+        for new_enum in &new.enums {
+            if !old.enums.iter().any(|e| e.name == new_enum.name) {
+                self.enter("enum", &new_enum.name);
+                self.compatible("added");
+                self.leave();
+            }
+        }
+    }
+
+    fn diff_messages(&mut self, kind: &str, old: &[Message], new: &[Message], old_version: u32) {
+        // messages are identified on the wire by their opcode, which is their index
+        for (opcode, old_msg) in old.iter().enumerate() {
+            self.enter(kind, &old_msg.name);
+            match new.iter().position(|msg| msg.name == old_msg.name) {
+                Some(new_opcode) if new_opcode != opcode => {
+                    self.breaking(format!("opcode changed from {} to {}", opcode, new_opcode))
+                }
+                Some(_) => self.diff_message(old_msg, &new[opcode]),
+                None => self.breaking("removed"),
+            }
+            self.leave();
+        }
+        for new_msg in new {
+            if old.iter().any(|msg| msg.name == new_msg.name) {
+                continue;
+            }
+            self.enter(kind, &new_msg.name);
+            if new_msg.since > old_version {
+                self.compatible(format!("added: {}", message_signature(new_msg)));
+            } else {
+                self.breaking(format!(
+                    "added with since={}, which is not greater than the previous version {}",
+                    new_msg.since, old_version
+                ));
+            }
+            self.leave();
+        }
+    }
+
+    fn diff_message(&mut self, old: &Message, new: &Message) {
+        if old.since != new.since {
+            self.breaking(format!("since changed from {} to {}", old.since, new.since));
+        }
+        if old.typ != new.typ {
+            let destructor =
+                |msg: &Message| if msg.typ.is_some() { "destructor" } else { "regular" };
+            self.breaking(format!(
+                "changed from a {} message to a {} message",
+                destructor(old),
+                destructor(new)
+            ));
+        }
+        if old.deprecated_since.is_none() {
+            if let Some(deprecated_since) = new.deprecated_since {
+                self.compatible(format!("deprecated since version {}", deprecated_since));
+            }
+        }
+        if old.args.len() != new.args.len() {
+            self.breaking(format!(
+                "signature changed from `{}` to `{}`",
+                message_signature(old),
+                message_signature(new)
+            ));
+            return;
+        }
+        for (old_arg, new_arg) in old.args.iter().zip(&new.args) {
+            self.enter("arg", &old_arg.name);
+            if arg_type(old_arg) != arg_type(new_arg) {
+                self.breaking(format!(
+                    "type changed from `{}` to `{}`",
+                    arg_type(old_arg),
+                    arg_type(new_arg)
+                ));
+            }
+            if old_arg.name != new_arg.name {
+                self.compatible(format!("renamed to `{}`", new_arg.name));
+            }
+            self.leave();
+        }
+    }
+
+    fn diff_enum(&mut self, old: &Enum, new: &Enum, old_version: u32) {
+        if old.bitfield != new.bitfield {
+            self.breaking(if new.bitfield {
+                "changed to a bitfield"
+            } else {
+                "changed from a bitfield"
+            });
+        }
+        for old_entry in &old.entries {
+            self.enter("entry", &old_entry.name);
+            match new.entries.iter().find(|entry| entry.name == old_entry.name) {
+                Some(new_entry) if new_entry.value != old_entry.value => self.breaking(format!(
+                    "value changed from {} to {}",
+                    old_entry.value, new_entry.value
+                )),
+                Some(_) => {}
+                None => self.breaking("removed"),
+            }
+            self.leave();
+        }
+        for new_entry in &new.entries {
+            if old.entries.iter().any(|entry| entry.name == new_entry.name) {
+                continue;
+            }
+            self.enter("entry", &new_entry.name);
+            if u32::from(new_entry.since) > old_version {
+                self.compatible(format!("added with value {}", new_entry.value));
+            } else {
+                self.breaking(format!(
+                    "added with since={}, which is not greater than the previous version {}",
+                    new_entry.since, old_version
+                ));
+            }
+            self.leave();
+        }
+    }
+}
+
+/// Compare two versions of a protocol
+///
+/// Breaking changes are the ones that can make a client or a server written for the old version of the
+/// protocol misbehave when talking to a peer using the new version.
+pub fn diff(old: &Protocol, new: &Protocol) -> Vec<Change> {
+    let mut differ = Differ { path: Vec::new(), changes: Vec::new() };
+    for old_interface in &old.interfaces {
+        differ.enter("interface", &old_interface.name);
+        match new.interfaces.iter().find(|i| i.name == old_interface.name) {
+            Some(new_interface) => differ.diff_interface(old_interface, new_interface),
+            None => differ.breaking("removed"),
+        }
+        differ.leave();
+    }
+    for new_interface in &new.interfaces {
+        if !old.interfaces.iter().any(|i| i.name == new_interface.name) {
+            differ.enter("interface", &new_interface.name);
+            differ.compatible(format!("added with version {}", new_interface.version));
+            differ.leave();
+        }
+    }
+    differ.changes
+}
+
+#[cfg(test)]
+mod tests {
+    fn diff(old: &str, new: &str) -> Vec<String> {
+        let old = wayland_codegen::parse(old.as_bytes()).unwrap();
+        let new = wayland_codegen::parse(new.as_bytes()).unwrap();
+        super::diff(&old, &new).iter().map(ToString::to_string).collect()
+    }
+
+    const BASE: &str = r#"<protocol name="test">
+  <interface name="foo" version="1">
+    <request name="bar">
+      <arg name="x" type="uint" enum="mode"/>
+    </request>
+    <request name="baz"/>
+    <event name="done"/>
+    <enum name="mode">
+      <entry name="a" value="0"/>
+      <entry name="b" value="1"/>
+    </enum>
+  </interface>
+</protocol>"#;
+
+    #[test]
+    fn compatible_changes() {
+        assert!(diff(BASE, BASE).is_empty());
+
+        let new = BASE
+            .replace(r#"version="1""#, r#"version="2""#)
+            .replace(
+                r#"<event name="done"/>"#,
+                r#"<event name="done"/><event name="ready" since="2"/>"#,
+            )
+            .replace(
+                r#"<entry name="b" value="1"/>"#,
+                r#"<entry name="b" value="1"/><entry name="c" value="2" since="2"/>"#,
+            );
+        assert_eq!(
+            diff(BASE, &new),
+            [
+                "compatible: in `interface[foo]`: version increased from 1 to 2",
+                "compatible: in `interface[foo] > event[ready]`: added: ready() since 2",
+                "compatible: in `interface[foo] > enum[mode] > entry[c]`: added with value 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn breaking_changes() {
+        // reordered and removed requests
+        let new =
+            BASE.replace(r#"<request name="bar">"#, r#"<request name="qux"/><request name="bar">"#);
+        assert_eq!(
+            diff(BASE, &new),
+            [
+                "breaking: in `interface[foo] > request[bar]`: opcode changed from 0 to 1",
+                "breaking: in `interface[foo] > request[baz]`: opcode changed from 1 to 2",
+                "breaking: in `interface[foo] > request[qux]`: \
+                 added with since=1, which is not greater than the previous version 1",
+            ]
+        );
+        let new = BASE.replace(r#"<request name="baz"/>"#, "");
+        assert_eq!(diff(BASE, &new), ["breaking: in `interface[foo] > request[baz]`: removed"]);
+
+        // changed signature
+        let new = BASE.replace(r#"type="uint" enum="mode""#, r#"type="int""#);
+        assert_eq!(
+            diff(BASE, &new),
+            ["breaking: in `interface[foo] > request[bar] > arg[x]`: \
+              type changed from `uint<mode>` to `int`"]
+        );
+
+        // changed enum value
+        let new = BASE.replace(r#"value="1""#, r#"value="3""#);
+        assert_eq!(
+            diff(BASE, &new),
+            ["breaking: in `interface[foo] > enum[mode] > entry[b]`: value changed from 1 to 3"]
+        );
+
+        // new event without a version bump
+        let new =
+            BASE.replace(r#"<event name="done"/>"#, r#"<event name="done"/><event name="ready"/>"#);
+        assert_eq!(
+            diff(BASE, &new),
+            ["breaking: in `interface[foo] > event[ready]`: \
+              added with since=1, which is not greater than the previous version 1"]
+        );
+    }
+}
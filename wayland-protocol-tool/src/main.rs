@@ -0,0 +1,110 @@
+//! Command-line tool to validate, diff and summarize Wayland XML protocol files
+
+use std::{path::Path, process::ExitCode};
+
+use wayland_codegen::protocol::Protocol;
+
+mod diff;
+mod summary;
+
+const USAGE: &str = "\
+Usage: wayland-protocol-tool <command> <args>
+
+Commands:
+    validate <file>...    check that protocol files are valid, their references to other protocols
+                          must be to one of the given files, and report their warnings
+    diff <old> <new>      report backward-incompatible changes between two versions of a protocol
+    summary <file>...     print the interfaces and message signatures of protocol files";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.split_first() {
+        Some((command, files)) if command == "validate" && !files.is_empty() => validate(files),
+        Some((command, files)) if command == "diff" && files.len() == 2 => {
+            diff(&files[0], &files[1])
+        }
+        Some((command, files)) if command == "summary" && !files.is_empty() => summary(files),
+        Some((command, _)) if command == "-h" || command == "--help" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read(path: impl AsRef<Path>) -> Result<Vec<u8>, String> {
+    let path = path.as_ref();
+    std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+fn load(path: impl AsRef<Path>) -> Result<Protocol, String> {
+    let path = path.as_ref();
+    wayland_codegen::parse(&read(path)?).map_err(|e| format!("{}:{}", path.display(), e))
+}
+
+fn validate(files: &[String]) -> Result<bool, String> {
+    let mut valid = true;
+    // parse all the files first, so that their references can be resolved against each other
+    let mut parsed = Vec::new();
+    for file in files {
+        let result = read(file).and_then(|source| {
+            let protocol =
+                wayland_codegen::parse(&source).map_err(|e| format!("{}:{}", file, e))?;
+            Ok((file, source, protocol))
+        });
+        match result {
+            Ok(entry) => parsed.push(entry),
+            Err(e) => {
+                eprintln!("{}", e);
+                valid = false;
+            }
+        }
+    }
+    for (i, (file, source, _)) in parsed.iter().enumerate() {
+        let dependencies = parsed
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, (_, _, protocol))| protocol.clone())
+            .collect::<Vec<_>>();
+        match wayland_codegen::parse_with_dependencies(source, &dependencies) {
+            Ok(_) => {
+                for warning in wayland_codegen::warnings(source) {
+                    eprintln!("warning: {}:{}", file, warning);
+                }
+                println!("{}: ok", file)
+            }
+            Err(e) => {
+                eprintln!("{}:{}", file, e);
+                valid = false;
+            }
+        }
+    }
+    Ok(valid)
+}
+
+fn diff(old: &str, new: &str) -> Result<bool, String> {
+    let changes = diff::diff(&load(old)?, &load(new)?);
+    for change in &changes {
+        println!("{}", change);
+    }
+    Ok(!changes.iter().any(|change| change.breaking))
+}
+
+fn summary(files: &[String]) -> Result<bool, String> {
+    for file in files {
+        print!("{}", summary::summary(&load(file)?));
+    }
+    Ok(true)
+}
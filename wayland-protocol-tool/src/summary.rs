@@ -0,0 +1,107 @@
+use std::fmt::Write;
+
+use wayland_codegen::protocol::{Arg, Message, Protocol, Type};
+
+/// Summarize the interfaces of a protocol and the signatures of their messages
+pub fn summary(protocol: &Protocol) -> String {
+    let mut out = String::new();
+    writeln!(out, "protocol {}", protocol.name).unwrap();
+    for interface in &protocol.interfaces {
+        write!(out, "  interface {} v{}", interface.name, interface.version).unwrap();
+        if interface.frozen {
+            out.push_str(" (frozen)");
+        }
+        out.push('\n');
+        for (kind, messages) in [("request", &interface.requests), ("event", &interface.events)] {
+            for (opcode, message) in messages.iter().enumerate() {
+                writeln!(out, "    {} {}: {}", kind, opcode, message_signature(message)).unwrap();
+            }
+        }
+        for enu in &interface.enums {
+            let kind = if enu.bitfield { "bitfield" } else { "enum" };
+            let entries = enu
+                .entries
+                .iter()
+                .map(|entry| format!("{} = {}", entry.name, entry.value))
+                .collect::<Vec<_>>();
+            writeln!(out, "    {} {} {{ {} }}", kind, enu.name, entries.join(", ")).unwrap();
+        }
+    }
+    out
+}
+
+/// Signature of a message, like `attach(buffer: object<wl_buffer>?, x: int, y: int) since 1`
+pub fn message_signature(message: &Message) -> String {
+    let args = message.args.iter().map(arg_signature).collect::<Vec<_>>();
+    let mut signature = format!("{}({})", message.name, args.join(", "));
+    if message.typ == Some(Type::Destructor) {
+        signature.push_str(" destructor");
+    }
+    write!(signature, " since {}", message.since).unwrap();
+    if let Some(deprecated_since) = message.deprecated_since {
+        write!(signature, ", deprecated since {}", deprecated_since).unwrap();
+    }
+    signature
+}
+
+/// Signature of an argument, like `buffer: object<wl_buffer>?`
+pub fn arg_signature(arg: &Arg) -> String {
+    format!("{}: {}", arg.name, arg_type(arg))
+}
+
+/// Type of an argument, like `object<wl_buffer>?` or `uint<wl_output.transform>`
+pub fn arg_type(arg: &Arg) -> String {
+    let mut typ = match arg.typ {
+        Type::Int => "int",
+        Type::Uint => "uint",
+        Type::Fixed => "fixed",
+        Type::String => "string",
+        Type::Object => "object",
+        Type::NewId => "new_id",
+        Type::Array => "array",
+        Type::Fd => "fd",
+        Type::Destructor => "destructor",
+    }
+    .to_owned();
+    if let Some(target) = arg.interface.as_ref().or(arg.enum_.as_ref()) {
+        write!(typ, "<{}>", target).unwrap();
+    }
+    if arg.allow_null {
+        typ.push('?');
+    }
+    typ
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn summary() {
+        let protocol = wayland_codegen::parse(
+            br#"<protocol name="test">
+  <interface name="foo" version="2">
+    <request name="destroy" type="destructor"/>
+    <request name="set" since="2">
+      <arg name="target" type="object" interface="foo" allow-null="true"/>
+      <arg name="mode" type="uint" enum="mode"/>
+    </request>
+    <event name="done"/>
+    <enum name="mode">
+      <entry name="a" value="0"/>
+      <entry name="b" value="1"/>
+    </enum>
+  </interface>
+</protocol>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            super::summary(&protocol),
+            "protocol test
+  interface foo v2
+    request 0: destroy() destructor since 1
+    request 1: set(target: object<foo>?, mode: uint<mode>) since 2
+    event 0: done() since 1
+    enum mode { a = 0, b = 1 }
+"
+        );
+    }
+}
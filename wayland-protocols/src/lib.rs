@@ -36,6 +36,12 @@
 //! are still under that umbrella. We can expect them to be replaced by staging and stable protocols in the
 //! long term, but in the meantime you can enable them with the `unstable` cargo feature.
 //!
+//! ## Server helpers
+//!
+//! The cargo feature `server-helpers` enables the [`server_helpers`] module, which implements the
+//! bookkeeping and validation of some protocols for compositors, like the configure sequences of
+//! `xdg_shell`.
+//!
 //! ## Other protocols
 //!
 //! Additionally, more protocol extensions are provided here:
@@ -54,3 +60,12 @@ pub mod ext;
 pub mod wp;
 pub mod xdg;
 pub mod xwayland;
+
+#[cfg(feature = "server-helpers")]
+pub mod server_helpers;
+
+#[cfg(feature = "server-helpers")]
+#[doc(hidden)]
+pub mod __private {
+    pub use wayland_server;
+}
@@ -0,0 +1,6 @@
+//! Helpers for the server side of the protocols
+//!
+//! These modules implement the bookkeeping and validation of some protocols, which every compositor
+//! would otherwise have to reimplement. They are only available with the `server-helpers` cargo feature.
+
+pub mod xdg_shell;
@@ -0,0 +1,1085 @@
+//! State tracking of the `xdg_shell` protocol
+//!
+//! [`XdgShellState`] implements the `xdg_wm_base` global and tracks the state of its objects: the
+//! positioners, the roles of the `xdg_surface`s, their configure sequences, and the double-buffered state
+//! of toplevels and popups. It validates the requests of the clients and posts the protocol errors of
+//! `xdg_wm_base`, `xdg_positioner`, `xdg_surface`, `xdg_toplevel` and `xdg_popup` when they break the
+//! rules of the protocol.
+//!
+//! Your `State` needs to implement [`XdgShellHandler`] to be notified of the new toplevels and popups
+//! and of the requests that need a decision of the compositor (like moving or maximizing a window), and
+//! to delegate the interfaces of the protocol to [`XdgShellState`] using the
+//! [`delegate_xdg_shell!`](crate::delegate_xdg_shell) macro.
+//!
+//! As the buffers of the surfaces are managed by your `wl_surface` implementation, it needs to call
+//! [`XdgShellState::commit()`] on every commit, which applies the double-buffered state and enforces the
+//! initial commit rules: after creating a role object, the client must commit without a buffer, to which
+//! the compositor answers with a configure, and must acknowledge a configure before attaching a buffer.
+//!
+//! Configures are sent with [`XdgShellState::send_toplevel_configure()`] and
+//! [`XdgShellState::send_popup_configure()`], which track their serials. Once acknowledged by the client,
+//! a configure becomes the current one of the surface on its next commit. Only the last 32 configures
+//! of a surface are kept waiting for an acknowledgement, the acknowledgements of older ones are ignored.
+//!
+//! Clients can be checked for responsiveness with [`XdgShellState::ping()`] and
+//! [`XdgShellState::unresponsive()`].
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use wayland_server::{
+    backend::{ClientId, GlobalId, ObjectId},
+    protocol::wl_surface::WlSurface,
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+use crate::xdg::shell::server::{
+    xdg_popup::{self, XdgPopup},
+    xdg_positioner::{self, XdgPositioner},
+    xdg_surface::{self, XdgSurface},
+    xdg_toplevel::{self, XdgToplevel},
+    xdg_wm_base::{self, XdgWmBase},
+};
+
+/// The version of the `xdg_wm_base` global created by [`XdgShellState`]
+pub const XDG_WM_BASE_VERSION: u32 = 6;
+
+/// The maximum number of configures of a surface waiting for an acknowledgement
+///
+/// When a client does not acknowledge its configures, the oldest ones are forgotten.
+const MAX_PENDING_CONFIGURES: usize = 32;
+
+/// A rectangle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rectangle {
+    /// The x coordinate of the top-left corner
+    pub x: i32,
+    /// The y coordinate of the top-left corner
+    pub y: i32,
+    /// The width
+    pub width: i32,
+    /// The height
+    pub height: i32,
+}
+
+/// The state of a `xdg_positioner`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionerState {
+    /// The size of the popup, `(0, 0)` until it is set
+    pub size: (i32, i32),
+    /// The anchor rectangle, relative to the window geometry of the parent
+    pub anchor_rect: Option<Rectangle>,
+    /// The edge or corner of the anchor rectangle the popup is anchored to
+    pub anchor: xdg_positioner::Anchor,
+    /// The direction in which the popup is placed from its anchor
+    pub gravity: xdg_positioner::Gravity,
+    /// How the popup can be moved or resized if it would be constrained
+    pub constraint_adjustment: xdg_positioner::ConstraintAdjustment,
+    /// The offset of the popup from its anchor
+    pub offset: (i32, i32),
+    /// Whether the popup should be repositioned when its parent moves
+    pub reactive: bool,
+    /// The size of the parent the positioning is computed for
+    pub parent_size: Option<(i32, i32)>,
+    /// The serial of the configure of the parent the positioning is computed for
+    pub parent_configure: Option<u32>,
+}
+
+impl PositionerState {
+    /// Whether the size and the anchor rectangle are set, which is required to create a popup
+    pub fn is_complete(&self) -> bool {
+        self.size != (0, 0) && self.anchor_rect.is_some()
+    }
+}
+
+impl Default for PositionerState {
+    fn default() -> Self {
+        Self {
+            size: (0, 0),
+            anchor_rect: None,
+            anchor: xdg_positioner::Anchor::None,
+            gravity: xdg_positioner::Gravity::None,
+            constraint_adjustment: xdg_positioner::ConstraintAdjustment::empty(),
+            offset: (0, 0),
+            reactive: false,
+            parent_size: None,
+            parent_configure: None,
+        }
+    }
+}
+
+/// The state of a `xdg_toplevel`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ToplevelState {
+    /// The title of the window
+    pub title: Option<String>,
+    /// The application id of the window
+    pub app_id: Option<String>,
+    /// The parent of the window
+    pub parent: Option<XdgToplevel>,
+    /// The minimum size of the window geometry, `0` meaning no minimum
+    pub min_size: (i32, i32),
+    /// The maximum size of the window geometry, `0` meaning no maximum
+    pub max_size: (i32, i32),
+}
+
+/// The state of a `xdg_popup`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PopupState {
+    /// The parent of the popup, if it was given when creating the popup
+    pub parent: Option<XdgSurface>,
+    /// The positioner of the popup, updated by the `reposition` requests
+    pub positioner: PositionerState,
+    /// Whether the client requested an explicit grab for this popup
+    pub grabbed: bool,
+}
+
+/// A configure of a toplevel
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ToplevelConfigure {
+    /// The size of the window geometry, `(0, 0)` letting the client choose it
+    pub size: (i32, i32),
+    /// The states of the window
+    ///
+    /// The states not supported by the version of the toplevel are not sent, and removed from the
+    /// configure tracked by [`XdgShellState`].
+    pub states: Vec<xdg_toplevel::State>,
+    /// The bounds of the window, sent to the clients supporting version 4 of the toplevel
+    pub bounds: Option<(i32, i32)>,
+}
+
+/// A configure of a popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PopupConfigure {
+    /// The geometry of the popup, relative to the window geometry of its parent
+    pub geometry: Rectangle,
+    /// The token of the `reposition` request this configure answers
+    pub reposition_token: Option<u32>,
+}
+
+/// A configure sent to a `xdg_surface`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Configure {
+    /// The configure of a toplevel
+    Toplevel(ToplevelConfigure),
+    /// The configure of a popup
+    Popup(PopupConfigure),
+}
+
+/// The outcome of a commit of a surface, returned by [`XdgShellState::commit()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XdgCommit {
+    /// The surface has no `xdg_surface`, or its `xdg_surface` has no role object
+    Unmanaged,
+    /// This is the initial commit of the role object, the compositor must answer it with a configure
+    Initial,
+    /// The surface was mapped by this commit
+    Mapped,
+    /// The surface was unmapped by this commit, it needs a new initial commit before being mapped again
+    Unmapped,
+    /// The surface was already mapped or unmapped, and stays so
+    Unchanged,
+    /// The commit broke the rules of the protocol, the client is being disconnected
+    ProtocolError,
+}
+
+/// Handler for the events of [`XdgShellState`]
+pub trait XdgShellHandler: AsMut<XdgShellState> {
+    /// A client created a toplevel
+    ///
+    /// The compositor must send it a configure after its initial commit. This is the right time to send
+    /// the `wm_capabilities` event to the clients supporting version 5 of the toplevel.
+    fn new_toplevel(&mut self, toplevel: &XdgToplevel);
+
+    /// A client created a popup
+    ///
+    /// The compositor must send it a configure after its initial commit.
+    fn new_popup(&mut self, popup: &XdgPopup);
+
+    /// A client sent a request to a toplevel
+    ///
+    /// This is invoked for all the requests except `destroy`, once they have been validated and
+    /// recorded in the [`ToplevelState`].
+    fn toplevel_request(&mut self, _toplevel: &XdgToplevel, _request: xdg_toplevel::Request) {}
+
+    /// A client sent a request to a popup
+    ///
+    /// This is invoked for all the requests except `destroy`, once they have been validated and
+    /// recorded in the [`PopupState`].
+    fn popup_request(&mut self, _popup: &XdgPopup, _request: xdg_popup::Request) {}
+
+    /// A client acknowledged a configure, which will become current on its next commit
+    fn ack_configure(&mut self, _surface: &XdgSurface, _configure: Configure) {}
+
+    /// A toplevel was destroyed
+    fn toplevel_destroyed(&mut self, _toplevel: &XdgToplevel) {}
+
+    /// A popup was destroyed
+    fn popup_destroyed(&mut self, _popup: &XdgPopup) {}
+}
+
+#[derive(Debug)]
+enum Role {
+    None,
+    Toplevel(XdgToplevel),
+    Popup,
+}
+
+impl Role {
+    fn kind(&self) -> Option<RoleKind> {
+        match self {
+            Role::None => None,
+            Role::Toplevel(_) => Some(RoleKind::Toplevel),
+            Role::Popup => Some(RoleKind::Popup),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RoleKind {
+    Toplevel,
+    Popup,
+}
+
+#[derive(Debug)]
+struct SurfaceData {
+    wm_base: XdgWmBase,
+    wl_surface: WlSurface,
+    role: Role,
+    /// The kind of the role objects created for this surface, the next ones must be of the same kind
+    constructed: Option<RoleKind>,
+    /// The configures sent and not yet acknowledged, oldest first
+    pending_configures: Vec<(u32, Configure)>,
+    /// The serial of the newest configure forgotten without being acknowledged
+    forgotten_configure: Option<u32>,
+    acked_configure: Option<Configure>,
+    current_configure: Option<Configure>,
+    configured: bool,
+    initial_commit: bool,
+    mapped: bool,
+    pending_geometry: Option<Rectangle>,
+    geometry: Option<Rectangle>,
+}
+
+impl SurfaceData {
+    fn reset(&mut self) {
+        self.pending_configures.clear();
+        self.forgotten_configure = None;
+        self.acked_configure = None;
+        self.current_configure = None;
+        self.configured = false;
+        self.initial_commit = false;
+        self.mapped = false;
+    }
+}
+
+#[derive(Debug)]
+struct ToplevelData {
+    xdg_surface: XdgSurface,
+    state: ToplevelState,
+    pending_min_size: (i32, i32),
+    pending_max_size: (i32, i32),
+}
+
+#[derive(Debug)]
+struct PopupData {
+    xdg_surface: XdgSurface,
+    state: PopupState,
+}
+
+/// The state of the `xdg_wm_base` global
+///
+/// Your `State` needs to implement [`XdgShellHandler`], and delegate the interfaces of the protocol to
+/// this type using the [`delegate_xdg_shell!`](crate::delegate_xdg_shell) macro.
+#[derive(Debug)]
+pub struct XdgShellState {
+    global: GlobalId,
+    serial: u32,
+    wm_bases: HashMap<ObjectId, (XdgWmBase, Option<(u32, Instant)>)>,
+    positioners: HashMap<ObjectId, PositionerState>,
+    surfaces: HashMap<ObjectId, SurfaceData>,
+    xdg_surfaces: HashMap<ObjectId, XdgSurface>,
+    toplevels: HashMap<ObjectId, ToplevelData>,
+    popups: HashMap<ObjectId, PopupData>,
+}
+
+impl XdgShellState {
+    /// Create the `xdg_wm_base` global, with version [`XDG_WM_BASE_VERSION`]
+    pub fn new<D>(display: &DisplayHandle) -> Self
+    where
+        D: GlobalDispatch<XdgWmBase, ()>
+            + Dispatch<XdgWmBase, ()>
+            + Dispatch<XdgPositioner, ()>
+            + Dispatch<XdgSurface, ()>
+            + Dispatch<XdgToplevel, ()>
+            + Dispatch<XdgPopup, ()>
+            + XdgShellHandler
+            + 'static,
+    {
+        Self {
+            global: display.create_global::<D, XdgWmBase, ()>(XDG_WM_BASE_VERSION, ()),
+            serial: 0,
+            wm_bases: HashMap::new(),
+            positioners: HashMap::new(),
+            surfaces: HashMap::new(),
+            xdg_surfaces: HashMap::new(),
+            toplevels: HashMap::new(),
+            popups: HashMap::new(),
+        }
+    }
+
+    /// The id of the `xdg_wm_base` global
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+
+    /// The `xdg_surface` of a surface, if any
+    pub fn xdg_surface(&self, surface: &WlSurface) -> Option<XdgSurface> {
+        self.xdg_surfaces.get(&surface.id()).cloned()
+    }
+
+    /// The state of a positioner
+    pub fn positioner_state(&self, positioner: &XdgPositioner) -> Option<&PositionerState> {
+        self.positioners.get(&positioner.id())
+    }
+
+    /// The state of a toplevel
+    pub fn toplevel_state(&self, toplevel: &XdgToplevel) -> Option<&ToplevelState> {
+        self.toplevels.get(&toplevel.id()).map(|toplevel| &toplevel.state)
+    }
+
+    /// The state of a popup
+    pub fn popup_state(&self, popup: &XdgPopup) -> Option<&PopupState> {
+        self.popups.get(&popup.id()).map(|popup| &popup.state)
+    }
+
+    /// The current window geometry of a surface, if the client set one
+    pub fn window_geometry(&self, surface: &XdgSurface) -> Option<Rectangle> {
+        self.surfaces.get(&surface.id()).and_then(|surface| surface.geometry)
+    }
+
+    /// The current configure of a surface: the last one acknowledged before its last commit
+    pub fn current_configure(&self, surface: &XdgSurface) -> Option<&Configure> {
+        self.surfaces.get(&surface.id()).and_then(|surface| surface.current_configure.as_ref())
+    }
+
+    /// Whether a surface is mapped
+    pub fn is_mapped(&self, surface: &XdgSurface) -> bool {
+        self.surfaces.get(&surface.id()).map_or(false, |surface| surface.mapped)
+    }
+
+    fn next_serial(&mut self) -> u32 {
+        self.serial = self.serial.wrapping_add(1);
+        self.serial
+    }
+
+    /// Send a configure to a toplevel, returning its serial
+    ///
+    /// Returns `None` if the toplevel is not managed by this state.
+    pub fn send_toplevel_configure(
+        &mut self,
+        toplevel: &XdgToplevel,
+        mut configure: ToplevelConfigure,
+    ) -> Option<u32> {
+        let xdg_surface = self.toplevels.get(&toplevel.id())?.xdg_surface.clone();
+        let version = toplevel.version();
+
+        let mut states = Vec::new();
+        configure.states.retain(|&state| {
+            let since = match state {
+                xdg_toplevel::State::TiledLeft
+                | xdg_toplevel::State::TiledRight
+                | xdg_toplevel::State::TiledTop
+                | xdg_toplevel::State::TiledBottom => 2,
+                xdg_toplevel::State::Suspended => 6,
+                _ => 1,
+            };
+            let state = u32::from(state).to_ne_bytes();
+            let keep = version >= since && !states.chunks(4).any(|other| other == state);
+            if keep {
+                states.extend_from_slice(&state);
+            }
+            keep
+        });
+
+        if let Some((width, height)) = configure.bounds {
+            if version >= xdg_toplevel::EVT_CONFIGURE_BOUNDS_SINCE {
+                toplevel.configure_bounds(width, height);
+            }
+        }
+        toplevel.configure(configure.size.0, configure.size.1, states);
+        Some(self.send_configure(&xdg_surface, Configure::Toplevel(configure)))
+    }
+
+    /// Send a configure to a popup, returning its serial
+    ///
+    /// If the configure answers a `reposition` request, the `repositioned` event is sent first.
+    ///
+    /// Returns `None` if the popup is not managed by this state.
+    pub fn send_popup_configure(
+        &mut self,
+        popup: &XdgPopup,
+        configure: PopupConfigure,
+    ) -> Option<u32> {
+        let xdg_surface = self.popups.get(&popup.id())?.xdg_surface.clone();
+
+        if let Some(token) = configure.reposition_token {
+            if popup.version() >= xdg_popup::EVT_REPOSITIONED_SINCE {
+                popup.repositioned(token);
+            }
+        }
+        let geometry = configure.geometry;
+        popup.configure(geometry.x, geometry.y, geometry.width, geometry.height);
+        Some(self.send_configure(&xdg_surface, Configure::Popup(configure)))
+    }
+
+    fn send_configure(&mut self, xdg_surface: &XdgSurface, configure: Configure) -> u32 {
+        let serial = self.next_serial();
+        xdg_surface.configure(serial);
+        if let Some(surface) = self.surfaces.get_mut(&xdg_surface.id()) {
+            if surface.pending_configures.len() == MAX_PENDING_CONFIGURES {
+                let (forgotten, _) = surface.pending_configures.remove(0);
+                surface.forgotten_configure = Some(forgotten);
+            }
+            surface.pending_configures.push((serial, configure));
+        }
+        serial
+    }
+
+    /// Apply a commit of a surface
+    ///
+    /// This must be invoked by your `wl_surface` implementation on every commit, with whether the surface
+    /// has a buffer once the commit is applied. It applies the double-buffered state of the `xdg_surface`
+    /// and of its role object, and posts a protocol error if the client attached a buffer before
+    /// acknowledging a configure or set a minimum size larger than its maximum size.
+    pub fn commit(&mut self, surface: &WlSurface, has_buffer: bool) -> XdgCommit {
+        let xdg_surface = match self.xdg_surfaces.get(&surface.id()) {
+            Some(xdg_surface) => xdg_surface.clone(),
+            None => return XdgCommit::Unmanaged,
+        };
+        let data = match self.surfaces.get_mut(&xdg_surface.id()) {
+            Some(data) => data,
+            None => return XdgCommit::Unmanaged,
+        };
+
+        if has_buffer && !data.configured {
+            xdg_surface.post_error(
+                xdg_surface::Error::UnconfiguredBuffer,
+                "a buffer was attached before acknowledging a configure",
+            );
+            return XdgCommit::ProtocolError;
+        }
+
+        if let Role::Toplevel(ref toplevel) = data.role {
+            if let Some(toplevel_data) = self.toplevels.get_mut(&toplevel.id()) {
+                let (min, max) = (toplevel_data.pending_min_size, toplevel_data.pending_max_size);
+                if (max.0 != 0 && min.0 > max.0) || (max.1 != 0 && min.1 > max.1) {
+                    toplevel.post_error(
+                        xdg_toplevel::Error::InvalidSize,
+                        "the minimum size is larger than the maximum size",
+                    );
+                    return XdgCommit::ProtocolError;
+                }
+                toplevel_data.state.min_size = min;
+                toplevel_data.state.max_size = max;
+            }
+        } else if let Role::None = data.role {
+            return XdgCommit::Unmanaged;
+        }
+
+        if let Some(geometry) = data.pending_geometry.take() {
+            data.geometry = Some(geometry);
+        }
+        if let Some(configure) = data.acked_configure.take() {
+            data.current_configure = Some(configure);
+        }
+
+        if !data.initial_commit {
+            data.initial_commit = true;
+            if !has_buffer {
+                return XdgCommit::Initial;
+            }
+        }
+
+        match (data.mapped, has_buffer) {
+            (false, true) => {
+                data.mapped = true;
+                XdgCommit::Mapped
+            }
+            (true, false) => {
+                data.reset();
+                XdgCommit::Unmapped
+            }
+            _ => XdgCommit::Unchanged,
+        }
+    }
+
+    /// Send a ping to a client, returning its serial
+    ///
+    /// If a ping is already waiting for its pong, it is not sent again and its serial is returned.
+    ///
+    /// Returns `None` if the `xdg_wm_base` is not managed by this state.
+    pub fn ping(&mut self, wm_base: &XdgWmBase) -> Option<u32> {
+        let serial = self.serial.wrapping_add(1);
+        let (wm_base, ping) = self.wm_bases.get_mut(&wm_base.id())?;
+        if let Some((serial, _)) = *ping {
+            return Some(serial);
+        }
+        *ping = Some((serial, Instant::now()));
+        wm_base.ping(serial);
+        self.serial = serial;
+        Some(serial)
+    }
+
+    /// The `xdg_wm_base`s whose ping has not been answered within `timeout`
+    ///
+    /// The compositor can then notify the user, or disconnect the client by posting the
+    /// `xdg_wm_base.unresponsive` error.
+    pub fn unresponsive(&self, timeout: Duration) -> Vec<XdgWmBase> {
+        self.wm_bases
+            .values()
+            .filter(|(_, ping)| ping.map_or(false, |(_, sent)| sent.elapsed() >= timeout))
+            .map(|(wm_base, _)| wm_base.clone())
+            .collect()
+    }
+
+    /// Check that a positioner is complete, posting the `invalid_positioner` error otherwise
+    fn complete_positioner(
+        &self,
+        wm_base: &XdgWmBase,
+        positioner: &XdgPositioner,
+    ) -> Option<PositionerState> {
+        match self.positioners.get(&positioner.id()) {
+            Some(state) if state.is_complete() => Some(state.clone()),
+            _ => {
+                wm_base.post_error(
+                    xdg_wm_base::Error::InvalidPositioner,
+                    "the positioner has no size or anchor rectangle",
+                );
+                None
+            }
+        }
+    }
+}
+
+impl<D> GlobalDispatch<XdgWmBase, (), D> for XdgShellState
+where
+    D: GlobalDispatch<XdgWmBase, ()> + Dispatch<XdgWmBase, ()> + XdgShellHandler + 'static,
+{
+    fn bind(
+        state: &mut D,
+        _: &DisplayHandle,
+        _: &Client,
+        resource: New<XdgWmBase>,
+        _: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let wm_base = data_init.init(resource, ());
+        state.as_mut().wm_bases.insert(wm_base.id(), (wm_base, None));
+    }
+}
+
+impl<D> Dispatch<XdgWmBase, (), D> for XdgShellState
+where
+    D: Dispatch<XdgWmBase, ()>
+        + Dispatch<XdgPositioner, ()>
+        + Dispatch<XdgSurface, ()>
+        + XdgShellHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        wm_base: &XdgWmBase,
+        request: xdg_wm_base::Request,
+        _: &(),
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let shell = state.as_mut();
+        match request {
+            xdg_wm_base::Request::Destroy => {
+                if shell.surfaces.values().any(|surface| surface.wm_base == *wm_base) {
+                    wm_base.post_error(
+                        xdg_wm_base::Error::DefunctSurfaces,
+                        "xdg_wm_base was destroyed before its surfaces",
+                    );
+                }
+            }
+            xdg_wm_base::Request::CreatePositioner { id } => {
+                let positioner = data_init.init(id, ());
+                shell.positioners.insert(positioner.id(), PositionerState::default());
+            }
+            xdg_wm_base::Request::GetXdgSurface { id, surface } => {
+                if shell.xdg_surfaces.contains_key(&surface.id()) {
+                    wm_base.post_error(xdg_wm_base::Error::Role, "the surface already has a role");
+                    data_init.init(id, ());
+                    return;
+                }
+                let xdg_surface = data_init.init(id, ());
+                shell.xdg_surfaces.insert(surface.id(), xdg_surface.clone());
+                shell.surfaces.insert(
+                    xdg_surface.id(),
+                    SurfaceData {
+                        wm_base: wm_base.clone(),
+                        wl_surface: surface,
+                        role: Role::None,
+                        constructed: None,
+                        pending_configures: Vec::new(),
+                        forgotten_configure: None,
+                        acked_configure: None,
+                        current_configure: None,
+                        configured: false,
+                        initial_commit: false,
+                        mapped: false,
+                        pending_geometry: None,
+                        geometry: None,
+                    },
+                );
+            }
+            xdg_wm_base::Request::Pong { serial } => {
+                if let Some((_, ping)) = shell.wm_bases.get_mut(&wm_base.id()) {
+                    if matches!(*ping, Some((ping_serial, _)) if ping_serial == serial) {
+                        *ping = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, wm_base: &XdgWmBase, _: &()) {
+        state.as_mut().wm_bases.remove(&wm_base.id());
+    }
+}
+
+impl<D> Dispatch<XdgPositioner, (), D> for XdgShellState
+where
+    D: Dispatch<XdgPositioner, ()> + XdgShellHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        positioner: &XdgPositioner,
+        request: xdg_positioner::Request,
+        _: &(),
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, D>,
+    ) {
+        let positioner_state = match state.as_mut().positioners.get_mut(&positioner.id()) {
+            Some(positioner_state) => positioner_state,
+            None => return,
+        };
+        match request {
+            xdg_positioner::Request::SetSize { width, height } => {
+                if width <= 0 || height <= 0 {
+                    positioner.post_error(xdg_positioner::Error::InvalidInput, "invalid size");
+                    return;
+                }
+                positioner_state.size = (width, height);
+            }
+            xdg_positioner::Request::SetAnchorRect { x, y, width, height } => {
+                if width < 0 || height < 0 {
+                    positioner
+                        .post_error(xdg_positioner::Error::InvalidInput, "invalid anchor size");
+                    return;
+                }
+                positioner_state.anchor_rect = Some(Rectangle { x, y, width, height });
+            }
+            xdg_positioner::Request::SetAnchor { anchor } => match anchor {
+                WEnum::Value(anchor) => positioner_state.anchor = anchor,
+                WEnum::Unknown(_) => {
+                    positioner.post_error(xdg_positioner::Error::InvalidInput, "invalid anchor");
+                }
+            },
+            xdg_positioner::Request::SetGravity { gravity } => match gravity {
+                WEnum::Value(gravity) => positioner_state.gravity = gravity,
+                WEnum::Unknown(_) => {
+                    positioner.post_error(xdg_positioner::Error::InvalidInput, "invalid gravity");
+                }
+            },
+            xdg_positioner::Request::SetConstraintAdjustment { constraint_adjustment } => {
+                positioner_state.constraint_adjustment = match constraint_adjustment {
+                    WEnum::Value(adjustment) => adjustment,
+                    WEnum::Unknown(bits) => {
+                        xdg_positioner::ConstraintAdjustment::from_bits_truncate(bits)
+                    }
+                };
+            }
+            xdg_positioner::Request::SetOffset { x, y } => positioner_state.offset = (x, y),
+            xdg_positioner::Request::SetReactive => positioner_state.reactive = true,
+            xdg_positioner::Request::SetParentSize { parent_width, parent_height } => {
+                positioner_state.parent_size = Some((parent_width, parent_height));
+            }
+            xdg_positioner::Request::SetParentConfigure { serial } => {
+                positioner_state.parent_configure = Some(serial);
+            }
+            _ => {}
+        }
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, positioner: &XdgPositioner, _: &()) {
+        state.as_mut().positioners.remove(&positioner.id());
+    }
+}
+
+impl<D> Dispatch<XdgSurface, (), D> for XdgShellState
+where
+    D: Dispatch<XdgSurface, ()>
+        + Dispatch<XdgToplevel, ()>
+        + Dispatch<XdgPopup, ()>
+        + XdgShellHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        xdg_surface: &XdgSurface,
+        request: xdg_surface::Request,
+        _: &(),
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let shell = state.as_mut();
+        let data = match shell.surfaces.get_mut(&xdg_surface.id()) {
+            Some(data) => data,
+            None => {
+                // the client made a protocol error when creating this xdg_surface
+                match request {
+                    xdg_surface::Request::GetToplevel { id } => {
+                        data_init.init(id, ());
+                    }
+                    xdg_surface::Request::GetPopup { id, .. } => {
+                        data_init.init(id, ());
+                    }
+                    _ => {}
+                }
+                return;
+            }
+        };
+
+        match request {
+            xdg_surface::Request::Destroy => {
+                if !matches!(data.role, Role::None) {
+                    xdg_surface.post_error(
+                        xdg_surface::Error::DefunctRoleObject,
+                        "xdg_surface was destroyed before its role object",
+                    );
+                }
+            }
+            xdg_surface::Request::GetToplevel { id } => {
+                let toplevel = data_init.init(id, ());
+                if !matches!(data.role, Role::None) {
+                    xdg_surface.post_error(
+                        xdg_surface::Error::AlreadyConstructed,
+                        "xdg_surface already has a role object",
+                    );
+                    return;
+                }
+                if data.constructed.map_or(false, |kind| kind != RoleKind::Toplevel) {
+                    data.wm_base.post_error(
+                        xdg_wm_base::Error::Role,
+                        "the surface already has another role",
+                    );
+                    return;
+                }
+                data.role = Role::Toplevel(toplevel.clone());
+                data.constructed = data.role.kind();
+                shell.toplevels.insert(
+                    toplevel.id(),
+                    ToplevelData {
+                        xdg_surface: xdg_surface.clone(),
+                        state: ToplevelState::default(),
+                        pending_min_size: (0, 0),
+                        pending_max_size: (0, 0),
+                    },
+                );
+                state.new_toplevel(&toplevel);
+            }
+            xdg_surface::Request::GetPopup { id, parent, positioner } => {
+                let popup = data_init.init(id, ());
+                if !matches!(data.role, Role::None) {
+                    xdg_surface.post_error(
+                        xdg_surface::Error::AlreadyConstructed,
+                        "xdg_surface already has a role object",
+                    );
+                    return;
+                }
+                if data.constructed.map_or(false, |kind| kind != RoleKind::Popup) {
+                    data.wm_base.post_error(
+                        xdg_wm_base::Error::Role,
+                        "the surface already has another role",
+                    );
+                    return;
+                }
+                let wm_base = data.wm_base.clone();
+                let positioner = match shell.complete_positioner(&wm_base, &positioner) {
+                    Some(positioner) => positioner,
+                    None => return,
+                };
+                if let Some(ref parent) = parent {
+                    let has_role = shell
+                        .surfaces
+                        .get(&parent.id())
+                        .map_or(false, |parent| !matches!(parent.role, Role::None));
+                    if !has_role {
+                        wm_base.post_error(
+                            xdg_wm_base::Error::InvalidPopupParent,
+                            "the parent of the popup has no role",
+                        );
+                        return;
+                    }
+                }
+
+                // the borrow of the surface data ended to look at the positioner and the parent
+                if let Some(data) = shell.surfaces.get_mut(&xdg_surface.id()) {
+                    data.role = Role::Popup;
+                    data.constructed = data.role.kind();
+                }
+                shell.popups.insert(
+                    popup.id(),
+                    PopupData {
+                        xdg_surface: xdg_surface.clone(),
+                        state: PopupState { parent, positioner, grabbed: false },
+                    },
+                );
+                state.new_popup(&popup);
+            }
+            xdg_surface::Request::SetWindowGeometry { x, y, width, height } => {
+                if matches!(data.role, Role::None) {
+                    xdg_surface
+                        .post_error(xdg_surface::Error::NotConstructed, "xdg_surface has no role");
+                } else if width <= 0 || height <= 0 {
+                    xdg_surface
+                        .post_error(xdg_surface::Error::InvalidSize, "invalid window geometry");
+                } else {
+                    data.pending_geometry = Some(Rectangle { x, y, width, height });
+                }
+            }
+            xdg_surface::Request::AckConfigure { serial } => {
+                if matches!(data.role, Role::None) {
+                    xdg_surface
+                        .post_error(xdg_surface::Error::NotConstructed, "xdg_surface has no role");
+                    return;
+                }
+                let index = data.pending_configures.iter().position(|&(s, _)| s == serial);
+                let configure = match index {
+                    // acknowledging a configure also acknowledges the older ones
+                    Some(index) => data.pending_configures.drain(..=index).next_back().unwrap().1,
+                    // the state of a forgotten configure is unknown, the newer ones stay pending
+                    None if data.forgotten_configure.map_or(false, |forgotten| {
+                        forgotten.wrapping_sub(serial) < u32::MAX / 2
+                    }) =>
+                    {
+                        return;
+                    }
+                    None => {
+                        xdg_surface.post_error(
+                            xdg_surface::Error::InvalidSerial,
+                            format!("no pending configure with serial {}", serial),
+                        );
+                        return;
+                    }
+                };
+                data.acked_configure = Some(configure.clone());
+                data.configured = true;
+                state.ack_configure(xdg_surface, configure);
+            }
+        }
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, xdg_surface: &XdgSurface, _: &()) {
+        let shell = state.as_mut();
+        if let Some(data) = shell.surfaces.remove(&xdg_surface.id()) {
+            shell.xdg_surfaces.remove(&data.wl_surface.id());
+        }
+    }
+}
+
+impl<D> Dispatch<XdgToplevel, (), D> for XdgShellState
+where
+    D: Dispatch<XdgToplevel, ()> + XdgShellHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        toplevel: &XdgToplevel,
+        request: xdg_toplevel::Request,
+        _: &(),
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, D>,
+    ) {
+        let shell = state.as_mut();
+        if !shell.toplevels.contains_key(&toplevel.id()) {
+            return;
+        }
+
+        match request {
+            xdg_toplevel::Request::Destroy => return,
+            xdg_toplevel::Request::SetParent { ref parent } => {
+                // the parent must not be the toplevel itself or one of its descendants
+                let mut ancestor = parent.clone();
+                while let Some(current) = ancestor {
+                    if current == *toplevel {
+                        toplevel.post_error(
+                            xdg_toplevel::Error::InvalidParent,
+                            "the parent would create a loop",
+                        );
+                        return;
+                    }
+                    ancestor =
+                        shell.toplevel_state(&current).and_then(|state| state.parent.clone());
+                }
+                shell.toplevels.get_mut(&toplevel.id()).unwrap().state.parent = parent.clone();
+            }
+            xdg_toplevel::Request::SetTitle { ref title } => {
+                shell.toplevels.get_mut(&toplevel.id()).unwrap().state.title = Some(title.clone());
+            }
+            xdg_toplevel::Request::SetAppId { ref app_id } => {
+                shell.toplevels.get_mut(&toplevel.id()).unwrap().state.app_id =
+                    Some(app_id.clone());
+            }
+            xdg_toplevel::Request::SetMinSize { width, height } => {
+                if width < 0 || height < 0 {
+                    toplevel.post_error(xdg_toplevel::Error::InvalidSize, "negative minimum size");
+                    return;
+                }
+                shell.toplevels.get_mut(&toplevel.id()).unwrap().pending_min_size = (width, height);
+            }
+            xdg_toplevel::Request::SetMaxSize { width, height } => {
+                if width < 0 || height < 0 {
+                    toplevel.post_error(xdg_toplevel::Error::InvalidSize, "negative maximum size");
+                    return;
+                }
+                shell.toplevels.get_mut(&toplevel.id()).unwrap().pending_max_size = (width, height);
+            }
+            xdg_toplevel::Request::Resize { edges: WEnum::Unknown(_), .. } => {
+                toplevel.post_error(xdg_toplevel::Error::InvalidResizeEdge, "invalid resize edge");
+                return;
+            }
+            _ => {}
+        }
+        state.toplevel_request(toplevel, request);
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, toplevel: &XdgToplevel, _: &()) {
+        let shell = state.as_mut();
+        let data = match shell.toplevels.remove(&toplevel.id()) {
+            Some(data) => data,
+            None => return,
+        };
+        if let Some(surface) = shell.surfaces.get_mut(&data.xdg_surface.id()) {
+            surface.role = Role::None;
+            surface.reset();
+        }
+        // the children of the toplevel are reparented to its parent
+        for child in shell.toplevels.values_mut() {
+            if child.state.parent.as_ref() == Some(toplevel) {
+                child.state.parent = data.state.parent.clone();
+            }
+        }
+        state.toplevel_destroyed(toplevel);
+    }
+}
+
+impl<D> Dispatch<XdgPopup, (), D> for XdgShellState
+where
+    D: Dispatch<XdgPopup, ()> + XdgShellHandler + 'static,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        popup: &XdgPopup,
+        request: xdg_popup::Request,
+        _: &(),
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, D>,
+    ) {
+        let shell = state.as_mut();
+        let xdg_surface = match shell.popups.get(&popup.id()) {
+            Some(data) => data.xdg_surface.clone(),
+            None => return,
+        };
+        let (wm_base, mapped) = match shell.surfaces.get(&xdg_surface.id()) {
+            Some(surface) => (surface.wm_base.clone(), surface.mapped),
+            None => return,
+        };
+
+        match request {
+            xdg_popup::Request::Destroy => {
+                if shell
+                    .popups
+                    .values()
+                    .any(|child| child.state.parent.as_ref() == Some(&xdg_surface))
+                {
+                    wm_base.post_error(
+                        xdg_wm_base::Error::NotTheTopmostPopup,
+                        "the popup was destroyed before its child popups",
+                    );
+                }
+                return;
+            }
+            xdg_popup::Request::Grab { .. } => {
+                if mapped {
+                    popup.post_error(
+                        xdg_popup::Error::InvalidGrab,
+                        "the popup grab was requested after it was mapped",
+                    );
+                    return;
+                }
+                shell.popups.get_mut(&popup.id()).unwrap().state.grabbed = true;
+            }
+            xdg_popup::Request::Reposition { ref positioner, .. } => {
+                let positioner = match shell.complete_positioner(&wm_base, positioner) {
+                    Some(positioner) => positioner,
+                    None => return,
+                };
+                shell.popups.get_mut(&popup.id()).unwrap().state.positioner = positioner;
+            }
+        }
+        state.popup_request(popup, request);
+    }
+
+    fn destroyed(state: &mut D, _: ClientId, popup: &XdgPopup, _: &()) {
+        let shell = state.as_mut();
+        let data = match shell.popups.remove(&popup.id()) {
+            Some(data) => data,
+            None => return,
+        };
+        if let Some(surface) = shell.surfaces.get_mut(&data.xdg_surface.id()) {
+            surface.role = Role::None;
+            surface.reset();
+        }
+        state.popup_destroyed(popup);
+    }
+}
+
+/// Delegate the interfaces of the `xdg_shell` protocol to
+/// [`XdgShellState`](crate::server_helpers::xdg_shell::XdgShellState)
+///
+/// Your `State` also needs to implement
+/// [`XdgShellHandler`](crate::server_helpers::xdg_shell::XdgShellHandler).
+#[macro_export]
+macro_rules! delegate_xdg_shell {
+    ($(@< $( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+ >)? $ty: ty) => {
+        $crate::__private::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::xdg::shell::server::xdg_wm_base::XdgWmBase: ()] => $crate::server_helpers::xdg_shell::XdgShellState
+        );
+        $crate::__private::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::xdg::shell::server::xdg_wm_base::XdgWmBase: ()] => $crate::server_helpers::xdg_shell::XdgShellState
+        );
+        $crate::__private::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::xdg::shell::server::xdg_positioner::XdgPositioner: ()] => $crate::server_helpers::xdg_shell::XdgShellState
+        );
+        $crate::__private::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::xdg::shell::server::xdg_surface::XdgSurface: ()] => $crate::server_helpers::xdg_shell::XdgShellState
+        );
+        $crate::__private::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::xdg::shell::server::xdg_toplevel::XdgToplevel: ()] => $crate::server_helpers::xdg_shell::XdgShellState
+        );
+        $crate::__private::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::xdg::shell::server::xdg_popup::XdgPopup: ()] => $crate::server_helpers::xdg_shell::XdgShellState
+        );
+    };
+}
@@ -7,6 +7,9 @@
 //! Before trying to use this crate, you may check if the protocol extension you want to use
 //! is not already exposed in the `wayland-protocols` crate.
 //!
+//! If you prefer generating the code from a build script, for example to be able to inspect it or
+//! to avoid re-parsing the protocol files on every compilation, see the `wayland-codegen` crate.
+//!
 //! ## Example usage
 //!
 //! Below is a template for generating the code for a custom protocol client-side. Server-side
@@ -38,39 +41,125 @@
 
 use std::{ffi::OsString, path::PathBuf};
 
-mod c_interfaces;
-mod client_gen;
-mod common;
-mod interfaces;
-mod parse;
-mod protocol;
-mod server_gen;
+use wayland_codegen::{CodeKind, GenerateError, Options};
+
 mod token;
-mod util;
 
-/// Proc-macro for generating low-level interfaces associated with an XML specification
-#[proc_macro]
-pub fn generate_interfaces(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let path: OsString = token::parse_lit_str_token(stream).into();
-    let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
-        let mut buf = PathBuf::from(manifest_dir);
-        buf.push(path);
-        buf
-    } else {
-        path.into()
+/// Split the content of a group at its top-level commas
+fn split_list(group: proc_macro::Group) -> Vec<Vec<proc_macro::TokenTree>> {
+    let mut items = vec![Vec::new()];
+    for token in group.stream() {
+        match token {
+            proc_macro::TokenTree::Punct(ref p) if p.as_char() == ',' => items.push(Vec::new()),
+            token => items.last_mut().unwrap().push(token),
+        }
+    }
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+fn parse_name(key: &str, token: &proc_macro::TokenTree) -> Result<String, String> {
+    match token {
+        proc_macro::TokenTree::Ident(ident) => Ok(ident.to_string()),
+        proc_macro::TokenTree::Literal(_) if token.to_string().starts_with('"') => {
+            Ok(token::parse_lit_str_token(token.clone().into()))
+        }
+        _ => Err(format!("expected an interface name in `{}`, found `{}`", key, token)),
+    }
+}
+
+fn parse_bool(key: &str, value: proc_macro::TokenTree) -> Result<bool, String> {
+    match value.to_string().as_str() {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("expected `true` or `false` for `{}`, found `{}`", key, value)),
+    }
+}
+
+/// Parse a list of interface names: `[wl_foo, "wl_bar"]`
+fn parse_names(key: &str, value: proc_macro::TokenTree) -> Result<Vec<String>, String> {
+    let group = match value {
+        proc_macro::TokenTree::Group(group)
+            if group.delimiter() == proc_macro::Delimiter::Bracket =>
+        {
+            group
+        }
+        _ => return Err(format!("expected a list of interfaces for `{}`, found `{}`", key, value)),
     };
-    let file = match std::fs::File::open(&path) {
-        Ok(file) => file,
-        Err(e) => panic!("Failed to open protocol file {}: {}", path.display(), e),
+    split_list(group)
+        .into_iter()
+        .map(|item| match item.as_slice() {
+            [token] => parse_name(key, token),
+            _ => Err(format!(
+                "expected an interface name in `{}`, found `{}`",
+                key,
+                item.into_iter().collect::<proc_macro::TokenStream>()
+            )),
+        })
+        .collect()
+}
+
+/// Parse a map of interface names to module paths: `{ wl_foo => path::to::module }`
+fn parse_externs(value: proc_macro::TokenTree) -> Result<Vec<(String, String)>, String> {
+    let group = match value {
+        proc_macro::TokenTree::Group(group)
+            if group.delimiter() == proc_macro::Delimiter::Brace =>
+        {
+            group
+        }
+        _ => {
+            return Err(format!(
+                "expected `{{ interface => path, ... }}` for `extern`, found `{}`",
+                value
+            ))
+        }
     };
-    let protocol = parse::parse(file);
-    interfaces::generate(&protocol, true).into()
+    split_list(group)
+        .into_iter()
+        .map(|item| match item.as_slice() {
+            [name, proc_macro::TokenTree::Punct(eq), proc_macro::TokenTree::Punct(gt), path @ ..]
+                if eq.as_char() == '=' && gt.as_char() == '>' && !path.is_empty() =>
+            {
+                let path = path.iter().cloned().collect::<proc_macro::TokenStream>();
+                Ok((parse_name("extern", name)?, path.to_string()))
+            }
+            _ => Err(format!(
+                "expected `interface => path` in `extern`, found `{}`",
+                item.into_iter().collect::<proc_macro::TokenStream>()
+            )),
+        })
+        .collect()
 }
 
-/// Proc-macro for generating client-side API associated with an XML specification
-#[proc_macro]
-pub fn generate_client_code(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let path: OsString = token::parse_lit_str_token(stream).into();
+fn parse_options(
+    kind: CodeKind,
+    args: Vec<(String, proc_macro::TokenTree)>,
+) -> Result<Options, String> {
+    let mut options = Options::default();
+    for (key, value) in args {
+        match key.as_str() {
+            "handlers" if kind != CodeKind::Interfaces => {
+                options.handlers = parse_bool(&key, value)?
+            }
+            "serde" if kind != CodeKind::Interfaces => options.serde = parse_bool(&key, value)?,
+            "mock" if kind == CodeKind::Server => options.mock = parse_bool(&key, value)?,
+            "only" => options.only = Some(parse_names(&key, value)?),
+            "exclude" => options.exclude.extend(parse_names(&key, value)?),
+            "extern" => options.externs.extend(parse_externs(value)?),
+            _ => return Err(format!("unknown option `{}`", key)),
+        }
+    }
+    Ok(options)
+}
+
+fn generate(stream: proc_macro::TokenStream, kind: CodeKind) -> proc_macro::TokenStream {
+    let (path, options) = match token::parse_macro_args(stream)
+        .and_then(|(path, args)| Ok((path, parse_options(kind, args)?)))
+    {
+        Ok(args) => args,
+        Err(e) => return compile_error(&e),
+    };
+    let path: OsString = path.into();
     let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
         let mut buf = PathBuf::from(manifest_dir);
         buf.push(path);
@@ -78,64 +167,80 @@ pub fn generate_client_code(stream: proc_macro::TokenStream) -> proc_macro::Toke
     } else {
         path.into()
     };
-    let file = match std::fs::File::open(&path) {
-        Ok(file) => file,
-        Err(e) => panic!("Failed to open protocol file {}: {}", path.display(), e),
+    let source = match std::fs::read(&path) {
+        Ok(source) => source,
+        Err(e) => {
+            return compile_error(&format!(
+                "Failed to open protocol file {}: {}",
+                path.display(),
+                e
+            ))
+        }
     };
-    let protocol = parse::parse(file);
-    client_gen::generate_client_objects(&protocol).into()
+    match wayland_codegen::generate_code(&source, kind, &options) {
+        Ok(code) => code.into(),
+        Err(GenerateError::Protocol(e)) => {
+            compile_error(&format!("Invalid protocol file {}:{}", path.display(), e))
+        }
+        Err(GenerateError::Options(message)) => compile_error(&message),
+    }
 }
 
-/// Proc-macro for generating server-side API associated with an XML specification
+/// Report an error at the macro invocation
+fn compile_error(message: &str) -> proc_macro::TokenStream {
+    format!("compile_error!({:?});", message).parse().unwrap()
+}
+
+/// Proc-macro for generating low-level interfaces associated with an XML specification
+///
+/// The path of the XML file can be followed by the `only`, `exclude` and `extern` options, see
+/// [`generate_client_code!`].
 #[proc_macro]
-pub fn generate_server_code(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let path: OsString = token::parse_lit_str_token(stream).into();
-    let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
-        let mut buf = PathBuf::from(manifest_dir);
-        buf.push(path);
-        buf
-    } else {
-        path.into()
-    };
-    let file = match std::fs::File::open(&path) {
-        Ok(file) => file,
-        Err(e) => panic!("Failed to open protocol file {}: {}", path.display(), e),
-    };
-    let protocol = parse::parse(file);
-    server_gen::generate_server_objects(&protocol).into()
+pub fn generate_interfaces(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    generate(stream, CodeKind::Interfaces)
 }
 
-#[cfg(test)]
-fn format_rust_code(code: &str) -> String {
-    use std::{
-        io::Write,
-        process::{Command, Stdio},
-    };
-    if let Ok(mut proc) = Command::new("rustfmt")
-        .arg("--emit=stdout")
-        .arg("--edition=2018")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        //.stderr(Stdio::null())
-        .spawn()
-    {
-        {
-            let stdin = proc.stdin.as_mut().unwrap();
-            stdin.write_all(code.as_bytes()).unwrap();
-        }
-        if let Ok(output) = proc.wait_with_output() {
-            if output.status.success() {
-                return std::str::from_utf8(&output.stdout).unwrap().to_owned();
-            }
-        }
-    }
-    panic!("Rustfmt failed!");
+/// Proc-macro for generating client-side API associated with an XML specification
+///
+/// The path of the XML file can be followed by these options:
+///
+/// - `handlers = true`: generate a `Handler` trait in the module of each interface, with one method per
+///   event. The interface type implements `Dispatch` for any state implementing this trait, so you can use
+///   it with `delegate_dispatch!(State: [WlFoo: UserData] => WlFoo)`.
+/// - `serde = true`: implement `serde::Serialize` for the objects, enums, requests and events. Objects
+///   serialize as `interface@id` and file descriptors as a `"<fd>"` placeholder. This requires the `serde`
+///   feature of `wayland-client`.
+/// - `only = [wl_foo, wl_bar]`: only generate these interfaces.
+/// - `exclude = [wl_foo]`: generate all interfaces except these ones.
+/// - `extern = { wl_foo => path::to::module }`: do not generate these interfaces, but use the ones
+///   generated in another module instead. For `generate_client_code!` and `generate_server_code!`, this
+///   is the module containing the `wl_foo` module, and for `generate_interfaces!`, the module containing
+///   `WL_FOO_INTERFACE`.
+///
+/// An interface cannot refer to an interface of the same protocol file that is neither generated nor
+/// `extern`. This allows splitting a large protocol across several modules, or reusing interfaces that
+/// another crate already generated.
+#[proc_macro]
+pub fn generate_client_code(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    generate(stream, CodeKind::Client)
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
-enum Side {
-    /// wayland client applications
-    Client,
-    /// wayland compositors
-    Server,
+/// Proc-macro for generating server-side API associated with an XML specification
+///
+/// The path of the XML file can be followed by these options:
+///
+/// - `handlers = true`: generate a `Handler` trait in the module of each interface, with one method per
+///   request. The interface type implements `Dispatch` for any state implementing this trait, so you can
+///   use it with `delegate_dispatch!(State: [WlFoo: UserData] => WlFoo)`.
+/// - `serde = true`: implement `serde::Serialize` for the generated types, see [`generate_client_code!`].
+///   This requires the `serde` feature of `wayland-server`.
+/// - `mock = true`: generate a `mock` module, whose `MockState` implements `GlobalDispatch` and `Dispatch`
+///   for all the interfaces by recording the requests it receives and initializing the objects they
+///   create. This provides a server for tests without writing these implementations by hand. The
+///   objects of interfaces that are not generated, such as the ones of other protocols, are created with
+///   an object data recording their requests as `MockRequest::Foreign`.
+/// - `only`, `exclude` and `extern`: filter the generated interfaces, see [`generate_client_code!`].
+#[proc_macro]
+pub fn generate_server_code(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    generate(stream, CodeKind::Server)
 }
@@ -168,6 +168,40 @@ fn backslash_u(mut s: &str) -> (char, &str) {
 
 // End of code adapted from syn
 
+/// Parse the arguments of a macro: a string literal, optionally followed by `key = value` options
+pub fn parse_macro_args(
+    stream: proc_macro::TokenStream,
+) -> Result<(String, Vec<(String, proc_macro::TokenTree)>), String> {
+    let mut iter = stream.into_iter();
+    let path = match iter.next() {
+        Some(token) => parse_lit_str_token(token.into()),
+        None => return Err("expected string argument".into()),
+    };
+    let mut options = Vec::new();
+    loop {
+        match iter.next() {
+            None => break,
+            Some(proc_macro::TokenTree::Punct(p)) if p.as_char() == ',' => {}
+            Some(token) => return Err(format!("expected `,`, found `{}`", token)),
+        }
+        let key = match iter.next() {
+            // trailing comma
+            None => break,
+            Some(proc_macro::TokenTree::Ident(ident)) => ident.to_string(),
+            Some(token) => return Err(format!("expected an option name, found `{}`", token)),
+        };
+        match iter.next() {
+            Some(proc_macro::TokenTree::Punct(p)) if p.as_char() == '=' => {}
+            _ => return Err(format!("expected `=` after `{}`", key)),
+        }
+        match iter.next() {
+            Some(value) => options.push((key, value)),
+            None => return Err(format!("expected a value for `{}`", key)),
+        }
+    }
+    Ok((path, options))
+}
+
 pub fn parse_lit_str_token(mut stream: proc_macro::TokenStream) -> String {
     loop {
         let mut iter = stream.into_iter();
@@ -107,6 +107,32 @@ impl Client {
     pub fn kill(&self, handle: &DisplayHandle, error: ProtocolError) {
         handle.handle.kill_client(self.id.clone(), DisconnectReason::ProtocolError(error))
     }
+
+    /// Stop dispatching the requests of this client, without disconnecting it
+    ///
+    /// The requests it sends are kept buffered until it is resumed with [`Client::resume()`].
+    /// This is not supported by the system backend, on which this method does nothing. See
+    /// [`Handle::suspend_client()`](crate::backend::Handle::suspend_client) for details.
+    pub fn suspend(&self, handle: &DisplayHandle) -> Result<(), InvalidId> {
+        handle.handle.suspend_client(self.id.clone())
+    }
+
+    /// Resume dispatching the requests of this client after it was suspended
+    pub fn resume(&self, handle: &DisplayHandle) -> Result<(), InvalidId> {
+        handle.handle.resume_client(self.id.clone())
+    }
+
+    /// Check whether this client is currently suspended
+    pub fn is_suspended(&self, handle: &DisplayHandle) -> Result<bool, InvalidId> {
+        handle.handle.is_client_suspended(self.id.clone())
+    }
+
+    /// Retrieve the statistics of this client
+    ///
+    /// See [`Handle::client_stats()`](crate::backend::Handle::client_stats) for details.
+    pub fn stats(&self, handle: &DisplayHandle) -> Result<crate::backend::ClientStats, InvalidId> {
+        handle.handle.client_stats(self.id.clone())
+    }
 }
 
 impl PartialEq for Client {
@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::os::unix::io::OwnedFd;
 use std::sync::Arc;
 
@@ -6,7 +7,7 @@ use wayland_backend::{
     server::{ClientId, DisconnectReason, ObjectData, ObjectId},
 };
 
-use crate::{Client, DisplayHandle, Resource};
+use crate::{resource_states::remove_state, Client, DisplayHandle, Resource, ResourceStates};
 
 /// A trait which provides an implementation for handling a client's requests from a resource with some type
 /// of associated user data.
@@ -105,7 +106,8 @@ pub trait Dispatch<I: Resource, UserData, State = Self>: Sized {
     /// Note this type only provides an immutable reference to the user data, you will need to use
     /// interior mutability to change it.
     ///
-    /// Typically a [`Mutex`](std::sync::Mutex) would be used to have interior mutability.
+    /// Typically a [`Mutex`](std::sync::Mutex) would be used to have interior mutability. Alternatively,
+    /// mutable state can be stored in your `State` using a [`ResourceStates`] map.
     ///
     /// You are given the [`ObjectId`] and [`ClientId`] associated with the destroyed object for cleanup
     /// convenience.
@@ -124,6 +126,8 @@ pub trait Dispatch<I: Resource, UserData, State = Self>: Sized {
 #[derive(Debug)]
 pub struct ResourceData<I, U> {
     marker: std::marker::PhantomData<fn(I)>,
+    // removes the entry of this object from a `ResourceStates` map of the type-erased `State`
+    cleanup: Option<fn(&mut dyn Any, &ObjectId)>,
     /// The user-data associated with this object
     pub udata: U,
 }
@@ -143,6 +147,17 @@ impl<I> New<I> {
     }
 }
 
+/// Serializes as the created object
+#[cfg(feature = "serde")]
+impl<I: wayland_backend::serde::Serialize> wayland_backend::serde::Serialize for New<I> {
+    fn serialize<S: wayland_backend::serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.id.serialize(serializer)
+    }
+}
+
 /// Helper to initialize client-created objects
 ///
 /// This helper is provided to you in your [`Dispatch`] and [`GlobalDispatch`](super::GlobalDispatch) to
@@ -150,10 +165,22 @@ impl<I> New<I> {
 /// need to go this lower-level route).
 ///
 /// This step is mandatory, and **failing to initialize a newly created object will cause a panic**.
-#[derive(Debug)]
 pub struct DataInit<'a, D: 'static> {
     pub(crate) store: &'a mut Option<Arc<dyn ObjectData<D>>>,
     pub(crate) error: &'a mut Option<(u32, String)>,
+    pub(crate) post_init: &'a mut Option<PostInit<D>>,
+}
+
+// Applied to the `State` once the handler that initialized the object returns
+pub(crate) type PostInit<D> = Box<dyn FnOnce(&mut D)>;
+
+impl<'a, D> std::fmt::Debug for DataInit<'a, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataInit")
+            .field("store", &self.store)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a, D> DataInit<'a, D> {
@@ -173,6 +200,30 @@ impl<'a, D> DataInit<'a, D> {
         obj
     }
 
+    /// Initialize an object by assigning it its user-data and a mutable state
+    ///
+    /// The state is inserted in the [`ResourceStates`] map of your `State` once your handler returns, and
+    /// automatically removed from it when the object is destroyed.
+    pub fn init_with_state<I: Resource + 'static, U: Send + Sync + 'static, T: 'static>(
+        &mut self,
+        resource: New<I>,
+        data: U,
+        state: T,
+    ) -> I
+    where
+        D: Dispatch<I, U> + AsMut<ResourceStates<I, T>> + 'static,
+    {
+        let mut data = ResourceData::<I, _>::new(data);
+        data.cleanup = Some(remove_state::<D, I, T>);
+        let arc = Arc::new(data);
+        *self.store = Some(arc.clone() as Arc<_>);
+        let mut obj = resource.id;
+        obj.__set_object_data(arc);
+        let id = obj.id();
+        *self.post_init = Some(Box::new(move |data: &mut D| data.as_mut().insert_id(id, state)));
+        obj
+    }
+
     /// Set a custom [`ObjectData`] for this object
     ///
     /// This object data is not managed by `wayland-server`, as a result you will not
@@ -213,7 +264,7 @@ impl<'a, D> DataInit<'a, D> {
 
 impl<I, U> ResourceData<I, U> {
     pub(crate) fn new(udata: U) -> Self {
-        ResourceData { marker: std::marker::PhantomData, udata }
+        ResourceData { marker: std::marker::PhantomData, cleanup: None, udata }
     }
 }
 
@@ -260,6 +311,7 @@ impl<I: Resource + 'static, U: Send + Sync + 'static, D: Dispatch<I, U> + 'stati
         let udata = resource.data::<U>().expect("Wrong user_data value for object");
 
         let mut new_data = None;
+        let mut post_init = None;
 
         <D as Dispatch<I, U>>::request(
             data,
@@ -269,9 +321,13 @@ impl<I: Resource + 'static, U: Send + Sync + 'static, D: Dispatch<I, U> + 'stati
             udata,
             &dhandle,
             // The error is None since the creating object posts an error.
-            &mut DataInit { store: &mut new_data, error: &mut None },
+            &mut DataInit { store: &mut new_data, error: &mut None, post_init: &mut post_init },
         );
 
+        if let Some(post_init) = post_init {
+            post_init(data);
+        }
+
         new_data
     }
 
@@ -283,13 +339,17 @@ impl<I: Resource + 'static, U: Send + Sync + 'static, D: Dispatch<I, U> + 'stati
         object_id: ObjectId,
     ) {
         let dhandle = DisplayHandle::from(handle.clone());
-        let mut resource = I::from_id(&dhandle, object_id).unwrap();
+        let mut resource = I::from_id(&dhandle, object_id.clone()).unwrap();
 
         // Proxy::from_id will return an inert protocol object wrapper inside of ObjectData::destroyed,
         // therefore manually initialize the data associated with protocol object wrapper.
         resource.__set_object_data(self.clone());
 
-        <D as Dispatch<I, U>>::destroyed(data, client_id, &resource, &self.udata)
+        <D as Dispatch<I, U>>::destroyed(data, client_id, &resource, &self.udata);
+
+        if let Some(cleanup) = self.cleanup {
+            cleanup(data, &object_id);
+        }
     }
 }
 
@@ -5,8 +5,10 @@ use std::{
 };
 
 use wayland_backend::{
-    protocol::ObjectInfo,
-    server::{Backend, ClientData, GlobalId, Handle, InitError, InvalidId, ObjectId},
+    protocol::{same_interface, ObjectInfo},
+    server::{
+        Backend, ClientData, DispatchBudget, GlobalId, Handle, InitError, InvalidId, ObjectId,
+    },
 };
 
 use crate::{
@@ -59,6 +61,14 @@ impl<State: 'static> Display<State> {
         self.backend.dispatch_all_clients(state)
     }
 
+    /// Limit the amount of work done by each invocation of [`dispatch_clients()`](Display::dispatch_clients)
+    ///
+    /// This allows to prevent a single client from starving the others by flooding the server with requests.
+    /// See [`DispatchBudget`] for details.
+    pub fn set_dispatch_budget(&mut self, budget: DispatchBudget) {
+        self.backend.set_dispatch_budget(budget)
+    }
+
     /// Flush outgoing buffers into their respective sockets.
     pub fn flush_clients(&mut self) -> std::io::Result<()> {
         self.backend.flush(None)
@@ -182,6 +192,67 @@ impl DisplayHandle {
         self.handle.send_event(msg)
     }
 
+    /// Send an event to all the live resources of interface `I`
+    ///
+    /// The `filter` closure is invoked for each resource with its owning [`Client`], and allows to restrict
+    /// the broadcast to some clients or resources. For each resource it accepted, the `event` closure is
+    /// invoked to create the event to send to it. If the version of the resource is lower than the `since`
+    /// version of this event, it is silently skipped.
+    ///
+    /// Returns the number of resources the event was actually sent to.
+    pub fn broadcast<'a, I: Resource + 'static>(
+        &self,
+        mut filter: impl FnMut(&Client, &I) -> bool,
+        mut event: impl FnMut(&I) -> I::Event<'a>,
+    ) -> usize {
+        // The backend lock is held while iterating, so collect the ids before sending anything
+        let mut clients = Vec::new();
+        self.handle.with_all_clients(|id| clients.push(id));
+
+        let mut sent = 0;
+        for client_id in clients {
+            let client = match Client::from_id(self, client_id.clone()) {
+                Ok(client) => client,
+                Err(InvalidId) => continue,
+            };
+            let mut ids = Vec::new();
+            let _ = self.handle.with_all_objects_for(client_id, |id| {
+                if same_interface(id.interface(), I::interface()) {
+                    ids.push(id);
+                }
+            });
+
+            for id in ids {
+                let resource = match I::from_id(self, id) {
+                    Ok(resource) => resource,
+                    Err(InvalidId) => continue,
+                };
+                if !filter(&client, &resource) {
+                    continue;
+                }
+                let msg = match resource.write_event(self, event(&resource)) {
+                    Ok(msg) => msg,
+                    Err(InvalidId) => continue,
+                };
+                let since = I::interface().events.get(msg.opcode as usize).map(|desc| desc.since);
+                if since.map(|since| resource.version() < since).unwrap_or(false) {
+                    continue;
+                }
+                if self.handle.send_event(msg.map_fd(|fd| fd.as_raw_fd())).is_ok() {
+                    sent += 1;
+                }
+            }
+        }
+        sent
+    }
+
+    /// Retrieve a snapshot of the statistics of all connected clients
+    ///
+    /// See [`Handle::stats()`] for details.
+    pub fn stats(&self) -> crate::backend::ServerStats {
+        self.handle.stats()
+    }
+
     /// Trigger a protocol error on this object
     ///
     /// This is intended to be a low-level method. See [`Resource::post_error()`], for a more convenient
@@ -38,6 +38,7 @@ impl<I: Resource + 'static, U: Send + Sync + 'static, D: GlobalDispatch<I, U> +
 
         let mut new_data = None;
         let mut protocol_error = None;
+        let mut post_init = None;
 
         <D as GlobalDispatch<I, U>>::bind(
             data,
@@ -45,9 +46,17 @@ impl<I: Resource + 'static, U: Send + Sync + 'static, D: GlobalDispatch<I, U> +
             &client,
             New::wrap(resource.clone()),
             &self.data,
-            &mut DataInit { store: &mut new_data, error: &mut protocol_error },
+            &mut DataInit {
+                store: &mut new_data,
+                error: &mut protocol_error,
+                post_init: &mut post_init,
+            },
         );
 
+        if let Some(post_init) = post_init {
+            post_init(data);
+        }
+
         match new_data {
             Some(data) => data,
             None => match protocol_error {
@@ -42,6 +42,19 @@
 //! those messages are printed to stderr. If you activate the `log` cargo feature, they will instead be
 //! piped through the `log` crate.
 //!
+//! ## Serialization
+//!
+//! If you activate the `serde` cargo feature, the objects, enums, requests and events of the
+//! [`protocol`] module implement `serde::Serialize`, which can be used to record or inspect the messages
+//! of a connection. Objects are serialized as `interface@id` (like `wl_surface@3`) and file
+//! descriptors as a `"<fd>"` placeholder. Code generated by `wayland-scanner` with its `serde = true`
+//! option also requires this feature.
+//!
+//! ## Shared memory buffers
+//!
+//! If you activate the `shm` cargo feature, the `shm` module provides a ready implementation of the
+//! `wl_shm` global, which maps the pools of the clients and gives access to the content of their buffers.
+//!
 //! ## Advanced use
 //!
 //! ### Bypassing [`Dispatch`]
@@ -89,20 +102,29 @@ mod client;
 mod dispatch;
 mod display;
 mod global;
+mod resource_states;
 mod socket;
 
+#[cfg(feature = "shm")]
+pub mod shm;
+
 pub use client::Client;
 pub use dispatch::{DataInit, Dispatch, New, ResourceData};
 pub use display::{Display, DisplayHandle};
 pub use global::GlobalDispatch;
+pub use resource_states::ResourceStates;
 pub use socket::{BindError, ListeningSocket};
 
 /// Backend reexports
 pub mod backend {
     pub use wayland_backend::protocol;
+    #[cfg(feature = "serde")]
+    #[doc(hidden)]
+    pub use wayland_backend::serde;
     pub use wayland_backend::server::{
-        Backend, ClientData, ClientId, Credentials, DisconnectReason, GlobalHandler, GlobalId,
-        Handle, InitError, InvalidId, ObjectData, ObjectId, WeakHandle,
+        Backend, ClientData, ClientId, ClientStats, Credentials, DisconnectReason, DispatchBudget,
+        GlobalHandler, GlobalId, Handle, InitError, InvalidId, ObjectData, ObjectId, ServerStats,
+        WeakHandle,
     };
     pub use wayland_backend::smallvec;
 }
@@ -120,7 +142,10 @@ pub mod protocol {
     pub mod __interfaces {
         wayland_scanner::generate_interfaces!("wayland.xml");
     }
-    wayland_scanner::generate_server_code!("wayland.xml");
+    #[cfg(not(feature = "serde"))]
+    wayland_scanner::generate_server_code!("wayland.xml", handlers = true);
+    #[cfg(feature = "serde")]
+    wayland_scanner::generate_server_code!("wayland.xml", handlers = true, serde = true);
 }
 
 // internal imports for dispatching logging depending on the `log` feature
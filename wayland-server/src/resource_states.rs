@@ -0,0 +1,139 @@
+use std::{any::Any, collections::HashMap, fmt};
+
+use wayland_backend::server::ObjectId;
+
+use crate::Resource;
+
+/// Typed storage for mutable per-resource state
+///
+/// The user data of a resource is only ever accessible through a shared reference, which requires
+/// interior mutability to change it. As an alternative, this map can be stored in your `State` to
+/// associate some state of type `T` to resources of interface `I`, which you can then mutate from your
+/// handlers through the `&mut State` they are given.
+///
+/// Resources initialized using [`DataInit::init_with_state()`](crate::DataInit::init_with_state) are
+/// automatically inserted in the map, and removed from it once they are destroyed, right after
+/// [`Dispatch::destroyed()`](crate::Dispatch::destroyed) is invoked. To find the map, this requires your
+/// `State` to implement `AsMut<ResourceStates<I, T>>`.
+///
+/// ```
+/// use wayland_server::{protocol::wl_region, DataInit, ResourceStates};
+///
+/// #[derive(Default)]
+/// struct RegionState {
+///     rects: Vec<(i32, i32, i32, i32)>,
+/// }
+///
+/// struct State {
+///     regions: ResourceStates<wl_region::WlRegion, RegionState>,
+/// }
+///
+/// impl AsMut<ResourceStates<wl_region::WlRegion, RegionState>> for State {
+///     fn as_mut(&mut self) -> &mut ResourceStates<wl_region::WlRegion, RegionState> {
+///         &mut self.regions
+///     }
+/// }
+///
+/// impl wayland_server::Dispatch<wl_region::WlRegion, ()> for State {
+///     fn request(
+///         state: &mut Self,
+///         _client: &wayland_server::Client,
+///         resource: &wl_region::WlRegion,
+///         request: wl_region::Request,
+///         _data: &(),
+///         _dhandle: &wayland_server::DisplayHandle,
+///         _data_init: &mut DataInit<'_, Self>,
+///     ) {
+///         if let wl_region::Request::Add { x, y, width, height } = request {
+///             if let Some(region) = state.regions.get_mut(resource) {
+///                 region.rects.push((x, y, width, height));
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub struct ResourceStates<I, T> {
+    states: HashMap<ObjectId, T>,
+    _marker: std::marker::PhantomData<fn(I)>,
+}
+
+impl<I: Resource, T> ResourceStates<I, T> {
+    /// Create a new empty map
+    pub fn new() -> Self {
+        ResourceStates { states: HashMap::new(), _marker: std::marker::PhantomData }
+    }
+
+    /// Insert the state of a resource, returning its previous state if any
+    ///
+    /// **Note:** Unlike the ones inserted by [`DataInit::init_with_state()`](crate::DataInit::init_with_state),
+    /// states inserted with this method are not automatically removed when the resource is destroyed.
+    pub fn insert(&mut self, resource: &I, state: T) -> Option<T> {
+        self.states.insert(resource.id(), state)
+    }
+
+    /// Access the state of a resource
+    pub fn get(&self, resource: &I) -> Option<&T> {
+        self.states.get(&resource.id())
+    }
+
+    /// Mutably access the state of a resource
+    pub fn get_mut(&mut self, resource: &I) -> Option<&mut T> {
+        self.states.get_mut(&resource.id())
+    }
+
+    /// Remove the state of a resource, returning it if any
+    pub fn remove(&mut self, resource: &I) -> Option<T> {
+        self.states.remove(&resource.id())
+    }
+
+    /// Check whether the map contains a state for this resource
+    pub fn contains(&self, resource: &I) -> bool {
+        self.states.contains_key(&resource.id())
+    }
+
+    /// Number of states stored in the map
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Whether the map is empty
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Iterate over the stored states and the ids of their resources
+    pub fn iter(&self) -> impl Iterator<Item = (&ObjectId, &T)> {
+        self.states.iter()
+    }
+
+    /// Iterate mutably over the stored states and the ids of their resources
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ObjectId, &mut T)> {
+        self.states.iter_mut()
+    }
+
+    pub(crate) fn insert_id(&mut self, id: ObjectId, state: T) {
+        self.states.insert(id, state);
+    }
+}
+
+impl<I: Resource, T> Default for ResourceStates<I, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, T: fmt::Debug> fmt::Debug for ResourceStates<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.states.iter()).finish()
+    }
+}
+
+/// Removes the state of a destroyed object from the map stored in the type-erased `State`
+pub(crate) fn remove_state<D, I, T>(data: &mut dyn Any, id: &ObjectId)
+where
+    D: AsMut<ResourceStates<I, T>> + 'static,
+{
+    if let Some(data) = data.downcast_mut::<D>() {
+        data.as_mut().states.remove(id);
+    }
+}
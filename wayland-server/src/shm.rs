@@ -0,0 +1,541 @@
+//! An implementation of the `wl_shm` global
+//!
+//! [`ShmState`] implements the `wl_shm`, `wl_shm_pool` and `wl_buffer` interfaces for shared memory
+//! buffers: it advertises the supported formats, maps the pools, validates the buffers created by the
+//! clients and posts the protocol errors of `wl_shm`. The content of the buffers can then be read with
+//! [`with_buffer_contents()`].
+//!
+//! Clients can truncate the file of a pool after creating it, which would make the server crash with a
+//! `SIGBUS` signal when reading their buffers. Like `libwayland-server`, this module installs a handler for
+//! this signal while a buffer is being read: the faulting pool is replaced by zeroes, the client is
+//! disconnected with an `invalid_fd` error and [`with_buffer_contents()`] returns
+//! [`BufferAccessError::BadMap`]. The handler forwards the signals it does not handle to the previously
+//! installed one.
+//!
+//! This module is only available with the `shm` cargo feature.
+//!
+//! ```
+//! use wayland_server::{delegate_shm, shm::ShmState, protocol::wl_shm::Format};
+//!
+//! struct State {
+//!     shm: ShmState,
+//! }
+//!
+//! impl AsMut<ShmState> for State {
+//!     fn as_mut(&mut self) -> &mut ShmState {
+//!         &mut self.shm
+//!     }
+//! }
+//!
+//! delegate_shm!(State);
+//!
+//! let display = wayland_server::Display::<State>::new().unwrap();
+//! // Argb8888 and Xrgb8888 are always supported
+//! let shm = ShmState::new::<State>(&display.handle(), [Format::Rgb565]);
+//! let state = State { shm };
+//! ```
+
+use std::{
+    cell::Cell,
+    ffi::c_void,
+    fmt,
+    os::unix::io::{AsFd, OwnedFd},
+    ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Arc, Once, RwLock,
+    },
+};
+
+use rustix::mm::{mmap, mmap_anonymous, munmap, MapFlags, ProtFlags};
+use wayland_backend::{protocol::wl_shm_bytes_per_pixel, server::GlobalId};
+
+use crate::{
+    protocol::{
+        wl_buffer::{self, WlBuffer},
+        wl_shm::{self, Format, WlShm},
+        wl_shm_pool::{self, WlShmPool},
+    },
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+/// The state of the `wl_shm` global
+///
+/// Your `State` needs to implement `AsMut<ShmState>`, and delegate the `wl_shm`, `wl_shm_pool` and
+/// `wl_buffer` interfaces to it using the [`delegate_shm!`](crate::delegate_shm) macro.
+#[derive(Debug)]
+pub struct ShmState {
+    formats: Vec<Format>,
+    global: GlobalId,
+}
+
+impl ShmState {
+    /// Create the `wl_shm` global, supporting the given formats in addition to `Argb8888` and `Xrgb8888`
+    pub fn new<D>(display: &DisplayHandle, formats: impl IntoIterator<Item = Format>) -> Self
+    where
+        D: GlobalDispatch<WlShm, ()>
+            + Dispatch<WlShm, ()>
+            + Dispatch<WlShmPool, ShmPoolData>
+            + Dispatch<WlBuffer, ShmBufferData>
+            + AsMut<ShmState>
+            + 'static,
+    {
+        let mut all_formats = vec![Format::Argb8888, Format::Xrgb8888];
+        for format in formats {
+            if !all_formats.contains(&format) {
+                all_formats.push(format);
+            }
+        }
+        let global = display.create_global::<D, WlShm, ()>(1, ());
+        Self { formats: all_formats, global }
+    }
+
+    /// The id of the `wl_shm` global
+    pub fn global(&self) -> GlobalId {
+        self.global.clone()
+    }
+
+    /// The supported formats
+    pub fn formats(&self) -> &[Format] {
+        &self.formats
+    }
+}
+
+impl<D> GlobalDispatch<WlShm, (), D> for ShmState
+where
+    D: GlobalDispatch<WlShm, ()> + Dispatch<WlShm, ()> + AsMut<ShmState> + 'static,
+{
+    fn bind(
+        state: &mut D,
+        _: &DisplayHandle,
+        _: &Client,
+        resource: New<WlShm>,
+        _: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let shm = data_init.init(resource, ());
+        for &format in &state.as_mut().formats {
+            shm.format(format);
+        }
+    }
+}
+
+impl<D> Dispatch<WlShm, (), D> for ShmState
+where
+    D: Dispatch<WlShm, ()> + Dispatch<WlShmPool, ShmPoolData> + AsMut<ShmState> + 'static,
+{
+    fn request(
+        _: &mut D,
+        _: &Client,
+        _: &WlShm,
+        request: wl_shm::Request,
+        _: &(),
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        let wl_shm::Request::CreatePool { id, fd, size } = request;
+        if size <= 0 {
+            data_init.post_error(id, wl_shm::Error::InvalidStride, "invalid size for the pool");
+            return;
+        }
+        match Pool::new(fd, size as usize) {
+            Ok(pool) => {
+                data_init.init(id, ShmPoolData { pool: Arc::new(pool) });
+            }
+            Err(_) => {
+                data_init.post_error(id, wl_shm::Error::InvalidFd, "failed to map the pool");
+            }
+        }
+    }
+}
+
+impl<D> Dispatch<WlShmPool, ShmPoolData, D> for ShmState
+where
+    D: Dispatch<WlShmPool, ShmPoolData>
+        + Dispatch<WlBuffer, ShmBufferData>
+        + AsMut<ShmState>
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _: &Client,
+        pool: &WlShmPool,
+        request: wl_shm_pool::Request,
+        data: &ShmPoolData,
+        _: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wl_shm_pool::Request::CreateBuffer { id, offset, width, height, stride, format } => {
+                let format = match format {
+                    WEnum::Value(format) if state.as_mut().formats.contains(&format) => format,
+                    _ => {
+                        pool.post_error(wl_shm::Error::InvalidFormat, "unsupported format");
+                        data_init.custom_init(id, Arc::new(InertBufferData));
+                        return;
+                    }
+                };
+                let metadata = BufferMetadata { offset, width, height, stride, format };
+                if !metadata.is_valid(data.pool.len()) {
+                    pool.post_error(
+                        wl_shm::Error::InvalidStride,
+                        format!("invalid buffer size, stride or offset: {:?}", metadata),
+                    );
+                    data_init.custom_init(id, Arc::new(InertBufferData));
+                    return;
+                }
+                data_init.init(id, ShmBufferData { pool: data.pool.clone(), metadata });
+            }
+            wl_shm_pool::Request::Resize { size } => {
+                if size <= 0 || (size as usize) < data.pool.len() {
+                    pool.post_error(wl_shm::Error::InvalidStride, "pools cannot shrink");
+                } else if data.pool.resize(size as usize).is_err() {
+                    pool.post_error(wl_shm::Error::InvalidFd, "failed to map the pool");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<WlBuffer, ShmBufferData, D> for ShmState
+where
+    D: Dispatch<WlBuffer, ShmBufferData> + 'static,
+{
+    fn request(
+        _: &mut D,
+        _: &Client,
+        _: &WlBuffer,
+        _: wl_buffer::Request,
+        _: &ShmBufferData,
+        _: &DisplayHandle,
+        _: &mut DataInit<'_, D>,
+    ) {
+    }
+}
+
+/// Delegate the `wl_shm`, `wl_shm_pool` and `wl_buffer` interfaces to [`ShmState`](crate::shm::ShmState)
+///
+/// Your `State` also needs to implement `AsMut<ShmState>`.
+#[macro_export]
+macro_rules! delegate_shm {
+    ($(@< $( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+ >)? $ty: ty) => {
+        $crate::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::protocol::wl_shm::WlShm: ()] => $crate::shm::ShmState
+        );
+        $crate::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::protocol::wl_shm::WlShm: ()] => $crate::shm::ShmState
+        );
+        $crate::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::protocol::wl_shm_pool::WlShmPool: $crate::shm::ShmPoolData] => $crate::shm::ShmState
+        );
+        $crate::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty:
+            [$crate::protocol::wl_buffer::WlBuffer: $crate::shm::ShmBufferData] => $crate::shm::ShmState
+        );
+    };
+}
+
+/// The user data of `wl_shm_pool` objects
+#[derive(Debug)]
+pub struct ShmPoolData {
+    pool: Arc<Pool>,
+}
+
+/// The user data of shared memory `wl_buffer` objects
+#[derive(Debug)]
+pub struct ShmBufferData {
+    pool: Arc<Pool>,
+    metadata: BufferMetadata,
+}
+
+impl ShmBufferData {
+    /// The metadata of the buffer
+    pub fn metadata(&self) -> BufferMetadata {
+        self.metadata
+    }
+}
+
+/// The layout of a shared memory buffer in its pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferMetadata {
+    /// The offset of the buffer in its pool, in bytes
+    pub offset: i32,
+    /// The width of the buffer, in pixels
+    pub width: i32,
+    /// The height of the buffer, in pixels
+    pub height: i32,
+    /// The length of a row of the buffer, in bytes
+    pub stride: i32,
+    /// The pixel format of the buffer
+    pub format: Format,
+}
+
+impl BufferMetadata {
+    /// Whether the buffer has a valid size and fits in a pool of `pool_len` bytes
+    fn is_valid(&self, pool_len: usize) -> bool {
+        if self.offset < 0 || self.width <= 0 || self.height <= 0 {
+            return false;
+        }
+        let bytes_per_pixel = wl_shm_bytes_per_pixel(self.format.into()).unwrap_or(1);
+        let min_stride = self.width as i64 * bytes_per_pixel as i64;
+        let end = self.offset as i64 + self.stride as i64 * self.height as i64;
+        self.stride as i64 >= min_stride && end <= pool_len as i64
+    }
+
+    /// The length of the buffer in bytes
+    fn len(&self) -> usize {
+        self.stride as usize * self.height as usize
+    }
+}
+
+/// An error that occurred while reading a shared memory buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferAccessError {
+    /// The buffer is not a shared memory buffer created by [`ShmState`]
+    NotShm,
+    /// The file of the pool was truncated by the client, which has been disconnected
+    BadMap,
+}
+
+impl std::error::Error for BufferAccessError {}
+
+impl fmt::Display for BufferAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BufferAccessError::NotShm => f.write_str("The buffer is not a shared memory buffer"),
+            BufferAccessError::BadMap => {
+                f.write_str("The shared memory of the buffer was truncated by the client")
+            }
+        }
+    }
+}
+
+/// Read the content of a shared memory buffer
+///
+/// The closure is given a pointer to the buffer in the mapped pool and the length of the buffer, from its
+/// offset in the pool to the end of its last row, and its metadata, which was validated against the size
+/// of the pool when the buffer was created. The pointer is valid for reads of this length during the
+/// call.
+///
+/// The memory is shared with the client, which can write to it at any time, so it is given as a raw
+/// pointer rather than a `&[u8]`, whose content must not change while it is borrowed. Copy the bytes you
+/// need, for example with [`std::ptr::copy_nonoverlapping()`], instead of creating references to them.
+///
+/// If the client truncated the file of the pool, the bytes read by the closure are zeroes, the client is
+/// disconnected and this returns [`BufferAccessError::BadMap`].
+pub fn with_buffer_contents<T, F>(buffer: &WlBuffer, f: F) -> Result<T, BufferAccessError>
+where
+    F: FnOnce(*const u8, usize, BufferMetadata) -> T,
+{
+    let data = buffer.data::<ShmBufferData>().ok_or(BufferAccessError::NotShm)?;
+    let map = data.pool.map.read().unwrap();
+
+    install_sigbus_handler();
+    let previous = ACCESS.with(|access| access.replace(Some((map.ptr as usize, map.len))));
+    let previous_faulted = FAULTED.with(|faulted| faulted.replace(false));
+
+    let metadata = data.metadata;
+    // the buffer was validated to fit in the pool, which can only grow
+    let ptr = unsafe { map.ptr.cast::<u8>().add(metadata.offset as usize) };
+    let result = f(ptr, metadata.len(), metadata);
+
+    ACCESS.with(|access| access.set(previous));
+    let faulted = FAULTED.with(|faulted| faulted.replace(previous_faulted));
+    if faulted {
+        buffer.post_error(wl_shm::Error::InvalidFd, "error accessing the shared memory buffer");
+        return Err(BufferAccessError::BadMap);
+    }
+    Ok(result)
+}
+
+/// A pool mapped in memory
+#[derive(Debug)]
+struct Pool {
+    fd: OwnedFd,
+    map: RwLock<Mapping>,
+}
+
+impl Pool {
+    fn new(fd: OwnedFd, len: usize) -> rustix::io::Result<Self> {
+        let map = Mapping::new(&fd, len)?;
+        Ok(Self { fd, map: RwLock::new(map) })
+    }
+
+    fn len(&self) -> usize {
+        self.map.read().unwrap().len
+    }
+
+    fn resize(&self, len: usize) -> rustix::io::Result<()> {
+        let mut map = self.map.write().unwrap();
+        *map = Mapping::new(&self.fd, len)?;
+        Ok(())
+    }
+}
+
+/// A read-only shared mapping of a file
+#[derive(Debug)]
+struct Mapping {
+    ptr: *mut c_void,
+    len: usize,
+}
+
+// The mapping is never written to, and only unmapped when dropped
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    fn new(fd: &OwnedFd, len: usize) -> rustix::io::Result<Self> {
+        let ptr = unsafe {
+            mmap(ptr::null_mut(), len, ProtFlags::READ, MapFlags::SHARED, fd.as_fd(), 0)
+        }?;
+        Ok(Self { ptr, len })
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        let _ = unsafe { munmap(self.ptr, self.len) };
+    }
+}
+
+thread_local! {
+    /// The address and length of the mapping accessed by this thread
+    static ACCESS: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+    /// Whether the mapping accessed by this thread caused a `SIGBUS`
+    static FAULTED: Cell<bool> = const { Cell::new(false) };
+}
+
+static INSTALL_HANDLER: Once = Once::new();
+static PREVIOUS_HANDLER: AtomicPtr<libc::sigaction> = AtomicPtr::new(ptr::null_mut());
+
+fn install_sigbus_handler() {
+    INSTALL_HANDLER.call_once(|| unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = sigbus_handler as *const () as libc::sighandler_t;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_NODEFER;
+        libc::sigemptyset(&mut action.sa_mask);
+        let previous = Box::into_raw(Box::new(std::mem::zeroed::<libc::sigaction>()));
+        if libc::sigaction(libc::SIGBUS, &action, previous) == 0 {
+            PREVIOUS_HANDLER.store(previous, Ordering::Release);
+        } else {
+            drop(Box::from_raw(previous));
+        }
+    });
+}
+
+extern "C" fn sigbus_handler(
+    signum: libc::c_int,
+    info: *mut libc::siginfo_t,
+    context: *mut c_void,
+) {
+    let address = unsafe { (*info).si_addr() } as usize;
+    let access = ACCESS.try_with(Cell::get).ok().flatten();
+    if let Some((start, len)) = access {
+        if (start..start + len).contains(&address) {
+            // replace the mapping with zeroes, so that the faulting access can be retried
+            let remapped = unsafe {
+                mmap_anonymous(
+                    start as *mut c_void,
+                    len,
+                    ProtFlags::READ,
+                    MapFlags::PRIVATE | MapFlags::FIXED,
+                )
+            };
+            if remapped.is_ok() {
+                let _ = FAULTED.try_with(|faulted| faulted.set(true));
+                return;
+            }
+        }
+    }
+
+    // not caused by a buffer access, forward the signal to the previous handler
+    let previous = PREVIOUS_HANDLER.load(Ordering::Acquire);
+    if previous.is_null() {
+        default_action(signum);
+        return;
+    }
+    let previous = unsafe { &*previous };
+    match previous.sa_sigaction {
+        libc::SIG_DFL => default_action(signum),
+        libc::SIG_IGN => {}
+        handler if previous.sa_flags & libc::SA_SIGINFO != 0 => {
+            let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut c_void) =
+                unsafe { std::mem::transmute(handler) };
+            handler(signum, info, context);
+        }
+        handler => {
+            let handler: extern "C" fn(libc::c_int) = unsafe { std::mem::transmute(handler) };
+            handler(signum);
+        }
+    }
+}
+
+/// Perform the default action of a signal, terminating the process for `SIGBUS`
+fn default_action(signum: libc::c_int) {
+    unsafe {
+        libc::signal(signum, libc::SIG_DFL);
+        libc::raise(signum);
+    }
+}
+
+/// Object data of the buffers whose creation failed, whose client is being disconnected
+struct InertBufferData;
+
+impl<D> crate::backend::ObjectData<D> for InertBufferData {
+    fn request(
+        self: Arc<Self>,
+        _: &crate::backend::Handle,
+        _: &mut D,
+        _: crate::backend::ClientId,
+        _: crate::backend::protocol::Message<crate::backend::ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn crate::backend::ObjectData<D>>> {
+        None
+    }
+
+    fn destroyed(
+        self: Arc<Self>,
+        _: &crate::backend::Handle,
+        _: &mut D,
+        _: crate::backend::ClientId,
+        _: crate::backend::ObjectId,
+    ) {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static FORWARDED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn previous_handler(_: libc::c_int, _: *mut libc::siginfo_t, _: *mut libc::c_void) {
+        FORWARDED.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn sigbus_forwarded() {
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = previous_handler as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_SIGINFO;
+            libc::sigemptyset(&mut action.sa_mask);
+            assert_eq!(libc::sigaction(libc::SIGBUS, &action, std::ptr::null_mut()), 0);
+        }
+        super::install_sigbus_handler();
+
+        // a signal unrelated to buffer accesses is given to the previous handler
+        for _ in 0..2 {
+            FORWARDED.store(false, Ordering::SeqCst);
+            unsafe { libc::raise(libc::SIGBUS) };
+            assert!(FORWARDED.load(Ordering::SeqCst));
+        }
+
+        // which does not replace the handler of this module
+        let current = unsafe {
+            let mut current: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(libc::SIGBUS, std::ptr::null(), &mut current);
+            current
+        };
+        assert_eq!(current.sa_sigaction, super::sigbus_handler as *const () as libc::sighandler_t);
+    }
+}
@@ -187,6 +187,45 @@ fn dead_object_argument() {
     assert!(client_ddata.entered);
 }
 
+#[test]
+fn version_checked_requests() {
+    use wayc::protocol::wl_output;
+
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = ServerHandler { output: None };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let name = client_ddata.globals.list()[0].name;
+    let old_output =
+        registry.bind::<wl_output::WlOutput, _, _>(name, 2, &client.event_queue.handle(), ());
+    let output = client_ddata
+        .globals
+        .bind::<wl_output::WlOutput, _, _>(&client.event_queue.handle(), &registry, 3..4, ())
+        .unwrap();
+
+    assert_eq!(wl_output::REQ_RELEASE_SINCE, 3);
+    assert_eq!(
+        old_output.try_release(),
+        Err(wayc::VersionTooLow { required: wl_output::REQ_RELEASE_SINCE, bound: 2 })
+    );
+    assert_eq!(output.try_release(), Ok(()));
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(wl_output::Event::Done.since(), wl_output::EVT_DONE_SINCE);
+    assert_eq!(wl_output::Event::Name { name: String::new() }.since(), 4);
+}
+
 struct ServerHandler {
     output: Option<ways::protocol::wl_output::WlOutput>,
 }
@@ -0,0 +1,211 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::io::OwnedFd;
+
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+use wayc::protocol::wl_shm::Format;
+use wayc::shm::{MultiBuffer, Shm, ShmError, ShmPool};
+use wayc::Proxy;
+
+use ways::protocol::{wl_buffer, wl_shm, wl_shm_pool};
+use ways::Resource;
+
+#[test]
+fn shm_formats_and_buffers() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, wl_shm::WlShm, _>(1, ());
+    let mut server_ddata = ServerHandler { pools: Vec::new(), buffers: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let name = client_ddata.globals.list().iter().find(|g| g.interface == "wl_shm").unwrap().name;
+    let shm = Shm::bind_from_registry(&registry, name).unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(shm.formats(), [Format::Argb8888, Format::Xrgb8888, Format::Rgb565]);
+    assert!(shm.supports(Format::Rgb565));
+    assert!(!shm.supports(Format::Nv12));
+
+    let mut pool = ShmPool::new(&shm, 16).unwrap();
+    assert!(matches!(
+        pool.create_buffer(2, 2, 8, Format::Nv12),
+        Err(ShmError::UnsupportedFormat(Format::Nv12))
+    ));
+    // the stride is in bytes, it must fit a row of 4-bytes pixels
+    assert!(matches!(pool.create_buffer(4, 2, 8, Format::Argb8888), Err(ShmError::InvalidSize)));
+    assert!(matches!(
+        pool.create_buffer(i32::MAX, 1, i32::MAX, Format::Argb8888),
+        Err(ShmError::InvalidSize)
+    ));
+
+    // fills the initial pool
+    let first = pool.create_buffer(2, 2, 8, Format::Argb8888).unwrap();
+    pool.canvas(&first).unwrap().copy_from_slice(&[1; 16]);
+    // grows it
+    let second = pool.create_buffer(2, 2, 8, Format::Rgb565).unwrap();
+    pool.canvas(&second).unwrap().copy_from_slice(&[2; 16]);
+    assert_eq!(pool.len(), 32);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.buffers.iter().map(|(_, offset)| *offset).collect::<Vec<_>>(), [0, 16]);
+    let mut contents = Vec::new();
+    let mut file = File::from(server_ddata.pools.remove(0));
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(contents[..16], [1; 16]);
+    assert_eq!(contents[16..], [2; 16]);
+
+    // a buffer in use by the server cannot be drawn into, and its space is not reused until released
+    first.attach_to(&surface_stub(&client));
+    assert!(first.is_busy());
+    assert!(pool.canvas(&first).is_none());
+    drop(first);
+    let third = pool.create_buffer(2, 2, 8, Format::Argb8888).unwrap();
+    assert_eq!(pool.len(), 64);
+    drop(third);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    server_ddata.buffers[0].0.release();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let fourth = pool.create_buffer(2, 2, 8, Format::Argb8888).unwrap();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(pool.canvas(&fourth).is_some());
+    assert_eq!(server_ddata.buffers.last().unwrap().1, 0);
+    drop(second);
+}
+
+#[test]
+fn multi_buffer() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, wl_shm::WlShm, _>(1, ());
+    let mut server_ddata = ServerHandler { pools: Vec::new(), buffers: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let name = client_ddata.globals.list().iter().find(|g| g.interface == "wl_shm").unwrap().name;
+    let shm = Shm::bind_from_registry(&registry, name).unwrap();
+    let pool = ShmPool::new(&shm, 64).unwrap();
+    let mut buffers = MultiBuffer::new(pool, 2, 4, 4, 16, Format::Argb8888);
+    let surface = surface_stub(&client);
+
+    let (buffer, canvas) = buffers.next_buffer().unwrap().unwrap();
+    canvas.fill(0xff);
+    buffer.attach_to(&surface);
+    let (buffer, _) = buffers.next_buffer().unwrap().unwrap();
+    buffer.attach_to(&surface);
+    // both buffers are in use
+    assert!(buffers.next_buffer().unwrap().is_none());
+    assert_eq!(buffers.pool().len(), 128);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    server_ddata.buffers[0].0.release();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the first buffer is reused, with its previous content
+    let (buffer, canvas) = buffers.next_buffer().unwrap().unwrap();
+    assert_eq!(canvas, [0xff; 64]);
+    assert_eq!(buffer.wl_buffer().id().protocol_id(), server_ddata.buffers[0].0.id().protocol_id());
+}
+
+/// A surface of a compositor which is not advertised, only used as the target of attach requests
+fn surface_stub(
+    client: &helpers::TestClient<ClientHandler>,
+) -> wayc::protocol::wl_surface::WlSurface {
+    wayc::protocol::wl_surface::WlSurface::inert(client.conn.backend().downgrade())
+}
+
+/*
+ * Server Handler
+ */
+
+struct ServerHandler {
+    pools: Vec<OwnedFd>,
+    buffers: Vec<(wl_buffer::WlBuffer, i32)>,
+}
+
+impl ways::GlobalDispatch<wl_shm::WlShm, ()> for ServerHandler {
+    fn bind(
+        _: &mut Self,
+        _: &ways::DisplayHandle,
+        _: &ways::Client,
+        resource: ways::New<wl_shm::WlShm>,
+        _: &(),
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        let shm = data_init.init(resource, ());
+        shm.format(wl_shm::Format::Argb8888);
+        shm.format(wl_shm::Format::Xrgb8888);
+        shm.format(wl_shm::Format::Rgb565);
+    }
+}
+
+impl ways::Dispatch<wl_shm::WlShm, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_shm::WlShm,
+        request: wl_shm::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let wl_shm::Request::CreatePool { id, fd, .. } = request {
+            data_init.init(id, ());
+            state.pools.push(fd);
+        }
+    }
+}
+
+impl ways::Dispatch<wl_shm_pool::WlShmPool, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_shm_pool::WlShmPool,
+        request: wl_shm_pool::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let wl_shm_pool::Request::CreateBuffer { id, offset, .. } = request {
+            let buffer = data_init.init(id, ());
+            state.buffers.push((buffer, offset));
+        }
+    }
+}
+
+server_ignore_impl!(ServerHandler => [wl_buffer::WlBuffer]);
+
+/*
+ * Client Handler
+ */
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
@@ -0,0 +1,240 @@
+use std::path::Path;
+
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::{wl_compositor as server_compositor, wl_surface as server_surface};
+use ways::shm::ShmState;
+
+use wayc::protocol::{wl_buffer, wl_compositor, wl_shm, wl_surface};
+use wayc::Proxy;
+
+use wayland_cursor::{CursorTheme, CursorThemeConfig};
+
+#[test]
+fn cursor_for_scale() {
+    let (_server, _server_ddata, _, client, _client_ddata, shm, _) = setup();
+    let themes = tempfile::tempdir().unwrap();
+    // the theme provides images of sizes 24 and 48, with the hotspot at the center
+    write_cursor(themes.path(), "left_ptr", &[(24, 12), (48, 24)]);
+
+    let config =
+        CursorThemeConfig::new().name("test").size(24).use_env(false).search_paths([themes.path()]);
+    let mut theme = CursorTheme::load_with_config(&client.conn, shm, &config).unwrap();
+
+    let cursor = theme.get_cursor_for_scale("left_ptr", 2.).unwrap();
+    assert_eq!(cursor[0].dimensions(), (48, 48));
+    assert_eq!(cursor.scale(), 2.);
+    assert_eq!(cursor.buffer_scale(0), Some(2));
+    assert_eq!(cursor.logical_size(0), (24, 24));
+    assert_eq!(cursor.logical_hotspot(0), (12, 12));
+
+    // without images of size 72, the ones of size 48 are displayed with a scale of 2
+    let cursor = theme.get_cursor_for_scale("left_ptr", 3.).unwrap();
+    assert_eq!(cursor[0].dimensions(), (48, 48));
+    assert_eq!(cursor.scale(), 2.);
+    assert_eq!(cursor.buffer_scale(0), Some(2));
+    assert_eq!(cursor.logical_size(0), (24, 24));
+    assert_eq!(cursor.logical_hotspot(0), (12, 12));
+
+    // and the ones of size 24 are the nearest to 30
+    let cursor = theme.get_cursor_for_scale("left_ptr", 1.25).unwrap();
+    assert_eq!(cursor[0].dimensions(), (24, 24));
+    assert_eq!(cursor.scale(), 1.);
+    assert_eq!(cursor.buffer_scale(0), Some(1));
+    assert_eq!(cursor.logical_size(0), (24, 24));
+    assert_eq!(cursor.logical_hotspot(0), (12, 12));
+}
+
+#[test]
+fn cursor_cache_limit() {
+    let (mut server, mut server_ddata, s_client, mut client, mut client_ddata, shm, compositor) =
+        setup();
+    let themes = tempfile::tempdir().unwrap();
+    for name in ["a", "b", "c", "d"] {
+        write_cursor(themes.path(), name, &[(24, 12)]);
+    }
+
+    // room for the images of two cursors
+    let config = CursorThemeConfig::new()
+        .name("test")
+        .size(24)
+        .use_env(false)
+        .search_paths([themes.path()])
+        .cache_limit(2 * 24 * 24 * 4);
+    let mut theme = CursorTheme::load_with_config(&client.conn, shm, &config).unwrap();
+    let surface = compositor.create_surface(&client.event_queue.handle(), ());
+
+    let a = (*theme.get_cursor("a").unwrap()[0]).clone();
+    let b = (*theme.get_cursor("b").unwrap()[0]).clone();
+    let c = (*theme.get_cursor("c").unwrap()[0]).clone();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the least recently used cursor is evicted
+    assert!(!a.is_alive());
+    assert!(b.is_alive());
+    assert!(c.is_alive());
+
+    // a displayed cursor is kept until the server releases it
+    theme.get_cursor("b").unwrap()[0].attach_to(&surface);
+    surface.commit();
+    let d = (*theme.get_cursor("d").unwrap()[0]).clone();
+    let a = (*theme.get_cursor("a").unwrap()[0]).clone();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(b.is_alive());
+    assert!(!c.is_alive());
+    assert!(!d.is_alive());
+    assert!(a.is_alive());
+    assert_eq!(theme.get_cursor("b").unwrap()[0].id(), b.id());
+
+    // and can be evicted once released
+    server_buffer(&server, &s_client, &b).release();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    theme.get_cursor("a").unwrap();
+    let c = (*theme.get_cursor("c").unwrap()[0]).clone();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(a.is_alive());
+    assert!(!b.is_alive());
+    assert!(c.is_alive());
+}
+
+/// Write an XCursor file with square images of the given sizes and hotspots in a `test` theme
+fn write_cursor(themes: &Path, name: &str, images: &[(u32, u32)]) {
+    const IMAGE_TYPE: u32 = 0xfffd_0002;
+    let header_len = 16 + 12 * images.len() as u32;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(b"Xcur");
+    for value in [16, 0x1_0000, images.len() as u32] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    let mut position = header_len;
+    for &(size, _) in images {
+        for value in [IMAGE_TYPE, size, position] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        position += 36 + 4 * size * size;
+    }
+    for &(size, hotspot) in images {
+        for value in [36, IMAGE_TYPE, size, 1, size, size, hotspot, hotspot, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.resize(data.len() + (4 * size * size) as usize, 255);
+    }
+
+    let dir = themes.join("test").join("cursors");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(name), data).unwrap();
+}
+
+type Setup = (
+    TestServer<ServerHandler>,
+    ServerHandler,
+    ways::Client,
+    TestClient<ClientHandler>,
+    ClientHandler,
+    wl_shm::WlShm,
+    wl_compositor::WlCompositor,
+);
+
+fn setup() -> Setup {
+    let mut server = TestServer::new();
+    let shm = ShmState::new::<ServerHandler>(&server.display.handle(), []);
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, server_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata = ServerHandler { shm };
+
+    let (s_client, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let shm = client_ddata
+        .globals
+        .bind::<wl_shm::WlShm, _, _>(&client.event_queue.handle(), &registry, 1..2, ())
+        .unwrap();
+    let compositor = client_ddata
+        .globals
+        .bind::<wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            (),
+        )
+        .unwrap();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    (server, server_ddata, s_client, client, client_ddata, shm, compositor)
+}
+
+fn server_buffer(
+    server: &TestServer<ServerHandler>,
+    client: &ways::Client,
+    buffer: &wl_buffer::WlBuffer,
+) -> ways::protocol::wl_buffer::WlBuffer {
+    client.object_from_protocol_id(&server.display.handle(), buffer.id().protocol_id()).unwrap()
+}
+
+/*
+ * Server Handler
+ */
+
+struct ServerHandler {
+    shm: ShmState,
+}
+
+impl AsMut<ShmState> for ServerHandler {
+    fn as_mut(&mut self) -> &mut ShmState {
+        &mut self.shm
+    }
+}
+
+ways::delegate_shm!(ServerHandler);
+
+server_ignore_global_impl!(ServerHandler => [server_compositor::WlCompositor]);
+server_ignore_impl!(ServerHandler => [server_surface::WlSurface]);
+
+impl ways::Dispatch<server_compositor::WlCompositor, ()> for ServerHandler {
+    fn request(
+        _: &mut Self,
+        _: &ways::Client,
+        _: &server_compositor::WlCompositor,
+        request: server_compositor::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let server_compositor::Request::CreateSurface { id } = request {
+            data_init.init(id, ());
+        }
+    }
+}
+
+/*
+ * Client Handler
+ */
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wl_shm::WlShm,
+    wl_compositor::WlCompositor,
+    wl_surface::WlSurface
+]);
@@ -0,0 +1,132 @@
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+use ways::protocol::{wl_compositor, wl_surface};
+
+#[test]
+fn handler_traits() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, wl_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata = ServerHandler { surfaces: 0, damage: Vec::new(), destroyed: 0 };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: Default::default(), done: None };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            (),
+        )
+        .unwrap();
+
+    let surface = compositor.create_surface(&client.event_queue.handle(), ());
+    surface.damage(1, 2, 3, 4);
+    client.display.sync(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.surfaces, 1);
+    assert_eq!(server_ddata.damage, vec![(1, 2, 3, 4)]);
+    assert!(client_ddata.done.is_some());
+
+    surface.destroy();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.destroyed, 1);
+}
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+    done: Option<u32>,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wayc::protocol::wl_compositor::WlCompositor,
+    wayc::protocol::wl_surface::WlSurface
+]);
+
+impl wayc::protocol::wl_callback::Handler<()> for ClientHandler {
+    fn done(
+        &mut self,
+        _: &wayc::protocol::wl_callback::WlCallback,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+        callback_data: u32,
+    ) {
+        self.done = Some(callback_data);
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_callback::WlCallback: ()] => wayc::protocol::wl_callback::WlCallback
+);
+
+struct ServerHandler {
+    surfaces: usize,
+    damage: Vec<(i32, i32, i32, i32)>,
+    destroyed: usize,
+}
+
+server_ignore_global_impl!(ServerHandler => [wl_compositor::WlCompositor]);
+
+impl wl_compositor::Handler<()> for ServerHandler {
+    fn create_surface(
+        &mut self,
+        _: &ways::Client,
+        _: &wl_compositor::WlCompositor,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+        id: ways::New<wl_surface::WlSurface>,
+    ) {
+        data_init.init(id, ());
+        self.surfaces += 1;
+    }
+}
+
+impl wl_surface::Handler<()> for ServerHandler {
+    fn damage(
+        &mut self,
+        _: &ways::Client,
+        _: &wl_surface::WlSurface,
+        _: &(),
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) {
+        self.damage.push((x, y, width, height));
+    }
+
+    fn destroyed(&mut self, _: ways::backend::ClientId, _: &wl_surface::WlSurface, _: &()) {
+        self.destroyed += 1;
+    }
+}
+
+ways::delegate_dispatch!(ServerHandler:
+    [wl_compositor::WlCompositor: ()] => wl_compositor::WlCompositor
+);
+ways::delegate_dispatch!(ServerHandler: [wl_surface::WlSurface: ()] => wl_surface::WlSurface);
@@ -0,0 +1,136 @@
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+use ways::protocol::{wl_compositor, wl_surface};
+
+#[test]
+fn serialize_messages() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, wl_compositor::WlCompositor, _>(4, ());
+    let mut server_ddata = ServerHandler { requests: Vec::new(), callbacks: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: Default::default(), events: Vec::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            4..5,
+            (),
+        )
+        .unwrap();
+
+    let surface = compositor.create_surface(&client.event_queue.handle(), ());
+    surface.attach(None, 0, 0);
+    surface.damage(1, 2, 3, 4);
+    surface.set_buffer_transform(wayc::protocol::wl_output::Transform::Flipped90);
+    surface.frame(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    for callback in server_ddata.callbacks.drain(..) {
+        callback.done(42);
+    }
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(
+        server_ddata.requests,
+        [
+            r#"{"create_surface":{"id":"wl_surface@4"}}"#,
+            r#"{"attach":{"buffer":null,"x":0,"y":0}}"#,
+            r#"{"damage":{"x":1,"y":2,"width":3,"height":4}}"#,
+            r#"{"set_buffer_transform":{"transform":"flipped_90"}}"#,
+            r#"{"frame":{"callback":"wl_callback@5"}}"#,
+        ]
+    );
+    assert_eq!(client_ddata.events, [r#"{"done":{"callback_data":42}}"#]);
+
+    // requests are serialized before being sent
+    let request = wayc::protocol::wl_surface::Request::Offset { x: -1, y: 1 };
+    assert_eq!(serde_json::to_string(&request).unwrap(), r#"{"offset":{"x":-1,"y":1}}"#);
+}
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+    events: Vec<String>,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wayc::protocol::wl_compositor::WlCompositor,
+    wayc::protocol::wl_surface::WlSurface
+]);
+
+impl wayc::Dispatch<wayc::protocol::wl_callback::WlCallback, ()> for ClientHandler {
+    fn event(
+        state: &mut Self,
+        _: &wayc::protocol::wl_callback::WlCallback,
+        event: wayc::protocol::wl_callback::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        state.events.push(serde_json::to_string(&event).unwrap());
+    }
+}
+
+struct ServerHandler {
+    requests: Vec<String>,
+    callbacks: Vec<ways::protocol::wl_callback::WlCallback>,
+}
+
+server_ignore_global_impl!(ServerHandler => [wl_compositor::WlCompositor]);
+
+impl ways::Dispatch<wl_compositor::WlCompositor, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_compositor::WlCompositor,
+        request: wl_compositor::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        state.requests.push(serde_json::to_string(&request).unwrap());
+        if let wl_compositor::Request::CreateSurface { id } = request {
+            data_init.init(id, ());
+        }
+    }
+}
+
+impl ways::Dispatch<wl_surface::WlSurface, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_surface::WlSurface,
+        request: wl_surface::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        state.requests.push(serde_json::to_string(&request).unwrap());
+        if let wl_surface::Request::Frame { callback } = request {
+            state.callbacks.push(data_init.init(callback, ()));
+        }
+    }
+}
+
+server_ignore_impl!(ServerHandler => [ways::protocol::wl_callback::WlCallback]);
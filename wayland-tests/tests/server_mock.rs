@@ -0,0 +1,168 @@
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+use ways::Resource;
+
+use mock_protocol::mock::{MockRequest, MockState};
+
+mod mock_protocol {
+    use self::__interfaces::*;
+    use crate::helpers::ways as wayland_server;
+    pub mod __interfaces {
+        wayland_scanner::generate_interfaces!("../wayland-server/wayland.xml");
+    }
+    wayland_scanner::generate_server_code!("../wayland-server/wayland.xml", mock = true);
+}
+
+// a mock of wl_compositor alone, whose surfaces and regions are foreign objects
+mod compositor_mock {
+    use crate::helpers::ways as wayland_server;
+    use crate::mock_protocol::__interfaces::*;
+    wayland_scanner::generate_server_code!(
+        "../wayland-server/wayland.xml",
+        mock = true,
+        only = [wl_compositor],
+        extern = { wl_surface => crate::mock_protocol, wl_region => crate::mock_protocol }
+    );
+}
+
+use mock_protocol::{wl_compositor, wl_surface};
+
+#[test]
+fn mock_records_requests() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<MockState, wl_compositor::WlCompositor, _>(6, ());
+    let mut server_ddata = MockState::new();
+    server_ddata.on_request(|dh, recorded| {
+        if let MockRequest::WlCompositor(wl_compositor::Request::CreateSurface { .. }) =
+            recorded.request
+        {
+            let surface = wl_surface::WlSurface::from_id(dh, recorded.created[0].clone()).unwrap();
+            surface.preferred_buffer_scale(2);
+        }
+    });
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: Default::default(), scales: Vec::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            6..7,
+            (),
+        )
+        .unwrap();
+    let surface = compositor.create_surface(&client.event_queue.handle(), ());
+    surface.damage(1, 2, 3, 4);
+    surface.commit();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(client_ddata.scales, [2]);
+    assert_eq!(server_ddata.bound.len(), 1);
+
+    let log = server_ddata.take_log();
+    assert_eq!(log.len(), 3);
+    assert!(server_ddata.log.is_empty());
+
+    assert_eq!(log[0].resource, server_ddata.bound[0]);
+    assert!(matches!(
+        log[0].request,
+        MockRequest::WlCompositor(wl_compositor::Request::CreateSurface { .. })
+    ));
+    assert_eq!(log[0].created.len(), 1);
+
+    assert_eq!(log[1].resource, log[0].created[0]);
+    assert!(matches!(
+        log[1].request,
+        MockRequest::WlSurface(wl_surface::Request::Damage { x: 1, y: 2, width: 3, height: 4 })
+    ));
+    assert!(log[1].created.is_empty());
+
+    assert_eq!(log[2].resource, log[0].created[0]);
+    assert!(matches!(log[2].request, MockRequest::WlSurface(wl_surface::Request::Commit)));
+}
+
+#[test]
+fn mock_records_foreign_requests() {
+    use compositor_mock::mock::{MockRequest, MockState};
+
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<MockState, compositor_mock::wl_compositor::WlCompositor, _>(6, ());
+    let mut server_ddata = MockState::new();
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: Default::default(), scales: Vec::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            6..7,
+            (),
+        )
+        .unwrap();
+    let surface = compositor.create_surface(&client.event_queue.handle(), ());
+    surface.commit();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let log = server_ddata.take_log();
+    assert_eq!(log.len(), 2);
+    assert!(matches!(
+        log[0].request,
+        MockRequest::WlCompositor(compositor_mock::wl_compositor::Request::CreateSurface { .. })
+    ));
+    assert_eq!(log[1].resource, log[0].created[0]);
+    assert!(matches!(log[1].request, MockRequest::Foreign { interface: "wl_surface", opcode: 6 }));
+}
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+    scales: Vec<i32>,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wayc::protocol::wl_compositor::WlCompositor
+]);
+
+impl wayc::Dispatch<wayc::protocol::wl_surface::WlSurface, ()> for ClientHandler {
+    fn event(
+        state: &mut Self,
+        _: &wayc::protocol::wl_surface::WlSurface,
+        event: wayc::protocol::wl_surface::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        if let wayc::protocol::wl_surface::Event::PreferredBufferScale { factor } = event {
+            state.scales.push(factor);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+use ways::protocol::{wl_compositor, wl_region};
+use ways::ResourceStates;
+
+#[test]
+fn resource_states() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, wl_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata =
+        ServerHandler { regions: ResourceStates::new(), destroyed_with_state: false };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            (),
+        )
+        .unwrap();
+
+    let region = compositor.create_region(&client.event_queue.handle(), ());
+    region.add(0, 0, 10, 10);
+    region.add(10, 10, 20, 20);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.regions.len(), 1);
+    let (_, rects) = server_ddata.regions.iter().next().unwrap();
+    assert_eq!(rects, &vec![(0, 0, 10, 10), (10, 10, 20, 20)]);
+
+    region.destroy();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the state was still available in the destructor, and removed afterwards
+    assert!(server_ddata.destroyed_with_state);
+    assert!(server_ddata.regions.is_empty());
+}
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+}
+
+impl ClientHandler {
+    fn new() -> ClientHandler {
+        ClientHandler { globals: Default::default() }
+    }
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wayc::protocol::wl_compositor::WlCompositor,
+    wayc::protocol::wl_region::WlRegion
+]);
+
+struct ServerHandler {
+    regions: ResourceStates<wl_region::WlRegion, Vec<(i32, i32, i32, i32)>>,
+    destroyed_with_state: bool,
+}
+
+impl AsMut<ResourceStates<wl_region::WlRegion, Vec<(i32, i32, i32, i32)>>> for ServerHandler {
+    fn as_mut(&mut self) -> &mut ResourceStates<wl_region::WlRegion, Vec<(i32, i32, i32, i32)>> {
+        &mut self.regions
+    }
+}
+
+server_ignore_global_impl!(ServerHandler => [wl_compositor::WlCompositor]);
+
+impl ways::Dispatch<wl_compositor::WlCompositor, ()> for ServerHandler {
+    fn request(
+        _: &mut Self,
+        _: &ways::Client,
+        _: &wl_compositor::WlCompositor,
+        request: wl_compositor::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let wl_compositor::Request::CreateRegion { id } = request {
+            data_init.init_with_state(id, (), Vec::new());
+        }
+    }
+}
+
+impl ways::Dispatch<wl_region::WlRegion, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        region: &wl_region::WlRegion,
+        request: wl_region::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let wl_region::Request::Add { x, y, width, height } = request {
+            state.regions.get_mut(region).unwrap().push((x, y, width, height));
+        }
+    }
+
+    fn destroyed(
+        state: &mut Self,
+        _: ways::backend::ClientId,
+        region: &wl_region::WlRegion,
+        _: &(),
+    ) {
+        state.destroyed_with_state = state.regions.contains(region);
+    }
+}
@@ -199,6 +199,63 @@ fn get_resource() {
         .is_ok());
 }
 
+#[test]
+fn broadcast() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = ServerHandler { outputs: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // create two outputs
+    for _ in 0..2 {
+        client_ddata
+            .globals
+            .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+                &client.event_queue.handle(),
+                &registry,
+                3..4,
+                (),
+            )
+            .unwrap();
+    }
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let handle = server.display.handle();
+
+    // wl_output.done is available since version 2
+    let sent = handle.broadcast::<wl_output::WlOutput>(|_, _| true, |_| wl_output::Event::Done);
+    assert_eq!(sent, 2);
+
+    // the filter can exclude resources
+    let first = server_ddata.outputs[0].clone();
+    let sent = handle
+        .broadcast::<wl_output::WlOutput>(|_, output| output != &first, |_| wl_output::Event::Done);
+    assert_eq!(sent, 1);
+
+    // wl_output.name is only available since version 4
+    let sent = handle.broadcast::<wl_output::WlOutput>(
+        |_, _| true,
+        |_| wl_output::Event::Name { name: "output".into() },
+    );
+    assert_eq!(sent, 0);
+
+    // resources of other interfaces are not visited
+    let sent = handle.broadcast::<wl_compositor::WlCompositor>(|_, _| panic!(), |_| unreachable!());
+    assert_eq!(sent, 0);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+}
+
 struct ClientHandler {
     globals: globals::GlobalList,
 }
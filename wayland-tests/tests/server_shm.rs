@@ -0,0 +1,226 @@
+use std::fs::File;
+use std::io::Write;
+use std::os::unix::io::AsFd;
+
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::wl_buffer::WlBuffer;
+use ways::protocol::wl_shm::Format;
+use ways::shm::{with_buffer_contents, BufferAccessError, BufferMetadata, ShmState};
+
+use wayc::protocol::{wl_buffer, wl_shm, wl_shm_pool};
+use wayc::Proxy;
+
+#[test]
+fn shm_buffer_contents() {
+    let (mut server, mut server_ddata, s_client, mut client, mut client_ddata, shm) = setup();
+    assert_eq!(client_ddata.formats, [Format::Argb8888, Format::Xrgb8888, Format::Rgb565]);
+
+    let file = pool_file(&(0..32).collect::<Vec<u8>>());
+    let pool = shm.create_pool(file.as_fd(), 32, &client.event_queue.handle(), ());
+    let buffer =
+        pool.create_buffer(8, 2, 2, 4, wl_shm::Format::Rgb565, &client.event_queue.handle(), ());
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let buffer = server_buffer(&server, &s_client, &buffer);
+    let contents = with_buffer_contents(&buffer, |ptr, len, metadata| (read(ptr, len), metadata));
+    assert_eq!(
+        contents,
+        Ok((
+            (8..16).collect(),
+            BufferMetadata { offset: 8, width: 2, height: 2, stride: 4, format: Format::Rgb565 }
+        ))
+    );
+
+    // buffers created after a resize can use the new space
+    file.set_len(96).unwrap();
+    pool.resize(96);
+    let buffer = pool.create_buffer(
+        32,
+        4,
+        4,
+        16,
+        wl_shm::Format::Argb8888,
+        &client.event_queue.handle(),
+        (),
+    );
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let buffer = server_buffer(&server, &s_client, &buffer);
+    assert_eq!(with_buffer_contents(&buffer, |_, len, _| len), Ok(64));
+}
+
+#[test]
+fn shm_invalid_buffers() {
+    let invalid_buffers = [
+        // unsupported format
+        (0, 2, 2, 8, wl_shm::Format::Nv12, wl_shm::Error::InvalidFormat),
+        // stride smaller than a row
+        (0, 2, 2, 4, wl_shm::Format::Argb8888, wl_shm::Error::InvalidStride),
+        // outside of the pool
+        (16, 2, 2, 8, wl_shm::Format::Argb8888, wl_shm::Error::InvalidStride),
+        (-4, 1, 1, 4, wl_shm::Format::Argb8888, wl_shm::Error::InvalidStride),
+        (0, 0, 2, 8, wl_shm::Format::Argb8888, wl_shm::Error::InvalidStride),
+    ];
+
+    for (offset, width, height, stride, format, error) in invalid_buffers {
+        let (mut server, mut server_ddata, _, mut client, mut client_ddata, shm) = setup();
+        let file = pool_file(&[0; 16]);
+        let pool = shm.create_pool(file.as_fd(), 16, &client.event_queue.handle(), ());
+        pool.create_buffer(offset, width, height, stride, format, &client.event_queue.handle(), ());
+
+        assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).is_err());
+        let protocol_error = client.conn.protocol_error().unwrap();
+        assert_eq!(protocol_error.code, error as u32);
+        assert_eq!(protocol_error.object_id, pool.id().protocol_id());
+    }
+}
+
+#[test]
+fn shm_pool_cannot_shrink() {
+    let (mut server, mut server_ddata, _, mut client, mut client_ddata, shm) = setup();
+    let file = pool_file(&[0; 16]);
+    let pool = shm.create_pool(file.as_fd(), 16, &client.event_queue.handle(), ());
+    pool.resize(8);
+
+    assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).is_err());
+    let protocol_error = client.conn.protocol_error().unwrap();
+    assert_eq!(protocol_error.code, wl_shm::Error::InvalidStride as u32);
+}
+
+#[test]
+fn shm_truncated_pool() {
+    let (mut server, mut server_ddata, s_client, mut client, mut client_ddata, shm) = setup();
+    let file = pool_file(&[1; 8192]);
+    let pool = shm.create_pool(file.as_fd(), 8192, &client.event_queue.handle(), ());
+    let buffer = pool.create_buffer(
+        4096,
+        16,
+        16,
+        64,
+        wl_shm::Format::Argb8888,
+        &client.event_queue.handle(),
+        (),
+    );
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the client truncates the file, reading the buffer would raise a SIGBUS
+    file.set_len(0).unwrap();
+    let buffer = server_buffer(&server, &s_client, &buffer);
+    let result = with_buffer_contents(&buffer, |ptr, len, _| {
+        read(ptr, len).iter().map(|&b| b as u32).sum::<u32>()
+    });
+    assert_eq!(result, Err(BufferAccessError::BadMap));
+
+    assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).is_err());
+    let protocol_error = client.conn.protocol_error().unwrap();
+    assert_eq!(protocol_error.code, wl_shm::Error::InvalidFd as u32);
+}
+
+type Setup = (
+    TestServer<ServerHandler>,
+    ServerHandler,
+    ways::Client,
+    TestClient<ClientHandler>,
+    ClientHandler,
+    wl_shm::WlShm,
+);
+
+fn setup() -> Setup {
+    let mut server = TestServer::new();
+    let shm = ShmState::new::<ServerHandler>(&server.display.handle(), [Format::Rgb565]);
+    let mut server_ddata = ServerHandler { shm };
+
+    let (s_client, mut client) = server.add_client();
+    let mut client_ddata =
+        ClientHandler { globals: globals::GlobalList::new(), formats: Vec::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let shm = client_ddata
+        .globals
+        .bind::<wl_shm::WlShm, _, _>(&client.event_queue.handle(), &registry, 1..2, ())
+        .unwrap();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    (server, server_ddata, s_client, client, client_ddata, shm)
+}
+
+/// Copy the content of a buffer
+fn read(ptr: *const u8, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    unsafe { std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), len) };
+    bytes
+}
+
+fn pool_file(contents: &[u8]) -> File {
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(contents).unwrap();
+    file
+}
+
+fn server_buffer(
+    server: &TestServer<ServerHandler>,
+    client: &ways::Client,
+    buffer: &wl_buffer::WlBuffer,
+) -> WlBuffer {
+    client
+        .object_from_protocol_id::<WlBuffer>(&server.display.handle(), buffer.id().protocol_id())
+        .unwrap()
+}
+
+/*
+ * Server Handler
+ */
+
+struct ServerHandler {
+    shm: ShmState,
+}
+
+impl AsMut<ShmState> for ServerHandler {
+    fn as_mut(&mut self) -> &mut ShmState {
+        &mut self.shm
+    }
+}
+
+ways::delegate_shm!(ServerHandler);
+
+/*
+ * Client Handler
+ */
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+    formats: Vec<Format>,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+impl wayc::Dispatch<wl_shm::WlShm, ()> for ClientHandler {
+    fn event(
+        state: &mut Self,
+        _: &wl_shm::WlShm,
+        event: wl_shm::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        if let wl_shm::Event::Format { format: wayc::WEnum::Value(format) } = event {
+            state.formats.push(Format::try_from(format as u32).unwrap());
+        }
+    }
+}
+
+client_ignore_impl!(ClientHandler => [wl_shm_pool::WlShmPool, wl_buffer::WlBuffer]);
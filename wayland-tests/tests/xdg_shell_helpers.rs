@@ -0,0 +1,518 @@
+use std::fs::File;
+use std::os::unix::io::AsFd;
+use std::time::Duration;
+
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestClient, TestServer};
+
+use wayland_protocols::server_helpers::xdg_shell::{
+    Configure, ToplevelConfigure, XdgCommit, XdgShellHandler, XdgShellState,
+};
+use wayland_protocols::xdg::shell::{client as xs_client, server as xs_server};
+
+use wayc::protocol::{wl_buffer, wl_compositor, wl_shm, wl_shm_pool, wl_surface};
+use wayc::Proxy;
+use ways::shm::ShmState;
+
+#[test]
+fn toplevel_configure_sequence() {
+    let (mut server, mut server_ddata, s_client, mut client, mut client_ddata, objects) = setup(5);
+    let qh = client.event_queue.handle();
+
+    let xdg_surface = objects.wm_base.get_xdg_surface(&objects.surface, &qh, ());
+    let toplevel = xdg_surface.get_toplevel(&qh, ());
+    toplevel.set_title("test".into());
+    toplevel.set_min_size(10, 10);
+    objects.surface.commit();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.commits, [XdgCommit::Initial]);
+    let s_toplevel = server_ddata.toplevels[0].clone();
+    let state = server_ddata.xdg.toplevel_state(&s_toplevel).unwrap();
+    assert_eq!(state.title.as_deref(), Some("test"));
+    assert_eq!(state.min_size, (10, 10));
+
+    // the duplicated state and the state of version 6 are not sent
+    let configure = ToplevelConfigure {
+        size: (100, 50),
+        states: vec![
+            xs_server::xdg_toplevel::State::Activated,
+            xs_server::xdg_toplevel::State::Activated,
+            xs_server::xdg_toplevel::State::Suspended,
+        ],
+        bounds: Some((800, 600)),
+    };
+    let serial = server_ddata.xdg.send_toplevel_configure(&s_toplevel, configure).unwrap();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(client_ddata.configures, [serial]);
+    assert_eq!(client_ddata.toplevel_configures, [(100, 50, 4u32.to_ne_bytes().to_vec())]);
+    assert_eq!(client_ddata.bounds, [(800, 600)]);
+
+    xdg_surface.ack_configure(serial);
+    objects.surface.attach(Some(&objects.buffer), 0, 0);
+    objects.surface.commit();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let expected = Configure::Toplevel(ToplevelConfigure {
+        size: (100, 50),
+        states: vec![xs_server::xdg_toplevel::State::Activated],
+        bounds: Some((800, 600)),
+    });
+    assert_eq!(server_ddata.commits, [XdgCommit::Initial, XdgCommit::Mapped]);
+    let s_xdg_surface =
+        server_ddata.xdg.xdg_surface(&server_surface(&server, &s_client, &objects)).unwrap();
+    assert_eq!(server_ddata.xdg.current_configure(&s_xdg_surface), Some(&expected));
+    assert!(server_ddata.xdg.is_mapped(&s_xdg_surface));
+    assert_eq!(server_ddata.acked, [expected]);
+
+    // unmapping requires a new initial commit
+    objects.surface.attach(None, 0, 0);
+    objects.surface.commit();
+    objects.surface.commit();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(
+        server_ddata.commits,
+        [XdgCommit::Initial, XdgCommit::Mapped, XdgCommit::Unmapped, XdgCommit::Initial]
+    );
+    assert!(!server_ddata.xdg.is_mapped(&s_xdg_surface));
+    assert_eq!(server_ddata.xdg.current_configure(&s_xdg_surface), None);
+}
+
+#[test]
+fn toplevel_recreated() {
+    let (mut server, mut server_ddata, _, mut client, mut client_ddata, objects) = setup(6);
+    let qh = client.event_queue.handle();
+
+    let xdg_surface = objects.wm_base.get_xdg_surface(&objects.surface, &qh, ());
+    let toplevel = xdg_surface.get_toplevel(&qh, ());
+    objects.surface.commit();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the surface can get a new role object of the same kind
+    toplevel.destroy();
+    xdg_surface.get_toplevel(&qh, ());
+    objects.surface.commit();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.commits, [XdgCommit::Initial, XdgCommit::Initial]);
+    assert_eq!(server_ddata.toplevels.len(), 2);
+}
+
+#[test]
+fn pending_configures_limit() {
+    let (mut server, mut server_ddata, _, mut client, mut client_ddata, objects) = setup(6);
+    let qh = client.event_queue.handle();
+
+    let xdg_surface = objects.wm_base.get_xdg_surface(&objects.surface, &qh, ());
+    xdg_surface.get_toplevel(&qh, ());
+    objects.surface.commit();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let s_toplevel = server_ddata.toplevels[0].clone();
+    let configure = |i| ToplevelConfigure { size: (i, i), states: Vec::new(), bounds: None };
+    let serials = (1..=40)
+        .map(|i| server_ddata.xdg.send_toplevel_configure(&s_toplevel, configure(i)).unwrap())
+        .collect::<Vec<_>>();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the acknowledgement of a forgotten configure is ignored
+    xdg_surface.ack_configure(serials[0]);
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(server_ddata.acked.is_empty());
+
+    xdg_surface.ack_configure(serials[20]);
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert_eq!(server_ddata.acked, [Configure::Toplevel(configure(21))]);
+}
+
+#[test]
+fn ping_timeout() {
+    let (mut server, mut server_ddata, s_client, mut client, mut client_ddata, objects) = setup(6);
+
+    let wm_base = s_client
+        .object_from_protocol_id::<xs_server::xdg_wm_base::XdgWmBase>(
+            &server.display.handle(),
+            objects.wm_base.id().protocol_id(),
+        )
+        .unwrap();
+    let serial = server_ddata.xdg.ping(&wm_base).unwrap();
+    // a pending ping is not sent again
+    assert_eq!(server_ddata.xdg.ping(&wm_base), Some(serial));
+    assert!(server_ddata.xdg.unresponsive(Duration::ZERO).contains(&wm_base));
+    assert!(server_ddata.xdg.unresponsive(Duration::from_secs(3600)).is_empty());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(client_ddata.pings, [serial]);
+    assert!(server_ddata.xdg.unresponsive(Duration::ZERO).is_empty());
+    assert_ne!(server_ddata.xdg.ping(&wm_base), Some(serial));
+}
+
+#[test]
+fn xdg_shell_protocol_errors() {
+    type Action = fn(&Objects, &wayc::QueueHandle<ClientHandler>);
+    let cases: [(Action, &str, u32); 12] = [
+        (
+            |o, qh| o.wm_base.get_xdg_surface(&o.surface, qh, ()).ack_configure(1),
+            "xdg_surface",
+            xs_client::xdg_surface::Error::NotConstructed as u32,
+        ),
+        (
+            |o, qh| {
+                o.wm_base.get_xdg_surface(&o.surface, qh, ());
+                o.wm_base.get_xdg_surface(&o.surface, qh, ());
+            },
+            "xdg_wm_base",
+            xs_client::xdg_wm_base::Error::Role as u32,
+        ),
+        (
+            |o, qh| {
+                o.wm_base.get_xdg_surface(&o.surface, qh, ()).get_toplevel(qh, ());
+                o.surface.attach(Some(&o.buffer), 0, 0);
+                o.surface.commit();
+            },
+            "xdg_surface",
+            xs_client::xdg_surface::Error::UnconfiguredBuffer as u32,
+        ),
+        (
+            |o, qh| {
+                let xdg_surface = o.wm_base.get_xdg_surface(&o.surface, qh, ());
+                xdg_surface.get_toplevel(qh, ());
+                xdg_surface.ack_configure(42);
+            },
+            "xdg_surface",
+            xs_client::xdg_surface::Error::InvalidSerial as u32,
+        ),
+        (
+            |o, qh| {
+                let xdg_surface = o.wm_base.get_xdg_surface(&o.surface, qh, ());
+                xdg_surface.get_toplevel(qh, ());
+                xdg_surface.get_toplevel(qh, ());
+            },
+            "xdg_surface",
+            xs_client::xdg_surface::Error::AlreadyConstructed as u32,
+        ),
+        (
+            |o, qh| {
+                let xdg_surface = o.wm_base.get_xdg_surface(&o.surface, qh, ());
+                xdg_surface.get_toplevel(qh, ()).destroy();
+                let positioner = o.wm_base.create_positioner(qh, ());
+                positioner.set_size(10, 10);
+                positioner.set_anchor_rect(0, 0, 10, 10);
+                xdg_surface.get_popup(None, &positioner, qh, ());
+            },
+            "xdg_wm_base",
+            xs_client::xdg_wm_base::Error::Role as u32,
+        ),
+        (
+            |o, qh| {
+                let xdg_surface = o.wm_base.get_xdg_surface(&o.surface, qh, ());
+                xdg_surface.get_toplevel(qh, ());
+                xdg_surface.destroy();
+            },
+            "xdg_surface",
+            xs_client::xdg_surface::Error::DefunctRoleObject as u32,
+        ),
+        (
+            |o, qh| {
+                o.wm_base.get_xdg_surface(&o.surface, qh, ());
+                o.wm_base.destroy();
+            },
+            "xdg_wm_base",
+            xs_client::xdg_wm_base::Error::DefunctSurfaces as u32,
+        ),
+        (
+            |o, qh| {
+                let positioner = o.wm_base.create_positioner(qh, ());
+                positioner.set_size(10, 10);
+                o.wm_base.get_xdg_surface(&o.surface, qh, ()).get_popup(None, &positioner, qh, ());
+            },
+            "xdg_wm_base",
+            xs_client::xdg_wm_base::Error::InvalidPositioner as u32,
+        ),
+        (
+            |o, qh| o.wm_base.create_positioner(qh, ()).set_size(0, 10),
+            "xdg_positioner",
+            xs_client::xdg_positioner::Error::InvalidInput as u32,
+        ),
+        (
+            |o, qh| {
+                let toplevel = o.wm_base.get_xdg_surface(&o.surface, qh, ()).get_toplevel(qh, ());
+                toplevel.set_min_size(20, 20);
+                toplevel.set_max_size(10, 10);
+                o.surface.commit();
+            },
+            "xdg_toplevel",
+            xs_client::xdg_toplevel::Error::InvalidSize as u32,
+        ),
+        (
+            |o, qh| {
+                let toplevel = o.wm_base.get_xdg_surface(&o.surface, qh, ()).get_toplevel(qh, ());
+                toplevel.set_parent(Some(&toplevel));
+            },
+            "xdg_toplevel",
+            xs_client::xdg_toplevel::Error::InvalidParent as u32,
+        ),
+    ];
+
+    for (action, interface, code) in cases {
+        let (mut server, mut server_ddata, _, mut client, mut client_ddata, objects) = setup(6);
+        action(&objects, &client.event_queue.handle());
+
+        assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).is_err());
+        let error = client.conn.protocol_error().unwrap();
+        assert_eq!((error.object_interface.as_str(), error.code), (interface, code));
+    }
+}
+
+struct Objects {
+    wm_base: xs_client::xdg_wm_base::XdgWmBase,
+    surface: wl_surface::WlSurface,
+    buffer: wl_buffer::WlBuffer,
+    _file: File,
+}
+
+type Setup = (
+    TestServer<ServerHandler>,
+    ServerHandler,
+    ways::Client,
+    TestClient<ClientHandler>,
+    ClientHandler,
+    Objects,
+);
+
+fn setup(xdg_version: u32) -> Setup {
+    let mut server = TestServer::new();
+    let dh = server.display.handle();
+    dh.create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata = ServerHandler {
+        xdg: XdgShellState::new::<ServerHandler>(&dh),
+        shm: ShmState::new::<ServerHandler>(&dh, []),
+        buffers: Vec::new(),
+        commits: Vec::new(),
+        toplevels: Vec::new(),
+        acked: Vec::new(),
+    };
+
+    let (s_client, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler {
+        globals: globals::GlobalList::new(),
+        configures: Vec::new(),
+        toplevel_configures: Vec::new(),
+        bounds: Vec::new(),
+        pings: Vec::new(),
+    };
+
+    let qh = client.event_queue.handle();
+    let registry = client.display.get_registry(&qh, ());
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wl_compositor::WlCompositor, _, _>(&qh, &registry, 1..2, ())
+        .unwrap();
+    let shm = client_ddata.globals.bind::<wl_shm::WlShm, _, _>(&qh, &registry, 1..2, ()).unwrap();
+    let wm_base_name =
+        client_ddata.globals.list().iter().find(|g| g.interface == "xdg_wm_base").unwrap().name;
+    let wm_base = registry.bind::<xs_client::xdg_wm_base::XdgWmBase, _, _>(
+        wm_base_name,
+        xdg_version,
+        &qh,
+        (),
+    );
+
+    let file = tempfile::tempfile().unwrap();
+    file.set_len(64).unwrap();
+    let pool = shm.create_pool(file.as_fd(), 64, &qh, ());
+    let buffer = pool.create_buffer(0, 4, 4, 16, wl_shm::Format::Argb8888, &qh, ());
+    let surface = compositor.create_surface(&qh, ());
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let objects = Objects { wm_base, surface, buffer, _file: file };
+    (server, server_ddata, s_client, client, client_ddata, objects)
+}
+
+fn server_surface(
+    server: &TestServer<ServerHandler>,
+    client: &ways::Client,
+    objects: &Objects,
+) -> ways::protocol::wl_surface::WlSurface {
+    client
+        .object_from_protocol_id(&server.display.handle(), objects.surface.id().protocol_id())
+        .unwrap()
+}
+
+/*
+ * Server Handler
+ */
+
+struct ServerHandler {
+    xdg: XdgShellState,
+    shm: ShmState,
+    // the surfaces with a buffer attached
+    buffers: Vec<ways::protocol::wl_surface::WlSurface>,
+    commits: Vec<XdgCommit>,
+    toplevels: Vec<xs_server::xdg_toplevel::XdgToplevel>,
+    acked: Vec<Configure>,
+}
+
+impl AsMut<XdgShellState> for ServerHandler {
+    fn as_mut(&mut self) -> &mut XdgShellState {
+        &mut self.xdg
+    }
+}
+
+impl AsMut<ShmState> for ServerHandler {
+    fn as_mut(&mut self) -> &mut ShmState {
+        &mut self.shm
+    }
+}
+
+impl XdgShellHandler for ServerHandler {
+    fn new_toplevel(&mut self, toplevel: &xs_server::xdg_toplevel::XdgToplevel) {
+        self.toplevels.push(toplevel.clone());
+    }
+
+    fn new_popup(&mut self, _: &xs_server::xdg_popup::XdgPopup) {}
+
+    fn ack_configure(&mut self, _: &xs_server::xdg_surface::XdgSurface, configure: Configure) {
+        self.acked.push(configure);
+    }
+}
+
+wayland_protocols::delegate_xdg_shell!(ServerHandler);
+ways::delegate_shm!(ServerHandler);
+
+server_ignore_global_impl!(ServerHandler => [ways::protocol::wl_compositor::WlCompositor]);
+
+impl ways::Dispatch<ways::protocol::wl_compositor::WlCompositor, ()> for ServerHandler {
+    fn request(
+        _: &mut Self,
+        _: &ways::Client,
+        _: &ways::protocol::wl_compositor::WlCompositor,
+        request: ways::protocol::wl_compositor::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let ways::protocol::wl_compositor::Request::CreateSurface { id } = request {
+            data_init.init(id, ());
+        }
+    }
+}
+
+impl ways::Dispatch<ways::protocol::wl_surface::WlSurface, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        surface: &ways::protocol::wl_surface::WlSurface,
+        request: ways::protocol::wl_surface::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+    ) {
+        match request {
+            ways::protocol::wl_surface::Request::Attach { buffer, .. } => {
+                state.buffers.retain(|s| s != surface);
+                if buffer.is_some() {
+                    state.buffers.push(surface.clone());
+                }
+            }
+            ways::protocol::wl_surface::Request::Commit => {
+                let has_buffer = state.buffers.contains(surface);
+                let commit = state.xdg.commit(surface, has_buffer);
+                state.commits.push(commit);
+            }
+            _ => {}
+        }
+    }
+}
+
+/*
+ * Client Handler
+ */
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+    configures: Vec<u32>,
+    toplevel_configures: Vec<(i32, i32, Vec<u8>)>,
+    bounds: Vec<(i32, i32)>,
+    pings: Vec<u32>,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wl_compositor::WlCompositor,
+    wl_surface::WlSurface,
+    wl_shm::WlShm,
+    wl_shm_pool::WlShmPool,
+    wl_buffer::WlBuffer,
+    xs_client::xdg_positioner::XdgPositioner,
+    xs_client::xdg_popup::XdgPopup
+]);
+
+impl wayc::Dispatch<xs_client::xdg_wm_base::XdgWmBase, ()> for ClientHandler {
+    fn event(
+        state: &mut Self,
+        wm_base: &xs_client::xdg_wm_base::XdgWmBase,
+        event: xs_client::xdg_wm_base::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        if let xs_client::xdg_wm_base::Event::Ping { serial } = event {
+            state.pings.push(serial);
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl wayc::Dispatch<xs_client::xdg_surface::XdgSurface, ()> for ClientHandler {
+    fn event(
+        state: &mut Self,
+        _: &xs_client::xdg_surface::XdgSurface,
+        event: xs_client::xdg_surface::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        if let xs_client::xdg_surface::Event::Configure { serial } = event {
+            state.configures.push(serial);
+        }
+    }
+}
+
+impl wayc::Dispatch<xs_client::xdg_toplevel::XdgToplevel, ()> for ClientHandler {
+    fn event(
+        state: &mut Self,
+        _: &xs_client::xdg_toplevel::XdgToplevel,
+        event: xs_client::xdg_toplevel::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        match event {
+            xs_client::xdg_toplevel::Event::Configure { width, height, states } => {
+                state.toplevel_configures.push((width, height, states));
+            }
+            xs_client::xdg_toplevel::Event::ConfigureBounds { width, height } => {
+                state.bounds.push((width, height));
+            }
+            _ => {}
+        }
+    }
+}